@@ -0,0 +1,97 @@
+//! Throughput comparison between the legacy recursive generator and the
+//! iterative odometer. Run with `cargo bench`.
+
+use crunch_rs::charset::Charset;
+use crunch_rs::filter::Filters;
+use crunch_rs::generator::generate_all_combinations;
+use crunch_rs::progress::Progress;
+use crunch_rs::LineEnding;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+const CHARSET: &str = "abcdefghijklmnopqrstuvwxyz";
+const LENGTH: usize = 5;
+
+/// The original per-word progress counter, kept so the baseline pays what it used to.
+struct LegacyProgress {
+    current: AtomicU64,
+    total: u64,
+    last_percentage: AtomicU64,
+}
+
+impl LegacyProgress {
+    fn increment(&self) {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        let percentage = (current as f64 / self.total as f64 * 100.0) as u64;
+        let last_percentage = self.last_percentage.load(Ordering::SeqCst);
+
+        if percentage >= last_percentage + 5 {
+            self.last_percentage.store(percentage, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The pre-odometer implementation, kept here as the baseline.
+fn recursive<W: Write>(
+    current: &mut String,
+    length: usize,
+    charset: &str,
+    writer: &mut W,
+    progress: &LegacyProgress,
+) -> io::Result<()> {
+    if length == 0 {
+        writeln!(writer, "{}", current)?;
+        progress.increment();
+        return Ok(());
+    }
+    for c in charset.chars() {
+        current.push(c);
+        recursive(current, length - 1, charset, writer, progress)?;
+        current.pop();
+    }
+    Ok(())
+}
+
+fn run(name: &str, f: impl FnOnce(&mut io::BufWriter<io::Sink>) -> io::Result<()>) -> f64 {
+    let words = (CHARSET.len() as u64).pow(LENGTH as u32);
+    let mut writer = io::BufWriter::new(io::sink());
+    let start = Instant::now();
+    f(&mut writer).unwrap();
+    writer.flush().unwrap();
+    let secs = start.elapsed().as_secs_f64();
+    let rate = words as f64 / secs;
+    println!(
+        "{:<10} {:>10} words in {:>7.3}s  {:>8.2} M words/s  {:>8.2} MB/s",
+        name,
+        words,
+        secs,
+        rate / 1e6,
+        rate * (LENGTH + 1) as f64 / 1e6
+    );
+    rate
+}
+
+fn main() {
+    let words = (CHARSET.len() as u64).pow(LENGTH as u32);
+    let baseline = run("recursive", |w| {
+        let progress = LegacyProgress {
+            current: AtomicU64::new(0),
+            total: words,
+            last_percentage: AtomicU64::new(0),
+        };
+        recursive(&mut String::new(), LENGTH, CHARSET, w, &progress)
+    });
+    let odometer = run("odometer", |w| {
+        let progress = Progress::new(words);
+        generate_all_combinations(
+            LENGTH,
+            &Charset::from_chars(CHARSET),
+            w,
+            &progress,
+            &Filters::default(),
+            LineEnding::Lf,
+        )
+    });
+    println!("speedup: {:.1}x", odometer / baseline);
+}
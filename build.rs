@@ -0,0 +1,20 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    ffi_header();
+}
+
+/// Writes `crunch_rs.h` for the `ffi` feature's C interface to `OUT_DIR`; the
+/// copy in `include/` is checked against it by the ffi tests.
+#[cfg(feature = "ffi")]
+fn ffi_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_config(cbindgen::Config::from_root_or_default(&crate_dir))
+        .with_src(std::path::Path::new(&crate_dir).join("src/ffi.rs"))
+        .generate()
+        .expect("cannot generate the C header")
+        .write_to_file(std::path::Path::new(&out_dir).join("crunch_rs.h"));
+}
@@ -0,0 +1,122 @@
+#ifndef CRUNCH_RS_H
+#define CRUNCH_RS_H
+
+/* Generated by cbindgen from src/ffi.rs into the build's OUT_DIR; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A word was written.
+ */
+#define CRUNCH_OK 0
+
+/**
+ * Every word has been returned.
+ */
+#define CRUNCH_DONE 1
+
+/**
+ * The buffer cannot hold the word and its NUL; `*len` is the word's length
+ * and the same word is returned by the next call.
+ */
+#define CRUNCH_BUFFER_TOO_SMALL 2
+
+/**
+ * Something failed; see [`crunch_last_error`].
+ */
+#define CRUNCH_ERROR -1
+
+/**
+ * A running generator from [`crunch_generator_new`].
+ */
+typedef struct CrunchGenerator CrunchGenerator;
+
+/**
+ * The settings of a keyspace. Strings are NUL-terminated UTF-8 and may be
+ * NULL when unused.
+ */
+typedef struct {
+  size_t min_len;
+  size_t max_len;
+  /**
+   * Charset or alias such as `lalpha-numeric`; NULL means `a`-`z`.
+   */
+  const char *charset;
+  /**
+   * Template such as `pass@@%`, used instead of the lengths.
+   */
+  const char *pattern;
+  const char *exclude_chars;
+  const char *starts_with;
+  const char *ends_with;
+  /**
+   * Words containing every one of these `contains_len` substrings.
+   */
+  const char *const *contains;
+  size_t contains_len;
+  bool no_duplicates;
+  /**
+   * Words passed over before the first one returned.
+   */
+  uint64_t skip;
+  /**
+   * Most words returned; 0 means no limit.
+   */
+  uint64_t limit;
+} CrunchConfig;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Starts generating the words of `config`, or returns NULL if it is invalid.
+ *
+ * # Safety
+ * `config` points to a valid `CrunchConfig` whose strings are NULL or
+ * NUL-terminated, and whose `contains` holds `contains_len` of them.
+ */
+CrunchGenerator *crunch_generator_new(const CrunchConfig *config);
+
+/**
+ * Copies the next word into `buf`, NUL-terminated, and its length without
+ * the NUL into `*len`. Returns one of the `CRUNCH_` status codes.
+ *
+ * # Safety
+ * `generator` comes from [`crunch_generator_new`], `buf` has room for
+ * `capacity` bytes and `len` is valid for writing.
+ */
+int crunch_generator_next(CrunchGenerator *generator, char *buf, size_t capacity, size_t *len);
+
+/**
+ * Stops the generator and frees it. NULL is ignored.
+ *
+ * # Safety
+ * `generator` comes from [`crunch_generator_new`] and is not used again.
+ */
+void crunch_generator_free(CrunchGenerator *generator);
+
+/**
+ * Stores the number of words in `config`'s keyspace, ignoring its skip and
+ * limit, in `*size`.
+ *
+ * # Safety
+ * As for [`crunch_generator_new`]; `size` is valid for writing.
+ */
+int crunch_keyspace_size(const CrunchConfig *config, uint64_t *size);
+
+/**
+ * The message of the last error on this thread, or NULL. It stays valid
+ * until the next failing call on the thread.
+ */
+const char *crunch_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CRUNCH_RS_H */
@@ -0,0 +1,103 @@
+use crate::classes::CharClass;
+use std::collections::{BTreeMap, HashMap};
+
+/// Length, composition and mask statistics of a wordlist, in the style of
+/// PACK's statsgen.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    pub words: u64,
+    /// Words per length in characters.
+    pub lengths: BTreeMap<usize, u64>,
+    /// Words per composition, see [`composition`].
+    pub compositions: HashMap<&'static str, u64>,
+    /// Words per hashcat mask, see [`mask`].
+    pub masks: HashMap<String, u64>,
+}
+
+impl Analysis {
+    pub fn add(&mut self, word: &str) {
+        self.words += 1;
+        *self.lengths.entry(word.chars().count()).or_default() += 1;
+        *self.compositions.entry(composition(word)).or_default() += 1;
+        *self.masks.entry(mask(word)).or_default() += 1;
+    }
+
+    /// The most common masks, most frequent first, until they cover
+    /// `coverage` percent of the words or `limit` masks have been taken.
+    pub fn top_masks(&self, coverage: f64, limit: Option<usize>) -> Vec<(&str, u64)> {
+        let target = (self.words as f64 * coverage / 100.0).ceil() as u64;
+        let mut masks = sorted(self.masks.iter().map(|(mask, &count)| (mask.as_str(), count)));
+        let mut covered = 0;
+        let taken = masks
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .take_while(|(_, count)| {
+                let more = covered < target;
+                covered += count;
+                more
+            })
+            .count();
+        masks.truncate(taken);
+        masks
+    }
+
+    /// Compositions, most frequent first.
+    pub fn top_compositions(&self) -> Vec<(&'static str, u64)> {
+        sorted(self.compositions.iter().map(|(&name, &count)| (name, count)))
+    }
+}
+
+/// Most frequent first, ties in name order so reports are stable.
+fn sorted<'a>(counts: impl Iterator<Item = (&'a str, u64)>) -> Vec<(&'a str, u64)> {
+    let mut counts: Vec<_> = counts.collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+}
+
+/// The hashcat mask matching `word`: `?l`, `?u`, `?d` and `?s` for ASCII
+/// letters, digits and symbols; anything else is kept literally.
+pub fn mask(word: &str) -> String {
+    let mut mask = String::with_capacity(word.len() * 2);
+    for c in word.chars() {
+        match c {
+            'a'..='z' => mask.push_str("?l"),
+            'A'..='Z' => mask.push_str("?u"),
+            '0'..='9' => mask.push_str("?d"),
+            ' '..='~' => mask.push_str("?s"),
+            c => mask.push(c),
+        }
+    }
+    mask
+}
+
+/// PACK's name for the character classes used in `word`, such as
+/// `loweralphanum` or `mixedalphaspecialnum`.
+pub fn composition(word: &str) -> &'static str {
+    let (mut lower, mut upper, mut digit, mut special) = (false, false, false, false);
+    for c in word.chars() {
+        match CharClass::of(c) {
+            CharClass::Lower => lower = true,
+            CharClass::Upper => upper = true,
+            CharClass::Digit => digit = true,
+            CharClass::Symbol => special = true,
+        }
+    }
+    match (lower, upper, special, digit) {
+        (false, false, false, false) => "empty",
+        (false, false, false, true) => "numeric",
+        (false, false, true, false) => "special",
+        (false, false, true, true) => "specialnum",
+        (true, false, false, false) => "loweralpha",
+        (true, false, false, true) => "loweralphanum",
+        (true, false, true, false) => "loweralphaspecial",
+        (true, false, true, true) => "loweralphaspecialnum",
+        (false, true, false, false) => "upperalpha",
+        (false, true, false, true) => "upperalphanum",
+        (false, true, true, false) => "upperalphaspecial",
+        (false, true, true, true) => "upperalphaspecialnum",
+        (true, true, false, false) => "mixedalpha",
+        (true, true, false, true) => "mixedalphanum",
+        (true, true, true, false) => "mixedalphaspecial",
+        (true, true, true, true) => "mixedalphaspecialnum",
+    }
+}
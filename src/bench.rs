@@ -0,0 +1,101 @@
+use crate::charset::Charset;
+use crate::compress::{Compression, ParallelEncoder, DEFAULT_BLOCK_SIZE};
+use crate::config::LineEnding;
+use crate::filter::Filters;
+use crate::generator::generate_layout;
+use crate::progress::Progress;
+use crate::output::OutputBuffer;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// What the words go through on their way to the null sink, as they would
+/// on their way to the output of `generate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchOutput {
+    /// The output buffer, of `--buffer-size` bytes.
+    Buffered { buffer_size: usize },
+    /// The `-z` compressor, on `--compress-threads` threads.
+    Compressed { compression: Compression, threads: usize },
+}
+
+/// Throughput of one benchmark configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchResult {
+    /// The output measured, with the threads the compressor really ran.
+    pub output: BenchOutput,
+    pub words: u64,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn words_per_sec(&self) -> f64 {
+        self.words as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn mb_per_sec(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64() / (1024.0 * 1024.0)
+    }
+}
+
+/// Discards everything written to it until `deadline`, then fails with `TimedOut`.
+struct DeadlineSink {
+    deadline: Instant,
+}
+
+impl Write for DeadlineSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if Instant::now() >= self.deadline {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Generates `layout` through `output` into a null sink for `duration`, as
+/// `generate` does. The keyspace restarts if it runs out early.
+pub fn run_bench(layout: &[&Charset], output: BenchOutput, duration: Duration) -> io::Result<BenchResult> {
+    if layout.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "nothing to benchmark"));
+    }
+    let start = Instant::now();
+    let deadline = start + duration;
+    let sink = DeadlineSink { deadline };
+    let (output, mut writer): (_, Box<dyn Write>) = match output {
+        BenchOutput::Buffered { buffer_size } => (output, Box::new(OutputBuffer::with_capacity(buffer_size, sink))),
+        BenchOutput::Compressed { compression, threads } => {
+            // The compressor runs at least one thread.
+            let threads = threads.max(1);
+            (
+                BenchOutput::Compressed { compression, threads },
+                Box::new(ParallelEncoder::new(sink, compression, threads, DEFAULT_BLOCK_SIZE)),
+            )
+        }
+    };
+
+    let progress = Progress::new(0);
+    loop {
+        let result = generate_layout(layout, &mut writer, &progress, &Filters::default(), LineEnding::Lf)
+            .and_then(|()| writer.flush());
+        match result {
+            Err(err) if err.kind() == io::ErrorKind::TimedOut => break,
+            Err(err) => return Err(err),
+            Ok(()) if Instant::now() >= deadline => break,
+            Ok(()) => {}
+        }
+    }
+    // Compressor threads still working on the last blocks are not waited for.
+    let elapsed = start.elapsed();
+    drop(writer);
+
+    Ok(BenchResult {
+        output,
+        words: progress.words(),
+        bytes: progress.bytes(),
+        elapsed,
+    })
+}
@@ -0,0 +1,104 @@
+use crate::config::LineEnding;
+use crate::error::Error;
+use crate::input::{for_each_line, open_input, read_lines, DEFAULT_BUFFER_SIZE};
+use crate::output::count_lines;
+use std::fmt;
+use std::hash::{DefaultHasher, Hasher};
+use std::io;
+use std::path::Path;
+
+/// Default false-positive rate for `--not-in`.
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+/// A Bloom filter over byte strings: a compact set that never misses an
+/// inserted item but wrongly reports about `false_positive_rate` of the
+/// others as present.
+#[derive(Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// An empty filter sized for `items` insertions at the given rate.
+    pub fn new(items: u64, false_positive_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let items = items.max(1) as f64;
+        let hashes = (-false_positive_rate.log2()).round().clamp(1.0, 32.0) as u32;
+        // At least 64 bits per hash, so a few items never fill most of a
+        // small filter.
+        let bit_count = (-items * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0 * hashes as f64) as u64;
+        BloomFilter {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            hashes,
+        }
+    }
+
+    /// A filter holding every line of the given wordlists; "-" is standard
+    /// input.
+    pub fn from_wordlists(paths: &[String], false_positive_rate: f64) -> io::Result<Self> {
+        let mut items = 0;
+        // Standard input can be read only once, so its lines are kept to be
+        // counted with the files before any is inserted.
+        let mut piped = Vec::new();
+        for path in paths {
+            if path == "-" {
+                piped.extend(read_lines(path, DEFAULT_BUFFER_SIZE)?);
+                continue;
+            }
+            // One more than the terminators, for a last line without one.
+            items += count_lines(Path::new(path), LineEnding::Lf).map_err(|err| Error::input(path.as_str(), err))? + 1;
+        }
+        let mut filter = BloomFilter::new(items + piped.len() as u64, false_positive_rate);
+        for word in &piped {
+            filter.insert(word.as_bytes());
+        }
+        for path in paths.iter().filter(|path| *path != "-") {
+            for_each_line(&mut open_input(path, DEFAULT_BUFFER_SIZE)?, |word| {
+                filter.insert(word.as_bytes());
+                Ok(())
+            })?;
+        }
+        Ok(filter)
+    }
+
+    pub fn insert(&mut self, item: &[u8]) {
+        for bit in self.positions(item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Whether `item` was probably inserted. `false` is always right.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.positions(item)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Bit positions of `item`, by double hashing two independent 64-bit
+    /// digests.
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        let digest = |seed: u64| {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(seed);
+            hasher.write(item);
+            hasher.finish()
+        };
+        let first = digest(0);
+        let step = digest(1) | 1;
+        let bit_count = self.bit_count;
+        (0..self.hashes as u64).map(move |i| first.wrapping_add(i.wrapping_mul(step)) % bit_count)
+    }
+}
+
+impl fmt::Debug for BloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("bit_count", &self.bit_count)
+            .field("hashes", &self.hashes)
+            .finish()
+    }
+}
@@ -0,0 +1,257 @@
+use std::io;
+use std::str::Chars;
+
+/// The symbols a single word position can take, each stored as its encoded bytes.
+///
+/// Symbols are whole characters rather than bytes, so multi-byte UTF-8
+/// characters (umlauts, Cyrillic, emoji) are never split. Escapes such as
+/// `\xff` and `--charset-file` add single raw bytes, which need not be UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charset {
+    symbols: Vec<Vec<u8>>,
+}
+
+impl Charset {
+    /// The characters of `chars`, each once; a repeat would only repeat words.
+    pub fn from_chars(chars: &str) -> Self {
+        Charset::unique(chars.chars().map(|c| c.to_string().into_bytes()))
+    }
+
+    /// The characters `spec` lists, each once, with escapes such as `\t` and
+    /// `\x00` decoded (see [`unescape`]).
+    pub fn parse(spec: &str) -> io::Result<Self> {
+        Ok(Charset::unique(unescape(spec)?))
+    }
+
+    /// The named charset `spec` (see [`aliases`]), or else the characters it
+    /// lists as for [`Charset::parse`].
+    pub fn resolve(spec: &str) -> io::Result<Self> {
+        match aliases().into_iter().find(|(alias, _)| alias == spec) {
+            Some((_, chars)) => Ok(Charset::from_chars(&chars)),
+            None => Charset::parse(spec),
+        }
+    }
+
+    /// Every byte of `bytes` as a symbol of its own, each once, for
+    /// non-text keyspaces read with `--charset-file`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Charset::unique(bytes.iter().map(|&byte| vec![byte]))
+    }
+
+    fn unique(symbols: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        let mut unique: Vec<Vec<u8>> = Vec::new();
+        for symbol in symbols {
+            if !unique.contains(&symbol) {
+                unique.push(symbol);
+            }
+        }
+        Charset { symbols: unique }
+    }
+
+    /// A charset whose symbols are arbitrary byte strings, e.g. whole words.
+    pub fn from_symbols(symbols: Vec<Vec<u8>>) -> Self {
+        Charset { symbols }
+    }
+
+    /// A position that always holds the same symbol, e.g. a template literal.
+    pub fn literal(symbol: &[u8]) -> Self {
+        Charset {
+            symbols: vec![symbol.to_vec()],
+        }
+    }
+
+    pub fn digits() -> Self {
+        Charset::from_chars("0123456789")
+    }
+
+    /// This charset minus every symbol of `excluded`.
+    pub fn without(&self, excluded: &Charset) -> Self {
+        Charset {
+            symbols: self
+                .symbols
+                .iter()
+                .filter(|symbol| !excluded.symbols.contains(symbol))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn symbols(&self) -> &[Vec<u8>] {
+        &self.symbols
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+const NUMERIC: &str = "0123456789";
+const SYMBOLS14: &str = "!@#$%^&*()-_+=";
+const SYMBOLS_ALL: &str = "!@#$%^&*()-_+=~`[]{}|\\:;\"'<>,.?/";
+const LALPHA: &str = "abcdefghijklmnopqrstuvwxyz";
+const UALPHA: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The named charsets of crunch's `charset.lst`, in its order.
+///
+/// Alphabets combine with `-numeric`, `-numeric-symbol14` or `-numeric-all`,
+/// and most names have a `-space` variant; the `-sv` names add the Swedish
+/// letters åäö.
+pub fn aliases() -> Vec<(String, String)> {
+    let mut aliases = vec![
+        ("hex-lower".to_string(), "0123456789abcdef".to_string()),
+        ("hex-upper".to_string(), "0123456789ABCDEF".to_string()),
+    ];
+    let with_space = |aliases: &mut Vec<(String, String)>, name: String, chars: String, suffix: &str| {
+        aliases.push((format!("{}{}", name, suffix), chars.clone()));
+        aliases.push((format!("{}-space{}", name, suffix), chars + " "));
+    };
+    for (name, chars) in [("numeric", NUMERIC), ("symbols14", SYMBOLS14), ("symbols-all", SYMBOLS_ALL)] {
+        with_space(&mut aliases, name.to_string(), chars.to_string(), "");
+    }
+    for (suffix, extra_lower, extra_upper) in [("", "", ""), ("-sv", "åäö", "ÅÄÖ")] {
+        let lower = format!("{}{}", LALPHA, extra_lower);
+        let upper = format!("{}{}", UALPHA, extra_upper);
+        let mixed = format!("{}{}", lower, upper);
+        for (alphabet, letters) in [("ualpha", upper), ("lalpha", lower), ("mixalpha", mixed)] {
+            for (combo, rest) in [
+                ("", String::new()),
+                ("-numeric", NUMERIC.to_string()),
+                ("-numeric-symbol14", format!("{}{}", NUMERIC, SYMBOLS14)),
+                ("-numeric-all", format!("{}{}", NUMERIC, SYMBOLS_ALL)),
+            ] {
+                with_space(&mut aliases, format!("{}{}", alphabet, combo), format!("{}{}", letters, rest), suffix);
+            }
+        }
+    }
+    aliases
+}
+
+/// The characters of the named charset `name`, or `name` itself when it is
+/// not a known name and so is taken as a literal list of characters.
+pub fn resolve_alias(name: &str) -> String {
+    aliases()
+        .into_iter()
+        .find(|(alias, _)| alias == name)
+        .map_or_else(|| name.to_string(), |(_, chars)| chars)
+}
+
+/// The symbols `spec` lists more than once, each reported once.
+pub fn repeated_symbols(spec: &str) -> io::Result<Vec<Vec<u8>>> {
+    let mut seen = Vec::new();
+    let mut repeated = Vec::new();
+    for symbol in unescape(spec)? {
+        if seen.contains(&symbol) {
+            if !repeated.contains(&symbol) {
+                repeated.push(symbol);
+            }
+        } else {
+            seen.push(symbol);
+        }
+    }
+    Ok(repeated)
+}
+
+/// Splits `spec` into symbols: one per character, except that `\\`, `\t`,
+/// `\n`, `\r`, `\0` and `\xHH` stand for a backslash, tab, newline,
+/// carriage return, NUL and the raw byte `HH`.
+pub fn unescape(spec: &str) -> io::Result<Vec<Vec<u8>>> {
+    let mut symbols = Vec::new();
+    let mut chars = spec.chars();
+    while let Some(c) = chars.next() {
+        symbols.push(match c {
+            '\\' => decode_escape(&mut chars)?,
+            c => c.to_string().into_bytes(),
+        });
+    }
+    Ok(symbols)
+}
+
+/// Decodes the escape whose backslash was just taken from `chars`.
+pub(crate) fn decode_escape(chars: &mut Chars) -> io::Result<Vec<u8>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let byte = match chars.next() {
+        Some('\\') => b'\\',
+        Some('t') => b'\t',
+        Some('n') => b'\n',
+        Some('r') => b'\r',
+        Some('0') => 0,
+        Some('x') => {
+            let hex: String = chars.by_ref().take(2).collect();
+            match u8::from_str_radix(&hex, 16) {
+                Ok(byte) if hex.len() == 2 => byte,
+                _ => return Err(invalid(format!("\\x{} is not a byte; use two hex digits, e.g. \\x0a", hex))),
+            }
+        }
+        Some(c) => return Err(invalid(format!("unknown escape \\{} (use \\\\ for a backslash)", c))),
+        None => return Err(invalid("dangling '\\' at the end (use \\\\ for a backslash)".to_string())),
+    };
+    Ok(vec![byte])
+}
+
+/// `symbol` in the notation [`unescape`] reads, so raw bytes print safely.
+pub fn escape(symbol: &[u8]) -> String {
+    let mut escaped = String::new();
+    for chunk in symbol.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\0' => escaped.push_str("\\0"),
+                c if c.is_control() => escaped.extend(c.to_string().bytes().map(|byte| format!("\\x{:02x}", byte))),
+                c => escaped.push(c),
+            }
+        }
+        escaped.extend(chunk.invalid().iter().map(|byte| format!("\\x{:02x}", byte)));
+    }
+    escaped
+}
+
+/// Whether `symbol` following `previous` puts a character twice in a row,
+/// where the two meet or within `symbol`. Repeated digits are always allowed.
+pub fn is_repeat(previous: &[u8], symbol: &[u8]) -> bool {
+    let mut last = last_char(previous);
+    let mut rest = symbol;
+    while !rest.is_empty() {
+        let (c, tail) = rest.split_at(char_len(rest));
+        if last == Some(c) && !is_digit(c) {
+            return true;
+        }
+        last = Some(c);
+        rest = tail;
+    }
+    false
+}
+
+/// Bytes of the character `bytes` starts with; a byte that does not start
+/// a UTF-8 character stands alone.
+fn char_len(bytes: &[u8]) -> usize {
+    let len = match bytes[0] {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    match bytes.get(..len).map(std::str::from_utf8) {
+        Some(Ok(_)) => len,
+        _ => 1,
+    }
+}
+
+/// The character `bytes` ends with, by the same rules as [`char_len`].
+fn last_char(bytes: &[u8]) -> Option<&[u8]> {
+    let start = (1..=bytes.len().min(4))
+        .rev()
+        .map(|len| bytes.len() - len)
+        .find(|&start| char_len(&bytes[start..]) == bytes.len() - start)?;
+    Some(&bytes[start..])
+}
+
+pub fn is_digit(symbol: &[u8]) -> bool {
+    matches!(symbol, [b'0'..=b'9'])
+}
@@ -0,0 +1,90 @@
+/// The disjoint classes every character falls into, as used by `--require`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    /// Anything else: punctuation, spaces, caseless letters.
+    Symbol,
+}
+
+pub const CLASS_COUNT: usize = 4;
+
+impl CharClass {
+    pub fn of(c: char) -> Self {
+        if c.is_ascii_digit() {
+            CharClass::Digit
+        } else if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else {
+            CharClass::Symbol
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lower" => Some(CharClass::Lower),
+            "upper" => Some(CharClass::Upper),
+            "digit" => Some(CharClass::Digit),
+            "symbol" => Some(CharClass::Symbol),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How many characters of one class a word must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassRequirement {
+    pub class: CharClass,
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Parses a comma-separated list such as `digit:2,symbol:1+,upper:1-3`:
+/// `N` means exactly, `N+` at least and `N-M` between N and M characters.
+pub fn parse_requirements(spec: &str) -> Result<Vec<ClassRequirement>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, count) = part
+                .split_once(':')
+                .ok_or_else(|| format!("expected CLASS:COUNT, got '{}'", part))?;
+            let class = CharClass::parse(name.trim())
+                .ok_or_else(|| format!("unknown class '{}' (use lower, upper, digit or symbol)", name))?;
+            let number = |value: &str| {
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid count '{}' in '{}'", count, part))
+            };
+            let (min, max) = if let Some(min) = count.strip_suffix('+') {
+                (number(min)?, None)
+            } else if let Some((min, max)) = count.split_once('-') {
+                (number(min)?, Some(number(max)?))
+            } else {
+                let exact = number(count)?;
+                (exact, Some(exact))
+            };
+            if max.is_some_and(|max| max < min) {
+                return Err(format!("empty range in '{}'", part));
+            }
+            Ok(ClassRequirement { class, min, max })
+        })
+        .collect()
+}
+
+/// Number of characters of each class in `word`, indexed by [`CharClass::index`].
+pub fn class_counts(word: &[u8]) -> [usize; CLASS_COUNT] {
+    let mut counts = [0; CLASS_COUNT];
+    for c in String::from_utf8_lossy(word).chars() {
+        counts[CharClass::of(c).index()] += 1;
+    }
+    counts
+}
@@ -0,0 +1,420 @@
+use crate::config::LineEnding;
+use crate::error::Error;
+use flate2::write::GzEncoder;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Uncompressed bytes compressed as one independent block.
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+/// Words per frame of indexed zstd output.
+pub const DEFAULT_FRAME_WORDS: u64 = 100_000;
+
+/// Blocks queued or being compressed per compressor thread.
+const BLOCKS_AHEAD: usize = 2;
+
+/// First line of a seek index.
+const INDEX_HEADER: &str = "crunch-rs seek index 1";
+
+/// Format of `-z` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gzip" | "gz" => Some(Compression::Gzip),
+            "zstd" | "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Suffix added to output file names.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    /// `data` as a complete gzip member or zstd frame. Both formats allow
+    /// these to be concatenated, so the blocks of a run form one valid file.
+    pub fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::with_capacity(data.len() / 4), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Compression::Zstd => zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+}
+
+type Job = (Vec<u8>, Sender<io::Result<Vec<u8>>>);
+
+/// Cuts the input into frames of whole words and lists where each starts in
+/// a seek index.
+struct Indexer {
+    out: Box<dyn Write>,
+    frame_words: u64,
+    delimiter: u8,
+    /// Complete words in the current block.
+    block_words: u64,
+    /// Where the next frame starts in the compressed output.
+    offset: u64,
+}
+
+/// A writer that compresses what it is given on a pool of threads, pigz
+/// style, and writes the results to `inner` in their original order.
+///
+/// Input is cut into blocks of `block_size` bytes, each compressed on its
+/// own, so throughput grows with the threads at a small cost in ratio. The
+/// blocks in flight are bounded, so a slow `inner` holds back the writer
+/// rather than filling memory. [`flush`](Write::flush) ends the current
+/// block early; the output stays valid however often it is called.
+///
+/// Made with [`indexed`](ParallelEncoder::indexed), blocks are cut every so
+/// many words instead and listed in a [`SeekIndex`].
+pub struct ParallelEncoder<W: Write> {
+    inner: W,
+    block: Vec<u8>,
+    block_size: usize,
+    /// Results of the submitted blocks with their words, oldest first.
+    pending: VecDeque<(Receiver<io::Result<Vec<u8>>>, u64)>,
+    max_pending: usize,
+    jobs: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    index: Option<Indexer>,
+}
+
+impl<W: Write> ParallelEncoder<W> {
+    pub fn new(inner: W, compression: Compression, threads: usize, block_size: usize) -> Self {
+        let threads = threads.max(1);
+        let (jobs, queue) = channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..threads)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || loop {
+                    // The lock is only held while taking a job, not while compressing it.
+                    let job = queue.lock().unwrap().recv();
+                    let Ok((block, result)) = job else {
+                        return;
+                    };
+                    let _ = result.send(compression.compress(&block));
+                })
+            })
+            .collect();
+        ParallelEncoder {
+            inner,
+            block: Vec::with_capacity(block_size),
+            block_size: block_size.max(1),
+            pending: VecDeque::new(),
+            max_pending: threads * BLOCKS_AHEAD,
+            jobs: Some(jobs),
+            workers,
+            index: None,
+        }
+    }
+
+    /// An encoder whose frames hold `frame_words` words each, listed in a
+    /// [`SeekIndex`] written to `index` as they are.
+    pub fn indexed(
+        inner: W,
+        compression: Compression,
+        threads: usize,
+        index: impl Write + 'static,
+        frame_words: u64,
+        line_ending: LineEnding,
+    ) -> io::Result<Self> {
+        let Some(&delimiter) = line_ending.as_bytes().last() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a seek index needs a line ending to find word boundaries",
+            ));
+        };
+        let mut out: Box<dyn Write> = Box::new(io::BufWriter::new(index));
+        writeln!(out, "{}\nline-ending {}", INDEX_HEADER, line_ending.name())?;
+        let mut encoder = Self::new(inner, compression, threads, DEFAULT_BLOCK_SIZE);
+        encoder.index = Some(Indexer {
+            out,
+            frame_words: frame_words.max(1),
+            delimiter,
+            block_words: 0,
+            offset: 0,
+        });
+        Ok(encoder)
+    }
+
+    /// Hands the first `end` bytes of the current block, holding `words`
+    /// words, to the pool, first writing out finished blocks and waiting
+    /// for the oldest if too many are in flight.
+    fn submit(&mut self, end: usize, words: u64) -> io::Result<()> {
+        if end == 0 {
+            return Ok(());
+        }
+        while self.pending.len() >= self.max_pending {
+            self.write_oldest()?;
+        }
+        let mut block = mem::replace(&mut self.block, Vec::with_capacity(self.block_size));
+        self.block.extend_from_slice(&block[end..]);
+        block.truncate(end);
+        let (sender, result) = channel();
+        self.jobs
+            .as_ref()
+            .expect("jobs is only taken on drop")
+            .send((block, sender))
+            .map_err(|_| io::Error::other("the compressor threads stopped"))?;
+        self.pending.push_back((result, words));
+        self.drain_finished()
+    }
+
+    fn write_oldest(&mut self) -> io::Result<()> {
+        if let Some((result, words)) = self.pending.pop_front() {
+            let compressed = result
+                .recv()
+                .map_err(|_| io::Error::other("a compressor thread panicked"))??;
+            self.write_frame(&compressed, words)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the blocks at the front that are already compressed.
+    fn drain_finished(&mut self) -> io::Result<()> {
+        while let Some((result, words)) = self.pending.front() {
+            let words = *words;
+            match result.try_recv() {
+                Ok(compressed) => {
+                    self.pending.pop_front();
+                    self.write_frame(&compressed?, words)?;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self, compressed: &[u8], words: u64) -> io::Result<()> {
+        self.inner.write_all(compressed)?;
+        if let Some(index) = &mut self.index {
+            writeln!(index.out, "{} {}", index.offset, words)?;
+            index.offset += compressed.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Adds `data` to the block, submitting it each time it holds a frame's
+    /// worth of words.
+    fn write_words(&mut self, mut data: &[u8]) -> io::Result<()> {
+        let index = self.index.as_ref().expect("only called when indexing");
+        let (frame_words, delimiter, mut block_words) = (index.frame_words, index.delimiter, index.block_words);
+        while !data.is_empty() {
+            let mut found = 0;
+            let mut cut = None;
+            for (i, _) in data.iter().enumerate().filter(|(_, &byte)| byte == delimiter) {
+                found += 1;
+                if block_words + found == frame_words {
+                    cut = Some(i + 1);
+                    break;
+                }
+            }
+            match cut {
+                Some(cut) => {
+                    self.block.extend_from_slice(&data[..cut]);
+                    data = &data[cut..];
+                    block_words = 0;
+                    self.submit(self.block.len(), frame_words)?;
+                }
+                None => {
+                    self.block.extend_from_slice(data);
+                    data = &[];
+                    block_words += found;
+                }
+            }
+        }
+        self.index.as_mut().unwrap().block_words = block_words;
+        Ok(())
+    }
+
+    /// Submits what is in the block. When indexing, a word without its
+    /// terminator yet is held back unless this is the end of the output.
+    fn submit_block(&mut self, last: bool) -> io::Result<()> {
+        let Some(index) = &mut self.index else {
+            return self.submit(self.block.len(), 0);
+        };
+        let delimiter = index.delimiter;
+        let mut words = mem::take(&mut index.block_words);
+        let end = match self.block.iter().rposition(|&byte| byte == delimiter) {
+            _ if last => {
+                words += u64::from(self.block.last().is_some_and(|&byte| byte != delimiter));
+                self.block.len()
+            }
+            Some(position) => position + 1,
+            None => 0,
+        };
+        self.submit(end, words)
+    }
+
+    /// Submits the block and writes every pending frame.
+    fn finish_frames(&mut self, last: bool) -> io::Result<()> {
+        self.submit_block(last)?;
+        while !self.pending.is_empty() {
+            self.write_oldest()?;
+        }
+        if let Some(index) = &mut self.index {
+            index.out.flush()?;
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Write for ParallelEncoder<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_all(data)?;
+        Ok(data.len())
+    }
+
+    fn write_all(&mut self, mut data: &[u8]) -> io::Result<()> {
+        if self.index.is_some() {
+            return self.write_words(data);
+        }
+        while !data.is_empty() {
+            let take = data.len().min(self.block_size - self.block.len());
+            self.block.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.block.len() == self.block_size {
+                self.submit(self.block.len(), 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish_frames(false)
+    }
+}
+
+impl<W: Write> Drop for ParallelEncoder<W> {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; call flush() to see them.
+        let _ = self.finish_frames(true);
+        drop(self.jobs.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Where the seek index of the compressed file `path` is written.
+pub fn index_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+/// One compressed frame of indexed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Where the frame starts in the compressed file.
+    pub offset: u64,
+    /// Position of its first word in the output.
+    pub first_word: u64,
+    pub words: u64,
+}
+
+/// The frames of a compressed file, from the sidecar written next to it by
+/// [`ParallelEncoder::indexed`]: a header, the line ending, then one
+/// `offset words` line per frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeekIndex {
+    pub line_ending: LineEnding,
+    pub frames: Vec<Frame>,
+}
+
+impl SeekIndex {
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = reader.lines();
+        if lines.next().transpose()?.as_deref() != Some(INDEX_HEADER) {
+            return Err(invalid("not a crunch-rs seek index".to_string()));
+        }
+        let line_ending = lines
+            .next()
+            .transpose()?
+            .as_deref()
+            .and_then(|line| line.strip_prefix("line-ending "))
+            .and_then(LineEnding::parse)
+            .filter(|line_ending| *line_ending != LineEnding::None)
+            .ok_or_else(|| invalid("the seek index has no line ending".to_string()))?;
+        let mut frames = Vec::new();
+        let mut first_word = 0;
+        for (number, line) in lines.enumerate() {
+            let line = line?;
+            let frame = line
+                .split_once(' ')
+                .and_then(|(offset, words)| Some((offset.parse().ok()?, words.parse().ok()?)));
+            let Some((offset, words)) = frame else {
+                return Err(invalid(format!("line {} of the seek index is not 'offset words'", number + 3)));
+            };
+            frames.push(Frame {
+                offset,
+                first_word,
+                words,
+            });
+            first_word += words;
+        }
+        Ok(SeekIndex { line_ending, frames })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = File::open(path).map_err(|err| Error::input(path.display().to_string(), err))?;
+        Self::read(BufReader::new(file)).map_err(|err| Error::input(path.display().to_string(), err).into())
+    }
+
+    /// Words in the whole file.
+    pub fn words(&self) -> u64 {
+        self.frames.last().map_or(0, |frame| frame.first_word + frame.words)
+    }
+}
+
+/// Writes `limit` words of the indexed zstd file `input`, or all that are
+/// left, starting at word `skip`. Only the frames holding them are read and
+/// decompressed. Returns the number of words written.
+pub fn extract<R: Read + Seek, W: Write + ?Sized>(
+    input: &mut R,
+    index: &SeekIndex,
+    skip: u64,
+    limit: Option<u64>,
+    out: &mut W,
+) -> io::Result<u64> {
+    let delimiter = *index.line_ending.as_bytes().last().unwrap();
+    let mut remaining = limit.unwrap_or(u64::MAX);
+    let first = index.frames.partition_point(|frame| frame.first_word + frame.words <= skip);
+    for (i, frame) in index.frames.iter().enumerate().skip(first) {
+        if remaining == 0 {
+            break;
+        }
+        let len = index.frames.get(i + 1).map_or(u64::MAX, |next| next.offset - frame.offset);
+        input.seek(SeekFrom::Start(frame.offset))?;
+        let data = zstd::decode_all(input.by_ref().take(len))?;
+        let skipped = skip.saturating_sub(frame.first_word);
+        for word in data.split_inclusive(|&byte| byte == delimiter).skip(skipped as usize) {
+            if remaining == 0 {
+                break;
+            }
+            out.write_all(word)?;
+            remaining -= 1;
+        }
+    }
+    Ok(limit.map_or(index.words().saturating_sub(skip), |limit| limit - remaining))
+}
@@ -0,0 +1,293 @@
+use crate::bloom::BloomFilter;
+use crate::charset::Charset;
+use crate::classes::ClassRequirement;
+use crate::error::{Error, Result};
+use crate::frequency::Frequencies;
+use crate::layout::{layouts, word_lengths};
+use crate::template::parse_template;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+/// What follows every generated word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    /// A NUL byte, for `xargs -0` and friends.
+    Nul,
+    /// Words are concatenated with nothing in between. The generator then
+    /// writes each word on its own, so a writer can tell them apart by its
+    /// write calls; an empty word is an empty write.
+    None,
+}
+
+impl LineEnding {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "lf" => Some(LineEnding::Lf),
+            "crlf" => Some(LineEnding::Crlf),
+            "nul" => Some(LineEnding::Nul),
+            "none" => Some(LineEnding::None),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "lf",
+            LineEnding::Crlf => "crlf",
+            LineEnding::Nul => "nul",
+            LineEnding::None => "none",
+        }
+    }
+
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::Crlf => b"\r\n",
+            LineEnding::Nul => b"\0",
+            LineEnding::None => b"",
+        }
+    }
+}
+
+/// Which end of a short word `--pad-to` fills.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PadSide {
+    #[default]
+    Left,
+    Right,
+}
+
+impl PadSide {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "left" => Some(PadSide::Left),
+            "right" => Some(PadSide::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Fills every word shorter than `width` symbols up to it with `symbol`,
+/// e.g. `7` becoming `0007` for fixed-width numeric IDs.
+///
+/// Padding is a fixed part of each layout, so sizes, ranks and resuming
+/// all count the padded words. A word that would pad to one a shorter
+/// length already makes, such as `07` to `7`'s `007`, is made only once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Padding {
+    pub width: usize,
+    pub symbol: Vec<u8>,
+    pub side: PadSide,
+}
+
+/// What happens to a candidate outside `min_len..=max_len` characters once
+/// `--length-policy` makes the bounds apply to templates and transforms too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPolicy {
+    /// Leave it out.
+    Skip,
+    /// Cut a long one down to `max_len` characters; a short one is left out.
+    /// Words that share their start then repeat.
+    Truncate,
+}
+
+impl LengthPolicy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "skip" => Some(LengthPolicy::Skip),
+            "truncate" => Some(LengthPolicy::Truncate),
+            _ => None,
+        }
+    }
+}
+
+/// Length bounds for words made from other words, as by `mutate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthBounds {
+    pub min: usize,
+    pub max: usize,
+    pub policy: LengthPolicy,
+}
+
+impl LengthBounds {
+    /// `word` as the bounds let it through, if at all.
+    pub fn apply<'a>(&self, word: &'a str) -> Option<&'a str> {
+        let len = word.chars().count();
+        if len < self.min {
+            return None;
+        }
+        match self.policy {
+            _ if len <= self.max => Some(word),
+            LengthPolicy::Truncate if self.max >= self.min => {
+                let end = word.char_indices().nth(self.max).map_or(word.len(), |(end, _)| end);
+                Some(&word[..end])
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Settings for a single generation run.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub min_len: usize,
+    pub max_len: usize,
+    pub charset: String,
+    /// Raw symbols, one per byte, used instead of `charset`, as read by
+    /// `--charset-file`.
+    pub charset_bytes: Option<Vec<u8>>,
+    pub template: Option<String>,
+    /// User-defined template placeholders: `?1` draws from `placeholders['1']`.
+    pub placeholders: BTreeMap<char, String>,
+    /// The words a template's `*` stands for, as read by `--wordlist`.
+    pub wordlist: Vec<Vec<u8>>,
+    pub output: Option<String>,
+    pub no_duplicates: bool,
+    /// Characters that never appear in generated words.
+    pub exclude_chars: String,
+    /// Substrings that never appear in generated words.
+    pub exclude_substrings: Vec<String>,
+    /// Every generated word starts with this.
+    pub starts_with: Option<String>,
+    /// Every generated word ends with this.
+    pub ends_with: Option<String>,
+    /// Substrings that appear somewhere in every generated word.
+    pub contains: Vec<String>,
+    /// How many characters of each class every generated word has.
+    pub require: Vec<ClassRequirement>,
+    /// Maximum number of times any single character may appear in a word.
+    pub max_char_count: Option<usize>,
+    /// Bounds on each word's Shannon entropy in bits, see [`crate::entropy::word_entropy`].
+    pub min_entropy: Option<f64>,
+    pub max_entropy: Option<f64>,
+    /// Words of an existing list to leave out, such as a baseline wordlist.
+    pub not_in: Option<Arc<BloomFilter>>,
+    /// Try the symbols of each position from most to least frequent instead
+    /// of in charset order.
+    pub frequencies: Option<Arc<Frequencies>>,
+    /// Emit the keyspace in the pseudo-random order chosen by this seed.
+    pub shuffle_seed: Option<u64>,
+    /// Take this many candidates of each length (or template layout) in
+    /// turn instead of finishing one before the next, see
+    /// [`crate::interleave::Interleaving`].
+    pub interleave: Option<u64>,
+    /// Every word ends with one of these, as made by `--append-years` and
+    /// `--append-numbers`; none when empty.
+    pub suffixes: Vec<Vec<u8>>,
+    /// Pads short words to a fixed width; filters see the padded word.
+    pub padding: Option<Padding>,
+    /// Holds every layout, templates and suffixes included, to
+    /// `min_len..=max_len` characters; without it only plain lengths do.
+    pub length_policy: Option<LengthPolicy>,
+    pub line_ending: LineEnding,
+}
+
+impl Config {
+    /// The symbols `@` and plain lengths draw from.
+    pub fn charset(&self) -> io::Result<Charset> {
+        match &self.charset_bytes {
+            Some(bytes) => Ok(Charset::from_bytes(bytes)),
+            None => Charset::resolve(&self.charset),
+        }
+    }
+
+    /// The symbols `--exclude-chars` removes from every position.
+    pub fn excluded(&self) -> io::Result<Charset> {
+        Charset::parse(&self.exclude_chars)
+    }
+
+    /// Checks that the settings can produce words, saying what to change when
+    /// they cannot rather than silently writing nothing.
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_entropy, self.max_entropy) {
+            if min > max {
+                return Err(Error::config(format!(
+                    "--min-entropy {} is greater than --max-entropy {}",
+                    min, max
+                )));
+            }
+        }
+        if self.interleave.is_some() && self.shuffle_seed.is_some() {
+            return Err(Error::config("--shuffle already mixes every length; leave out --order interleave"));
+        }
+        let charset = self.charset()?;
+        let exclusions = self.excluded()?;
+        let excluded = if self.exclude_chars.is_empty() { "" } else { " after --exclude-chars" };
+        match &self.template {
+            None => {
+                if !self.placeholders.is_empty() {
+                    return Err(Error::config("--set defines template placeholders, but there is no --template"));
+                }
+                if !self.wordlist.is_empty() {
+                    return Err(Error::config("--wordlist supplies a template's *, but there is no --template"));
+                }
+                if self.min_len > self.max_len {
+                    return Err(Error::config(format!(
+                        "<min_len> {} is greater than <max_len> {}",
+                        self.min_len, self.max_len
+                    )));
+                }
+                if self.max_len > 0 && charset.without(&exclusions).is_empty() {
+                    return Err(Error::config(format!("the charset has no characters{}", excluded)));
+                }
+            }
+            Some(template) => {
+                if let Some((key, _)) = self.placeholders.iter().find(|(_, chars)| chars.is_empty()) {
+                    return Err(Error::config(format!("placeholder ?{} has no characters", key)));
+                }
+                let expanded = parse_template(template, &charset, &self.placeholders, &self.wordlist)?;
+                let shortest = expanded.iter().map(|layout| word_lengths(layout).0).min().unwrap_or(0);
+                let longest = expanded.iter().map(|layout| word_lengths(layout).1).max().unwrap_or(0);
+                let words = if shortest == longest {
+                    format!("{}-character words", shortest)
+                } else {
+                    format!("words of {} to {} characters", shortest, longest)
+                };
+                if self.length_policy.is_none() && (shortest < self.min_len || longest > self.max_len) {
+                    return Err(Error::config(format!(
+                        "template '{}' makes {}, outside <min_len> {} and <max_len> {} (see --length-policy); use {} {}",
+                        template, words, self.min_len, self.max_len, shortest, longest
+                    )));
+                }
+                if self.length_policy.is_some() && layouts(self)?.is_empty() {
+                    return Err(Error::config(format!(
+                        "template '{}' makes {}, none of them between <min_len> {} and <max_len> {}",
+                        template, words, self.min_len, self.max_len
+                    )));
+                }
+                for layout in &expanded {
+                    if let Some(pos) = layout.iter().position(|chars| chars.without(&exclusions).is_empty()) {
+                        return Err(Error::config(format!(
+                            "template '{}' position {} has no characters{}",
+                            template,
+                            pos + 1,
+                            excluded
+                        )));
+                    }
+                }
+            }
+        }
+        // Suffixes, padding and wordlist positions all count towards a word's
+        // length, so it is taken from the layouts as they are enumerated.
+        let layouts = layouts(self)?;
+        if (self.starts_with.is_some() || self.ends_with.is_some()) && layouts.is_empty() {
+            return Err(Error::config(
+                "no word of the keyspace can take the --startswith/--endswith characters; check their length and that the charset has them",
+            ));
+        }
+        let Some(longest) = layouts.iter().map(|layout| word_lengths(layout).1).max() else {
+            return Ok(());
+        };
+        if let Some(needle) = self.contains.iter().find(|needle| needle.chars().count() > longest) {
+            return Err(Error::config(format!(
+                "--contains '{}' is longer than the longest word ({} characters)",
+                needle, longest
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use crate::bloom::BloomFilter;
+use crate::config::LineEnding;
+use crate::merge::Runs;
+use crate::output::Framing;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// Default bytes of words `--unique` remembers in memory before it spills
+/// them to disk.
+pub const DEFAULT_UNIQUE_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Rough cost in bytes of keeping one word in the hash set, beyond its bytes.
+const WORD_OVERHEAD: usize = 48;
+
+/// Words per entry of a spilled run's index; a lookup reads one such block.
+const BLOCK_WORDS: usize = 256;
+
+/// Share of new words each spilled run's filter sends to the disk anyway.
+const RUN_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Every word seen so far in a run, remembered exactly however many there are.
+///
+/// Words are kept in a hash set until about `memory` bytes are held; the set
+/// is then written out sorted to a temporary run file, and only a Bloom
+/// filter and a sparse index of it stay in memory. A new word mostly costs a
+/// few filter probes per run, a repeat of a spilled word one block read.
+pub struct SeenWords {
+    memory: usize,
+    temp_dir: PathBuf,
+    recent: HashSet<Vec<u8>>,
+    held: usize,
+    runs: Option<Runs>,
+    spilled: Vec<SpilledRun>,
+}
+
+/// A sorted run file of length-prefixed words.
+struct SpilledRun {
+    file: File,
+    filter: BloomFilter,
+    /// The first word of every block with the block's offset.
+    index: Vec<(Vec<u8>, u64)>,
+    len: u64,
+}
+
+impl SpilledRun {
+    fn contains(&mut self, word: &[u8]) -> io::Result<bool> {
+        if !self.filter.contains(word) {
+            return Ok(false);
+        }
+        let block = match self.index.partition_point(|(first, _)| first.as_slice() <= word) {
+            0 => return Ok(false),
+            after => after - 1,
+        };
+        let start = self.index[block].1;
+        let end = self.index.get(block + 1).map_or(self.len, |(_, offset)| *offset);
+        let mut bytes = vec![0; (end - start) as usize];
+        self.file.seek(SeekFrom::Start(start))?;
+        self.file.read_exact(&mut bytes)?;
+        let mut rest = bytes.as_slice();
+        while let Some((len, tail)) = rest.split_first_chunk::<4>() {
+            let (stored, tail) = tail.split_at(u32::from_le_bytes(*len) as usize);
+            if stored == word {
+                return Ok(true);
+            }
+            rest = tail;
+        }
+        Ok(false)
+    }
+}
+
+impl SeenWords {
+    pub fn new(memory: usize, temp_dir: impl Into<PathBuf>) -> Self {
+        SeenWords {
+            memory,
+            temp_dir: temp_dir.into(),
+            recent: HashSet::new(),
+            held: 0,
+            runs: None,
+            spilled: Vec::new(),
+        }
+    }
+
+    /// Remembers `word`, returning whether it had not been seen before.
+    pub fn insert(&mut self, word: &[u8]) -> io::Result<bool> {
+        if self.recent.contains(word) {
+            return Ok(false);
+        }
+        for run in &mut self.spilled {
+            if run.contains(word)? {
+                return Ok(false);
+            }
+        }
+        self.recent.insert(word.to_vec());
+        self.held += word.len() + WORD_OVERHEAD;
+        if self.held >= self.memory {
+            self.spill()?;
+        }
+        Ok(true)
+    }
+
+    /// Number of runs spilled to disk so far.
+    pub fn runs(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// Moves the words held in memory to a new run file.
+    fn spill(&mut self) -> io::Result<()> {
+        if self.runs.is_none() {
+            self.runs = Some(Runs::create(&self.temp_dir, "unique")?);
+        }
+        let path = self.runs.as_mut().expect("run directory was just created").next_path();
+        let mut words: Vec<Vec<u8>> = self.recent.drain().collect();
+        words.sort_unstable();
+        let mut filter = BloomFilter::new(words.len() as u64, RUN_FALSE_POSITIVE_RATE);
+        let mut index = Vec::with_capacity(words.len().div_ceil(BLOCK_WORDS));
+        let mut file = BufWriter::new(File::create(&path)?);
+        let mut len = 0;
+        for (position, word) in words.iter().enumerate() {
+            filter.insert(word);
+            if position % BLOCK_WORDS == 0 {
+                index.push((word.clone(), len));
+            }
+            file.write_all(&(word.len() as u32).to_le_bytes())?;
+            file.write_all(word)?;
+            len += 4 + word.len() as u64;
+        }
+        file.flush()?;
+        drop(file);
+        self.spilled.push(SpilledRun {
+            file: File::open(&path)?,
+            filter,
+            index,
+            len,
+        });
+        self.held = 0;
+        Ok(())
+    }
+}
+
+/// Passes on each word the first time it is written and drops its repeats,
+/// for `--unique`.
+///
+/// Words are told apart by the line ending, so one is required. A word is
+/// held back until its terminator arrives.
+pub struct Unique<W: Write> {
+    inner: W,
+    seen: SeenWords,
+    framing: Framing,
+    /// The words of the current write that are passed on.
+    kept: Vec<u8>,
+    duplicates: u64,
+    duplicate_bytes: u64,
+}
+
+impl<W: Write> Unique<W> {
+    pub fn new(inner: W, seen: SeenWords, line_ending: LineEnding) -> io::Result<Self> {
+        Ok(Unique {
+            inner,
+            seen,
+            framing: Framing::required(line_ending, "--unique")?,
+            kept: Vec::new(),
+            duplicates: 0,
+            duplicate_bytes: 0,
+        })
+    }
+
+    /// Repeated words dropped so far.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Bytes of the dropped words, terminators included.
+    pub fn duplicate_bytes(&self) -> u64 {
+        self.duplicate_bytes
+    }
+
+    /// Keeps or drops `line`, one word with its terminator.
+    fn judge(&mut self, line: &[u8]) -> io::Result<()> {
+        let word = self.framing.word(line);
+        if self.seen.insert(word)? {
+            self.kept.extend_from_slice(line);
+        } else {
+            self.duplicates += 1;
+            self.duplicate_bytes += line.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Unique<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.judge(line)?;
+        }
+        if !self.kept.is_empty() {
+            self.inner.write_all(&self.kept)?;
+            self.kept.clear();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
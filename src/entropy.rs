@@ -0,0 +1,47 @@
+/// Shannon entropy of `word` in bits: its length in characters times the
+/// entropy of its own character distribution.
+///
+/// Repetitive words score low (`aaaaaa` is 0, `abab` is 4) and words without
+/// a repeated character score highest (`n·log2(n)` for `n` characters), which
+/// makes it a cheap stand-in for how "random" a candidate looks.
+pub fn word_entropy(word: &[u8]) -> f64 {
+    // Words are short, so a linear scan beats hashing.
+    let mut counts: Vec<(char, u32)> = Vec::new();
+    let mut total = 0u32;
+    for c in String::from_utf8_lossy(word).chars() {
+        match counts.iter_mut().find(|(seen, _)| *seen == c) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((c, 1)),
+        }
+        total += 1;
+    }
+    let total = total as f64;
+    counts
+        .iter()
+        .map(|&(_, count)| {
+            let count = count as f64;
+            count * (total / count).log2()
+        })
+        .sum()
+}
+
+/// Inclusive bounds on [`word_entropy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EntropyBounds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl EntropyBounds {
+    /// `None` when neither bound is set.
+    pub fn new(min: Option<f64>, max: Option<f64>) -> Option<Self> {
+        (min.is_some() || max.is_some()).then_some(EntropyBounds { min, max })
+    }
+
+    pub fn contains(&self, word: &[u8]) -> bool {
+        let entropy = word_entropy(word);
+        // A little slack so that e.g. `--min-entropy 2` keeps `ab` (exactly 2 bits).
+        const EPSILON: f64 = 1e-9;
+        self.min.is_none_or(|min| entropy >= min - EPSILON) && self.max.is_none_or(|max| entropy <= max + EPSILON)
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::io;
+
+/// Exit status for a failure while running, such as a full disk.
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit status for settings that cannot work; clap uses it for malformed
+/// command lines too.
+pub const EXIT_CONFIG: u8 = 2;
+/// Exit status for an input file that cannot be read.
+pub const EXIT_INPUT: u8 = 3;
+/// Exit status for an output that cannot be written.
+pub const EXIT_OUTPUT: u8 = 4;
+
+/// Why a run failed, deciding its message and exit status.
+///
+/// Library functions keep returning [`io::Result`]; an `Error` travels inside
+/// the [`io::Error`] and [`Error::from`] recovers it, so the binary can report
+/// what went wrong however deep it was raised.
+#[derive(Debug)]
+pub enum Error {
+    /// Settings that contradict each other or cannot produce anything.
+    Config(String),
+    /// A wordlist, rule file, model or other input that cannot be read.
+    Input { path: String, source: io::Error },
+    /// An output file, directory, archive or pipe that cannot be written.
+    Output { path: String, source: io::Error },
+    /// Anything else that failed while running.
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn config(message: impl Into<String>) -> Self {
+        Error::Config(message.into())
+    }
+
+    pub fn input(path: impl Into<String>, source: io::Error) -> Self {
+        Error::Input {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn output(path: impl Into<String>, source: io::Error) -> Self {
+        Error::Output {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Config(_) => EXIT_CONFIG,
+            Error::Input { .. } => EXIT_INPUT,
+            Error::Output { .. } => EXIT_OUTPUT,
+            Error::Io(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(message) => f.write_str(message),
+            Error::Input { path, source } => write!(f, "cannot read {}: {}", path, source),
+            Error::Output { path, source } => write!(f, "cannot write {}: {}", path, source),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Config(_) => None,
+            Error::Input { source, .. } | Error::Output { source, .. } | Error::Io(source) => Some(source),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            Error::Config(_) => io::Error::new(io::ErrorKind::InvalidInput, err),
+            Error::Input { ref source, .. } | Error::Output { ref source, .. } => io::Error::new(source.kind(), err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    /// Recovers an `Error` carried by `err`; other invalid-input errors are
+    /// configuration mistakes and the rest are run-time failures.
+    fn from(err: io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = err.into_inner().expect("checked above");
+            return *inner.downcast::<Error>().expect("checked above");
+        }
+        match err.kind() {
+            io::ErrorKind::InvalidInput => Error::Config(err.to_string()),
+            _ => Error::Io(err),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! C interface, built with `--features ffi`. The matching header is
+//! `include/crunch_rs.h`, generated into the build's `OUT_DIR`.
+//!
+//! ```c
+//! CrunchConfig config = { .min_len = 1, .max_len = 4, .charset = "abc123" };
+//! CrunchGenerator *gen = crunch_generator_new(&config);
+//! char word[256];
+//! size_t len;
+//! while (crunch_generator_next(gen, word, sizeof word, &len) == CRUNCH_OK) {
+//!     /* word holds len bytes, NUL-terminated */
+//! }
+//! crunch_generator_free(gen);
+//! ```
+
+use crate::config::Config;
+use crate::generator::Window;
+use crate::size::calculate_size;
+use crate::stream::Words;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::io;
+use std::ptr;
+
+/// A word was written.
+pub const CRUNCH_OK: c_int = 0;
+/// Every word has been returned.
+pub const CRUNCH_DONE: c_int = 1;
+/// The buffer cannot hold the word and its NUL; `*len` is the word's length
+/// and the same word is returned by the next call.
+pub const CRUNCH_BUFFER_TOO_SMALL: c_int = 2;
+/// Something failed; see [`crunch_last_error`].
+pub const CRUNCH_ERROR: c_int = -1;
+
+/// The settings of a keyspace. Strings are NUL-terminated UTF-8 and may be
+/// NULL when unused.
+#[repr(C)]
+pub struct CrunchConfig {
+    pub min_len: usize,
+    pub max_len: usize,
+    /// Charset or alias such as `lalpha-numeric`; NULL means `a`-`z`.
+    pub charset: *const c_char,
+    /// Template such as `pass@@%`, used instead of the lengths.
+    pub pattern: *const c_char,
+    pub exclude_chars: *const c_char,
+    pub starts_with: *const c_char,
+    pub ends_with: *const c_char,
+    /// Words containing every one of these `contains_len` substrings.
+    pub contains: *const *const c_char,
+    pub contains_len: usize,
+    pub no_duplicates: bool,
+    /// Words passed over before the first one returned.
+    pub skip: u64,
+    /// Most words returned; 0 means no limit.
+    pub limit: u64,
+}
+
+/// A running generator from [`crunch_generator_new`].
+pub struct CrunchGenerator {
+    words: Words,
+    /// A word that did not fit the caller's buffer.
+    pending: Option<Vec<u8>>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(err: io::Error) {
+    let message = CString::new(err.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// # Safety
+/// `value` is NULL or a NUL-terminated string.
+unsafe fn string(value: *const c_char, name: &str) -> io::Result<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(|value| Some(value.to_string()))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not valid UTF-8", name)))
+}
+
+/// # Safety
+/// See [`crunch_generator_new`].
+unsafe fn config(config: *const CrunchConfig) -> io::Result<(Config, Window)> {
+    let Some(config) = config.as_ref() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "config is NULL"));
+    };
+    let pattern = string(config.pattern, "pattern")?;
+    let mut contains = Vec::with_capacity(config.contains_len);
+    for i in 0..config.contains_len {
+        contains.extend(string(*config.contains.add(i), "contains")?);
+    }
+    let window = Window {
+        skip: config.skip,
+        limit: Some(config.limit).filter(|&limit| limit > 0),
+    };
+    let keyspace = Config {
+        min_len: config.min_len,
+        max_len: config.max_len,
+        charset: string(config.charset, "charset")?.unwrap_or_else(|| "abcdefghijklmnopqrstuvwxyz".to_string()),
+        template: pattern,
+        exclude_chars: string(config.exclude_chars, "exclude_chars")?.unwrap_or_default(),
+        starts_with: string(config.starts_with, "starts_with")?,
+        ends_with: string(config.ends_with, "ends_with")?,
+        contains,
+        no_duplicates: config.no_duplicates,
+        ..Config::default()
+    };
+    keyspace.validate()?;
+    Ok((keyspace, window))
+}
+
+/// Starts generating the words of `config`, or returns NULL if it is invalid.
+///
+/// # Safety
+/// `config` points to a valid `CrunchConfig` whose strings are NULL or
+/// NUL-terminated, and whose `contains` holds `contains_len` of them.
+#[no_mangle]
+pub unsafe extern "C" fn crunch_generator_new(config: *const CrunchConfig) -> *mut CrunchGenerator {
+    match self::config(config) {
+        Ok((config, window)) => Box::into_raw(Box::new(CrunchGenerator {
+            words: Words::window(config, window),
+            pending: None,
+        })),
+        Err(err) => {
+            set_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Copies the next word into `buf`, NUL-terminated, and its length without
+/// the NUL into `*len`. Returns one of the `CRUNCH_` status codes.
+///
+/// # Safety
+/// `generator` comes from [`crunch_generator_new`], `buf` has room for
+/// `capacity` bytes and `len` is valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn crunch_generator_next(
+    generator: *mut CrunchGenerator,
+    buf: *mut c_char,
+    capacity: usize,
+    len: *mut usize,
+) -> c_int {
+    let Some(generator) = generator.as_mut() else {
+        set_error(io::Error::new(io::ErrorKind::InvalidInput, "generator is NULL"));
+        return CRUNCH_ERROR;
+    };
+    let word = match generator.pending.take().map(Ok).or_else(|| generator.words.next()) {
+        Some(Ok(word)) => word,
+        Some(Err(err)) => {
+            set_error(err);
+            return CRUNCH_ERROR;
+        }
+        None => return CRUNCH_DONE,
+    };
+    *len = word.len();
+    if word.len() >= capacity {
+        generator.pending = Some(word);
+        return CRUNCH_BUFFER_TOO_SMALL;
+    }
+    ptr::copy_nonoverlapping(word.as_ptr(), buf.cast::<u8>(), word.len());
+    *buf.add(word.len()) = 0;
+    CRUNCH_OK
+}
+
+/// Stops the generator and frees it. NULL is ignored.
+///
+/// # Safety
+/// `generator` comes from [`crunch_generator_new`] and is not used again.
+#[no_mangle]
+pub unsafe extern "C" fn crunch_generator_free(generator: *mut CrunchGenerator) {
+    if !generator.is_null() {
+        drop(Box::from_raw(generator));
+    }
+}
+
+/// Stores the number of words in `config`'s keyspace, ignoring its skip and
+/// limit, in `*size`.
+///
+/// # Safety
+/// As for [`crunch_generator_new`]; `size` is valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn crunch_keyspace_size(config: *const CrunchConfig, size: *mut u64) -> c_int {
+    match self::config(config).and_then(|(config, _)| calculate_size(&config)) {
+        Ok(words) => {
+            *size = words;
+            CRUNCH_OK
+        }
+        Err(err) => {
+            set_error(err);
+            CRUNCH_ERROR
+        }
+    }
+}
+
+/// The message of the last error on this thread, or NULL. It stays valid
+/// until the next failing call on the thread.
+#[no_mangle]
+pub extern "C" fn crunch_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
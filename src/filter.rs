@@ -0,0 +1,208 @@
+use crate::bloom::BloomFilter;
+use crate::charset::is_repeat;
+use crate::classes::{class_counts, ClassRequirement, CLASS_COUNT};
+use crate::config::Config;
+use crate::entropy::EntropyBounds;
+use std::sync::Arc;
+
+/// Rules checked as each symbol is placed, so rejected prefixes prune their
+/// whole subtree instead of being filtered after the fact.
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// Reject the same character twice in a row (digits excepted).
+    pub no_duplicates: bool,
+    /// Reject any word containing one of these byte strings.
+    pub exclude_substrings: Vec<Vec<u8>>,
+    /// Reject any word missing one of these byte strings.
+    pub required_substrings: Vec<Vec<u8>>,
+    /// Reject words without the required number of characters of each class.
+    pub classes: Vec<ClassRequirement>,
+    /// Reject words in which any single symbol appears more often than this.
+    pub max_char_count: Option<usize>,
+    /// Reject complete words whose entropy falls outside these bounds.
+    pub entropy: Option<EntropyBounds>,
+    /// Reject complete words found in this set.
+    pub not_in: Option<Arc<BloomFilter>>,
+}
+
+impl Filters {
+    pub fn from_config(config: &Config) -> Self {
+        Filters {
+            no_duplicates: config.no_duplicates,
+            exclude_substrings: config
+                .exclude_substrings
+                .iter()
+                .filter(|substring| !substring.is_empty())
+                .map(|substring| substring.as_bytes().to_vec())
+                .collect(),
+            required_substrings: config
+                .contains
+                .iter()
+                .filter(|substring| !substring.is_empty())
+                .map(|substring| substring.as_bytes().to_vec())
+                .collect(),
+            classes: config.require.clone(),
+            max_char_count: config.max_char_count,
+            entropy: EntropyBounds::new(config.min_entropy, config.max_entropy),
+            not_in: config.not_in.clone(),
+        }
+    }
+
+    /// Whether any rule needs to inspect individual words.
+    pub fn is_active(&self) -> bool {
+        self.no_duplicates
+            || !self.exclude_substrings.is_empty()
+            || !self.required_substrings.is_empty()
+            || !self.classes.is_empty()
+            || self.max_char_count.is_some()
+            || self.judges_whole_words()
+    }
+
+    /// Whether any rule only applies to complete words, see [`Filters::accepts`].
+    pub fn judges_whole_words(&self) -> bool {
+        self.entropy.is_some() || self.not_in.is_some()
+    }
+
+    /// Whether a complete word passes the rules that can only judge all of it.
+    /// These cannot prune prefixes, so their rejects still cost enumeration.
+    pub fn accepts(&self, word: &[u8]) -> bool {
+        self.entropy.is_none_or(|bounds| bounds.contains(word))
+            && self.not_in.as_ref().is_none_or(|seen| !seen.contains(word))
+    }
+
+    /// Whether `symbol` may be appended to `word`, whose last symbol is `previous`.
+    pub fn allows(&self, word: &[u8], previous: Option<&[u8]>, symbol: &[u8]) -> bool {
+        if self.no_duplicates && is_repeat(previous.unwrap_or_default(), symbol) {
+            return false;
+        }
+        if let Some(max) = self.max_char_count {
+            if occurrences(word, symbol) >= max {
+                return false;
+            }
+        }
+        !self
+            .exclude_substrings
+            .iter()
+            .any(|substring| ends_within(word, symbol, substring))
+    }
+
+    /// Whether `word + symbol` can still be completed when the positions after
+    /// it add at most `remaining` characters: every required substring is in
+    /// place or still fits, and so does every class count.
+    pub fn reachable(&self, word: &[u8], symbol: &[u8], remaining: usize) -> bool {
+        if !self.classes.is_empty() {
+            let mut counts = class_counts(word);
+            for (count, extra) in counts.iter_mut().zip(class_counts(symbol)) {
+                *count += extra;
+            }
+            if !self.classes_feasible(&counts, remaining) {
+                return false;
+            }
+        }
+        self.required_substrings.iter().all(|substring| {
+            if word.windows(substring.len()).any(|window| window == substring.as_slice())
+                || ends_within(word, symbol, substring)
+            {
+                return true;
+            }
+            // The longest start of `substring` already in place at the end.
+            let matched = (1..substring.len())
+                .rev()
+                .find(|&len| ends_with_joined(word, symbol, &substring[..len]))
+                .unwrap_or(0);
+            char_count(&substring[matched..]) <= remaining
+        })
+    }
+
+    /// Whether a word with `counts` characters per class can still meet every
+    /// class requirement with at most `remaining` more characters.
+    pub fn classes_feasible(&self, counts: &[usize; CLASS_COUNT], remaining: usize) -> bool {
+        let mut deficits = [0; CLASS_COUNT];
+        for requirement in &self.classes {
+            let count = counts[requirement.class.index()];
+            if requirement.max.is_some_and(|max| count > max) {
+                return false;
+            }
+            let deficit = &mut deficits[requirement.class.index()];
+            *deficit = (*deficit).max(requirement.min.saturating_sub(count));
+        }
+        deficits.iter().sum::<usize>() <= remaining
+    }
+
+    /// The count past which a class no longer matters to the requirements;
+    /// counts can be capped there without changing [`classes_feasible`](Self::classes_feasible).
+    pub fn class_cap(&self, class: usize) -> usize {
+        self.classes
+            .iter()
+            .filter(|requirement| requirement.class.index() == class)
+            .map(|requirement| requirement.max.map_or(requirement.min, |max| max + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether `word + symbol` contains each required substring, as a mask
+    /// updated from `found`. `word` needs only be as long as [`pending_suffix`](Self::pending_suffix) keeps.
+    pub fn found_required(&self, found: &[bool], word: &[u8], symbol: &[u8]) -> Vec<bool> {
+        self.required_substrings
+            .iter()
+            .zip(found)
+            .map(|(substring, &found)| found || ends_within(word, symbol, substring))
+            .collect()
+    }
+
+    /// Longest suffix of `word` that could still grow into an excluded or
+    /// required substring; everything before it can no longer affect `allows`
+    /// or [`found_required`](Self::found_required).
+    pub fn pending_suffix<'a>(&self, word: &'a [u8]) -> &'a [u8] {
+        for start in 0..word.len() {
+            let suffix = &word[start..];
+            if self
+                .exclude_substrings
+                .iter()
+                .chain(&self.required_substrings)
+                .any(|substring| substring.len() > suffix.len() && substring.starts_with(suffix))
+            {
+                return suffix;
+            }
+        }
+        &[]
+    }
+}
+
+/// Whether `word + symbol` has an occurrence of `substring` that ends inside `symbol`.
+fn ends_within(word: &[u8], symbol: &[u8], substring: &[u8]) -> bool {
+    (1..=symbol.len()).any(|end| {
+        let placed = &symbol[..end];
+        if placed.len() >= substring.len() {
+            placed.ends_with(substring)
+        } else {
+            let (head, tail) = substring.split_at(substring.len() - placed.len());
+            placed == tail && word.ends_with(head)
+        }
+    })
+}
+
+/// Whether `word + symbol` ends with `pattern`.
+fn ends_with_joined(word: &[u8], symbol: &[u8], pattern: &[u8]) -> bool {
+    if pattern.len() <= symbol.len() {
+        symbol.ends_with(pattern)
+    } else {
+        let (head, tail) = pattern.split_at(pattern.len() - symbol.len());
+        tail == symbol && word.ends_with(head)
+    }
+}
+
+/// Number of UTF-8 characters in `bytes`.
+fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&byte| byte & 0xc0 != 0x80).count()
+}
+
+/// Number of times `symbol` occurs in `word`. Symbols are whole characters,
+/// so byte matches cannot straddle a character boundary.
+fn occurrences(word: &[u8], symbol: &[u8]) -> usize {
+    match symbol {
+        [] => 0,
+        [byte] => word.iter().filter(|&b| b == byte).count(),
+        _ => word.windows(symbol.len()).filter(|w| *w == symbol).count(),
+    }
+}
@@ -0,0 +1,140 @@
+use crate::analyze::{composition, mask};
+use crate::config::LineEnding;
+use crate::output::Framing;
+use crate::progress::json_string;
+use std::io::{self, Write};
+
+/// Structured encodings of the output, for `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One JSON object per line.
+    Jsonl,
+    /// Comma-separated values under a header line.
+    Csv,
+}
+
+impl RecordFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "jsonl" => Some(RecordFormat::Jsonl),
+            "csv" => Some(RecordFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Names of the fields of every record, in order.
+pub const FIELDS: [&str; 5] = ["index", "word", "length", "mask", "composition"];
+
+/// Writes every word written through it as a record with its position in
+/// the output, its length in characters, its hashcat mask and its PACK
+/// composition, ended by the line ending.
+///
+/// Words are told apart by the line ending, so one is required. Words that
+/// are not UTF-8 go into JSON with their bad bytes replaced; CSV keeps them.
+pub struct Records<W: Write> {
+    inner: W,
+    format: RecordFormat,
+    framing: Framing,
+    /// The records of the current write.
+    encoded: Vec<u8>,
+    index: u64,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+impl<W: Write> Records<W> {
+    pub fn new(inner: W, format: RecordFormat, line_ending: LineEnding) -> io::Result<Self> {
+        Ok(Records {
+            inner,
+            format,
+            framing: Framing::required(line_ending, "--format")?,
+            encoded: Vec::new(),
+            index: 0,
+            bytes_in: 0,
+            bytes_out: 0,
+        })
+    }
+
+    /// Bytes of the words taken in, terminators included.
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in
+    }
+
+    /// Bytes of the records written, the CSV header included.
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out
+    }
+
+    fn encode(&mut self, line: &[u8]) {
+        let word = self.framing.word(line);
+        let text = String::from_utf8_lossy(word);
+        let length = text.chars().count();
+        let out = &mut self.encoded;
+        match self.format {
+            RecordFormat::Jsonl => {
+                out.extend_from_slice(
+                    format!(
+                        "{{\"index\":{},\"word\":{},\"length\":{},\"mask\":{},\"composition\":\"{}\"}}",
+                        self.index,
+                        json_string(&text),
+                        length,
+                        json_string(&mask(&text)),
+                        composition(&text)
+                    )
+                    .as_bytes(),
+                );
+            }
+            RecordFormat::Csv => {
+                if self.index == 0 {
+                    out.extend_from_slice(FIELDS.join(",").as_bytes());
+                    out.extend_from_slice(self.framing.terminator());
+                }
+                out.extend_from_slice(format!("{},", self.index).as_bytes());
+                csv_field(out, word);
+                out.extend_from_slice(format!(",{},", length).as_bytes());
+                csv_field(out, mask(&text).as_bytes());
+                out.push(b',');
+                out.extend_from_slice(composition(&text).as_bytes());
+            }
+        }
+        out.extend_from_slice(self.framing.terminator());
+        self.index += 1;
+        self.bytes_in += line.len() as u64;
+    }
+}
+
+/// `field`, quoted if it holds a comma, quote or line break.
+fn csv_field(out: &mut Vec<u8>, field: &[u8]) {
+    if !field.iter().any(|byte| matches!(byte, b',' | b'"' | b'\r' | b'\n')) {
+        out.extend_from_slice(field);
+        return;
+    }
+    out.push(b'"');
+    for &byte in field {
+        if byte == b'"' {
+            out.push(b'"');
+        }
+        out.push(byte);
+    }
+    out.push(b'"');
+}
+
+impl<W: Write> Write for Records<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.encode(line);
+        }
+        if !self.encoded.is_empty() {
+            self.inner.write_all(&self.encoded)?;
+            self.bytes_out += self.encoded.len() as u64;
+            self.encoded.clear();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
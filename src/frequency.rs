@@ -0,0 +1,70 @@
+use crate::charset::Charset;
+use crate::input::{for_each_line, open_input, DEFAULT_BUFFER_SIZE};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io;
+
+/// Characters of leaked passwords from most to least common, used by
+/// `--order freq` when no training list is given.
+pub const PASSWORD_FREQUENCY: &str = "ae1ionrls0t2m9c3d8hy4u75b6kgpjvfwzxqAEINROLSTMCDYHUBKGPJVFWZXQ.!_-@* #/$&+=?,%'\"()^~;:<>[]{}|\\`";
+
+/// How often each symbol occurs, overall and at each position of a word,
+/// deciding the order in which `--order freq` tries symbols.
+#[derive(Debug, Clone, Default)]
+pub struct Frequencies {
+    overall: HashMap<Vec<u8>, u64>,
+    positions: Vec<HashMap<Vec<u8>, u64>>,
+}
+
+impl Frequencies {
+    /// Frequencies following [`PASSWORD_FREQUENCY`], the same at every position.
+    pub fn builtin() -> Self {
+        let ranked: Vec<char> = PASSWORD_FREQUENCY.chars().collect();
+        Frequencies {
+            overall: ranked
+                .iter()
+                .enumerate()
+                .map(|(rank, c)| (c.to_string().into_bytes(), (ranked.len() - rank) as u64))
+                .collect(),
+            positions: Vec::new(),
+        }
+    }
+
+    /// Frequencies counted from every line of a wordlist.
+    pub fn learn(path: &str) -> io::Result<Self> {
+        let mut frequencies = Frequencies::default();
+        for_each_line(&mut open_input(path, DEFAULT_BUFFER_SIZE)?, |word| {
+            frequencies.add(word);
+            Ok(())
+        })?;
+        Ok(frequencies)
+    }
+
+    pub fn add(&mut self, word: &str) {
+        for (pos, c) in word.chars().enumerate() {
+            let symbol = c.to_string().into_bytes();
+            if self.positions.len() <= pos {
+                self.positions.push(HashMap::new());
+            }
+            *self.positions[pos].entry(symbol.clone()).or_default() += 1;
+            *self.overall.entry(symbol).or_default() += 1;
+        }
+    }
+
+    /// `charset` with its symbols sorted by how common they are at `pos`,
+    /// falling back to their overall frequency and then to charset order.
+    pub fn order(&self, charset: &Charset, pos: usize) -> Charset {
+        let count = |counts: Option<&HashMap<Vec<u8>, u64>>, symbol: &Vec<u8>| {
+            counts.and_then(|counts| counts.get(symbol)).copied().unwrap_or(0)
+        };
+        let mut symbols = charset.symbols().to_vec();
+        // A stable sort keeps charset order among equally common symbols.
+        symbols.sort_by_cached_key(|symbol| {
+            (
+                Reverse(count(self.positions.get(pos), symbol)),
+                Reverse(count(Some(&self.overall), symbol)),
+            )
+        });
+        Charset::from_symbols(symbols)
+    }
+}
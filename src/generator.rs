@@ -0,0 +1,416 @@
+use crate::charset::Charset;
+use crate::config::{Config, LineEnding};
+use crate::filter::Filters;
+use crate::interleave::Interleaving;
+use crate::layout::{layouts, room_after, Layout};
+use crate::progress::Progress;
+use crate::rank::{layout_len, symbol_indices};
+use crate::shuffle::generate_shuffled;
+use crate::size::layout_size;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// The slice of a keyspace to write: the first `skip` words are passed over,
+/// then at most `limit` are written. Both count down as words go by, so one
+/// window can be carried across several layouts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Window {
+    pub skip: u64,
+    pub limit: Option<u64>,
+}
+
+impl Window {
+    pub fn is_exhausted(&self) -> bool {
+        self.limit == Some(0)
+    }
+}
+
+/// Writes every word described by `layout`, one charset per position.
+pub fn generate_layout<W: Write + ?Sized>(
+    layout: &[&Charset],
+    writer: &mut W,
+    progress: &Progress,
+    filters: &Filters,
+    line_ending: LineEnding,
+) -> io::Result<()> {
+    generate_layout_window(layout, writer, progress, filters, line_ending, &mut Window::default())
+}
+
+/// Writes the words of `layout` that fall inside `window`.
+///
+/// The word is kept as an odometer over symbol indices: each step bumps the
+/// rightmost position and carries left, rewriting only the bytes that changed.
+/// The last position is swept in one go into a reusable batch of complete
+/// words, so most words cost a single in-place byte patch.
+///
+/// Without filters the odometer starts directly at the first word of the
+/// window; with filters the skipped words are counted off batch by batch.
+pub fn generate_layout_window<W: Write + ?Sized>(
+    layout: &[&Charset],
+    writer: &mut W,
+    progress: &Progress,
+    filters: &Filters,
+    line_ending: LineEnding,
+    window: &mut Window,
+) -> io::Result<()> {
+    generate_layout_span(layout, writer, progress, filters, line_ending, window, 0..u64::MAX)
+}
+
+/// As [`generate_layout_window`], over the candidates of `layout` numbered
+/// `span` in the unfiltered order, which filters then thin out.
+fn generate_layout_span<W: Write + ?Sized>(
+    layout: &[&Charset],
+    writer: &mut W,
+    progress: &Progress,
+    filters: &Filters,
+    line_ending: LineEnding,
+    window: &mut Window,
+    span: Range<u64>,
+) -> io::Result<()> {
+    let terminator = line_ending.as_bytes();
+    if window.is_exhausted() || layout.iter().any(|charset| charset.is_empty()) || span.is_empty() {
+        return Ok(());
+    }
+    let Some((last_charset, _)) = layout.split_last() else {
+        if span.start > 0 {
+            return Ok(());
+        }
+        if !filters.reachable(b"", b"", 0) {
+            // Nothing to write: the empty word contains no required substring.
+        } else if !filters.accepts(b"") {
+            progress.reject(1);
+        } else if window.skip > 0 {
+            window.skip -= 1;
+        } else {
+            write_word(writer, b"", progress, line_ending)?;
+            window.limit = window.limit.map(|limit| limit - 1);
+        }
+        return Ok(());
+    };
+
+    // `word` holds the bytes of every position but the last; position `p`
+    // occupies `word[offsets[p]..offsets[p + 1]]`.
+    let room = room_after(layout);
+    let allowed = |word: &[u8], offsets: &[usize], pos: usize, symbol: &[u8]| {
+        let previous = pos.checked_sub(1).map(|prev| &word[offsets[prev]..offsets[pos]]);
+        filters.allows(word, previous, symbol) && filters.reachable(word, symbol, room[pos])
+    };
+    let next_allowed = |word: &[u8], offsets: &[usize], pos: usize, from: usize| {
+        let symbols = layout[pos].symbols();
+        (from..symbols.len()).find(|&idx| allowed(word, offsets, pos, &symbols[idx]))
+    };
+
+    let last = layout.len() - 1;
+    let mut indices = vec![0usize; last];
+    let mut offsets = vec![0usize; last + 1];
+    let mut word = Vec::new();
+
+    // Unfiltered words are numbered in mixed radix, so the window's first
+    // word can be reached directly. `resume` makes the first fill and sweep
+    // start from there instead of from each position's first symbol.
+    let total = layout
+        .iter()
+        .fold(1u64, |total, charset| total.saturating_mul(charset.len() as u64));
+    let end = span.end.min(total);
+    let mut start = span.start;
+    if !filters.is_active() && window.skip > 0 {
+        let available = end.saturating_sub(start);
+        if window.skip >= available {
+            window.skip -= available;
+            return Ok(());
+        }
+        start += window.skip;
+        window.skip = 0;
+    }
+    if start >= end {
+        return Ok(());
+    }
+    let mut resume = false;
+    let mut sweep_from = 0;
+    if start > 0 {
+        let first = symbol_indices(layout, start).expect("start is within the layout");
+        indices.copy_from_slice(&first[..last]);
+        sweep_from = first[last];
+        resume = true;
+    }
+    // Index of the first word of each sweep, kept only when the span ends early.
+    let last_len = last_charset.len() as u64;
+    let sweep_start = |indices: &[usize]| {
+        layout[..last]
+            .iter()
+            .zip(indices)
+            .fold(0u64, |index, (charset, &idx)| {
+                index.saturating_mul(charset.len() as u64).saturating_add(idx as u64)
+            })
+            .saturating_mul(last_len)
+    };
+
+    let mut batch = Vec::new();
+    // Start offset of each word in `batch`, and the prefix length it was built for.
+    let mut slots = Vec::with_capacity(last_charset.len());
+    let mut batch_prefix_len = None;
+    // Bytes of `word` before this offset are unchanged since the last sweep.
+    let mut dirty = 0;
+
+    // `pos` is the first position that still needs to be (re)filled.
+    let mut pos = 0;
+    loop {
+        while pos < last {
+            word.truncate(offsets[pos]);
+            let from = if resume { indices[pos] } else { 0 };
+            match next_allowed(&word, &offsets, pos, from) {
+                Some(idx) => {
+                    // A filter moved this position past the start, so the
+                    // rest starts from their first symbols.
+                    if resume && idx != from {
+                        resume = false;
+                        sweep_from = 0;
+                    }
+                    indices[pos] = idx;
+                    word.extend_from_slice(&layout[pos].symbols()[idx]);
+                    offsets[pos + 1] = word.len();
+                    pos += 1;
+                }
+                None => break,
+            }
+        }
+        if pos < last {
+            sweep_from = 0;
+        }
+        resume = false;
+
+        // Sweep the last position directly; this is where nearly all words come from.
+        if pos == last {
+            let from = std::mem::take(&mut sweep_from);
+            let mut to = last_charset.len();
+            if end < total {
+                let first = sweep_start(&indices);
+                if first >= end {
+                    return Ok(());
+                }
+                to = to.min((end - first).min(usize::MAX as u64) as usize);
+            }
+            if filters.is_active() || from > 0 || to < last_charset.len() {
+                batch.clear();
+                slots.clear();
+                let mut rejected = 0;
+                for symbol in &last_charset.symbols()[from..to] {
+                    if allowed(&word, &offsets, last, symbol) {
+                        let start = batch.len();
+                        batch.extend_from_slice(&word);
+                        batch.extend_from_slice(symbol);
+                        if !filters.accepts(&batch[start..]) {
+                            batch.truncate(start);
+                            rejected += 1;
+                            continue;
+                        }
+                        slots.push(start);
+                        batch.extend_from_slice(terminator);
+                    }
+                }
+                if rejected > 0 {
+                    progress.reject(rejected);
+                }
+                batch_prefix_len = None;
+            } else if batch_prefix_len != Some(word.len()) {
+                batch.clear();
+                slots.clear();
+                for symbol in last_charset.symbols() {
+                    slots.push(batch.len());
+                    batch.extend_from_slice(&word);
+                    batch.extend_from_slice(symbol);
+                    batch.extend_from_slice(terminator);
+                }
+                batch_prefix_len = Some(word.len());
+            } else {
+                // Every word of the batch stays valid between sweeps; only the
+                // prefix bytes that changed since the last sweep are patched.
+                for &start in &slots {
+                    batch[start + dirty..start + word.len()].copy_from_slice(&word[dirty..]);
+                }
+            }
+            dirty = word.len();
+
+            // Trim the batch to the window.
+            let mut first = 0;
+            if window.skip > 0 {
+                let skipped = window.skip.min(slots.len() as u64);
+                window.skip -= skipped;
+                first = skipped as usize;
+            }
+            let mut end = slots.len();
+            if let Some(limit) = window.limit {
+                end = end.min(first.saturating_add(limit.min(usize::MAX as u64) as usize));
+            }
+            if first < end {
+                let bytes = &batch[slots[first]..slots.get(end).copied().unwrap_or(batch.len())];
+                let words = (end - first) as u64;
+                if terminator.is_empty() {
+                    for (pos, &start) in slots.iter().enumerate().take(end).skip(first) {
+                        write_whole(writer, &batch[start..slots.get(pos + 1).copied().unwrap_or(batch.len())])?;
+                    }
+                } else {
+                    writer.write_all(bytes)?;
+                }
+                progress.add(words, bytes.len() as u64);
+                window.limit = window.limit.map(|limit| limit - words);
+                if window.is_exhausted() {
+                    return Ok(());
+                }
+            }
+        }
+
+        // Advance the deepest position that still has an allowed successor.
+        loop {
+            if pos == 0 {
+                return Ok(());
+            }
+            pos -= 1;
+            word.truncate(offsets[pos]);
+            if let Some(idx) = next_allowed(&word, &offsets, pos, indices[pos] + 1) {
+                indices[pos] = idx;
+                word.extend_from_slice(&layout[pos].symbols()[idx]);
+                offsets[pos + 1] = word.len();
+                dirty = dirty.min(offsets[pos]);
+                pos += 1;
+                break;
+            }
+        }
+    }
+}
+
+/// Writes the words of `layouts` inside `window` in the [`Interleaving`]
+/// order, `batch` candidates of each layout at a time.
+fn generate_interleaved<W: Write + ?Sized>(
+    layouts: &[Layout],
+    batch: u64,
+    writer: &mut W,
+    progress: &Progress,
+    filters: &Filters,
+    line_ending: LineEnding,
+    window: &mut Window,
+) -> io::Result<()> {
+    let interleaving = Interleaving::new(layouts, batch);
+    let batch = interleaving.batch();
+    let layouts: Vec<Vec<&Charset>> = layouts.iter().map(|layout| layout.iter().collect()).collect();
+    // Without filters the window's first word is found directly, as the
+    // layout, round and index it falls on.
+    let mut first = (0, 0, 0);
+    if !filters.is_active() && window.skip > 0 {
+        match interleaving.locate(window.skip) {
+            Some((layout, index)) => first = (index / batch, layout, index),
+            None => {
+                window.skip = window.skip.saturating_sub(interleaving.len());
+                return Ok(());
+            }
+        }
+        window.skip = 0;
+    }
+    let (first_round, first_layout, first_index) = first;
+    for round in first_round.. {
+        let start = round.saturating_mul(batch);
+        let mut visited = false;
+        for (which, layout) in layouts.iter().enumerate() {
+            if window.is_exhausted() {
+                return Ok(());
+            }
+            if round == first_round && which < first_layout {
+                continue;
+            }
+            if layout_len(layout).unwrap_or(u64::MAX) <= start {
+                continue;
+            }
+            visited = true;
+            let from = if (round, which) == (first_round, first_layout) { first_index } else { start };
+            let span = from..start.saturating_add(batch);
+            generate_layout_span(layout, writer, progress, filters, line_ending, window, span)?;
+        }
+        if !visited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a single word followed by `line_ending`; without one, as a write
+/// of its own.
+pub fn write_word<W: Write + ?Sized>(
+    writer: &mut W,
+    word: &[u8],
+    progress: &Progress,
+    line_ending: LineEnding,
+) -> io::Result<()> {
+    let terminator = line_ending.as_bytes();
+    if terminator.is_empty() {
+        write_whole(writer, word)?;
+    } else {
+        writer.write_all(word)?;
+        writer.write_all(terminator)?;
+    }
+    progress.increment((word.len() + terminator.len()) as u64);
+    Ok(())
+}
+
+/// Writes `word` in write calls of its own, where `write_all` would skip an
+/// empty one.
+fn write_whole<W: Write + ?Sized>(writer: &mut W, word: &[u8]) -> io::Result<()> {
+    if word.is_empty() {
+        writer.write(word).map(drop)
+    } else {
+        writer.write_all(word)
+    }
+}
+
+/// Writes every word of exactly `length` symbols drawn from `charset`.
+pub fn generate_all_combinations<W: Write + ?Sized>(
+    length: usize,
+    charset: &Charset,
+    writer: &mut W,
+    progress: &Progress,
+    filters: &Filters,
+    line_ending: LineEnding,
+) -> io::Result<()> {
+    let layout = vec![charset; length];
+    generate_layout(&layout, writer, progress, filters, line_ending)
+}
+
+pub fn generate_words<W: Write + ?Sized>(
+    config: &Config,
+    writer: &mut W,
+    progress: &Progress,
+) -> io::Result<()> {
+    generate_words_window(config, writer, progress, &mut Window::default())
+}
+
+/// Writes the words of the whole keyspace that fall inside `window`.
+pub fn generate_words_window<W: Write + ?Sized>(
+    config: &Config,
+    writer: &mut W,
+    progress: &Progress,
+    window: &mut Window,
+) -> io::Result<()> {
+    if let Some(seed) = config.shuffle_seed {
+        return generate_shuffled(config, seed, writer, progress, window);
+    }
+    let filters = Filters::from_config(config);
+    if let Some(batch) = config.interleave {
+        return generate_interleaved(&layouts(config)?, batch, writer, progress, &filters, config.line_ending, window);
+    }
+    for layout in layouts(config)? {
+        if window.is_exhausted() {
+            break;
+        }
+        let layout: Vec<&Charset> = layout.iter().collect();
+        // Whole layouts before the window are skipped by their size when it is exact.
+        let exact = filters.max_char_count.is_none() && !filters.judges_whole_words();
+        if window.skip > 0 && filters.is_active() && exact {
+            let size = layout_size(&layout, &filters);
+            if window.skip >= size {
+                window.skip -= size;
+                continue;
+            }
+        }
+        generate_layout_window(&layout, writer, progress, &filters, config.line_ending, window)?;
+    }
+    Ok(())
+}
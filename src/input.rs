@@ -0,0 +1,52 @@
+use crate::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// Default read buffer for wordlist input.
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Opens a wordlist for streaming; `-` reads from stdin.
+pub fn open_input(path: &str, buffer_size: usize) -> io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        Ok(Box::new(BufReader::with_capacity(buffer_size, io::stdin())))
+    } else {
+        let file = File::open(path).map_err(|err| Error::input(path, err))?;
+        Ok(Box::new(BufReader::with_capacity(buffer_size, file)))
+    }
+}
+
+/// Calls `f` for every line of `reader` without its line terminator.
+///
+/// Lines are read as raw bytes so a stray invalid byte in a large wordlist is
+/// replaced rather than aborting the whole run.
+pub fn for_each_line<R, F>(reader: &mut R, mut f: F) -> io::Result<()>
+where
+    R: BufRead + ?Sized,
+    F: FnMut(&str) -> io::Result<()>,
+{
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        f(&String::from_utf8_lossy(&line))?;
+    }
+}
+
+/// Reads every line of a wordlist into memory.
+pub fn read_lines(path: &str, buffer_size: usize) -> io::Result<Vec<String>> {
+    let mut reader = open_input(path, buffer_size)?;
+    let mut lines = Vec::new();
+    for_each_line(&mut reader, |line| {
+        lines.push(line.to_string());
+        Ok(())
+    })?;
+    Ok(lines)
+}
@@ -0,0 +1,97 @@
+use crate::charset::Charset;
+use crate::layout::Layout;
+use crate::rank::layout_len;
+
+/// Candidates taken from each layout in turn by `--order interleave`.
+pub const DEFAULT_INTERLEAVE_BATCH: u64 = 10_000;
+
+/// The order `--order interleave` visits a keyspace in: round after round,
+/// the next `batch` candidates of every layout that has any left, shortest
+/// layout first. Round `r` of a layout covers its candidates
+/// `r * batch .. (r + 1) * batch` in the usual order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interleaving {
+    /// Candidates in each layout, saturating at `u64::MAX`.
+    sizes: Vec<u64>,
+    batch: u64,
+}
+
+impl Interleaving {
+    pub fn new(layouts: &[Layout], batch: u64) -> Self {
+        let sizes = layouts
+            .iter()
+            .map(|layout| {
+                let layout: Vec<&Charset> = layout.iter().collect();
+                layout_len(&layout).unwrap_or(u64::MAX)
+            })
+            .collect();
+        Interleaving {
+            sizes,
+            batch: batch.max(1),
+        }
+    }
+
+    pub fn batch(&self) -> u64 {
+        self.batch
+    }
+
+    /// Candidates in every layout together, saturating at `u64::MAX`.
+    pub fn len(&self) -> u64 {
+        self.sizes.iter().fold(0u64, |total, &size| total.saturating_add(size))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Candidates visited before round `round` starts.
+    fn before_round(&self, round: u64) -> u64 {
+        let taken = round.saturating_mul(self.batch);
+        self.sizes.iter().fold(0u64, |total, &size| total.saturating_add(size.min(taken)))
+    }
+
+    /// Position of candidate `index` of layout `layout`, or `None` if the
+    /// layout has no such candidate.
+    pub fn position(&self, layout: usize, index: u64) -> Option<u64> {
+        if index >= *self.sizes.get(layout)? {
+            return None;
+        }
+        let round = index / self.batch;
+        let start = round * self.batch;
+        // Earlier layouts still going in this round take their share first.
+        let earlier: u64 = self.sizes[..layout]
+            .iter()
+            .map(|&size| size.saturating_sub(start).min(self.batch))
+            .sum();
+        Some(self.before_round(round) + earlier + index - start)
+    }
+
+    /// The layout and index within it of the candidate at `position`, or
+    /// `None` past the end.
+    pub fn locate(&self, position: u64) -> Option<(usize, u64)> {
+        if position >= self.len() {
+            return None;
+        }
+        // The last round starting at or before `position`.
+        let longest = self.sizes.iter().copied().max().unwrap_or(0);
+        let (mut low, mut high) = (0, longest.div_ceil(self.batch));
+        while low + 1 < high {
+            let middle = low + (high - low) / 2;
+            if self.before_round(middle) <= position {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let start = low * self.batch;
+        let mut rest = position - self.before_round(low);
+        for (layout, &size) in self.sizes.iter().enumerate() {
+            let share = size.saturating_sub(start).min(self.batch);
+            if rest < share {
+                return Some((layout, start + rest));
+            }
+            rest -= share;
+        }
+        None
+    }
+}
@@ -0,0 +1,37 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static TIMED_OUT: AtomicBool = AtomicBool::new(false);
+
+/// Set once Ctrl-C or SIGTERM has been received or the time budget is spent.
+pub fn flag() -> &'static AtomicBool {
+    &REQUESTED
+}
+
+/// Sets [`flag`] once `duration` has passed.
+pub fn stop_after(duration: Duration) {
+    thread::spawn(move || {
+        thread::sleep(duration);
+        TIMED_OUT.store(true, Ordering::SeqCst);
+        REQUESTED.store(true, Ordering::SeqCst);
+    });
+}
+
+/// Whether the stop was requested by [`stop_after`] rather than a signal.
+pub fn timed_out() -> bool {
+    TIMED_OUT.load(Ordering::SeqCst)
+}
+
+/// Turns Ctrl-C and SIGTERM into a request to stop after the current word,
+/// see [`crate::output::Interruptible`]. A second signal exits at once.
+pub fn install() -> io::Result<()> {
+    ctrlc::set_handler(|| {
+        if REQUESTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+    })
+    .map_err(io::Error::other)
+}
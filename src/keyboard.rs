@@ -0,0 +1,228 @@
+use crate::config::LineEnding;
+use crate::generator::write_word;
+use crate::progress::Progress;
+use std::io::{self, Write};
+
+/// Physical keyboard layouts known to the walk generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+}
+
+impl KeyboardLayout {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "qwerty" => Some(KeyboardLayout::Qwerty),
+            "azerty" => Some(KeyboardLayout::Azerty),
+            "qwertz" => Some(KeyboardLayout::Qwertz),
+            _ => None,
+        }
+    }
+
+    /// Rows from top to bottom as (unshifted, shifted, horizontal offset in key widths).
+    fn rows(self) -> [(&'static str, &'static str, f32); 4] {
+        match self {
+            KeyboardLayout::Qwerty => [
+                ("`1234567890-=", "~!@#$%^&*()_+", 0.0),
+                ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1.5),
+                ("asdfghjkl;'", "ASDFGHJKL:\"", 1.75),
+                ("zxcvbnm,./", "ZXCVBNM<>?", 2.25),
+            ],
+            KeyboardLayout::Azerty => [
+                ("²&é\"'(-è_çà)=", "²1234567890°+", 0.0),
+                ("azertyuiop^$", "AZERTYUIOP¨£", 1.5),
+                ("qsdfghjklmù*", "QSDFGHJKLM%µ", 1.75),
+                ("<wxcvbn,;:!", ">WXCVBN?./§", 1.25),
+            ],
+            KeyboardLayout::Qwertz => [
+                ("^1234567890ß´", "°!\"§$%&/()=?`", 0.0),
+                ("qwertzuiopü+", "QWERTZUIOPÜ*", 1.5),
+                ("asdfghjklöä#", "ASDFGHJKLÖÄ'", 1.75),
+                ("<yxcvbnm,.-", ">YXCVBNM;:_", 1.25),
+            ],
+        }
+    }
+}
+
+/// Keys of a layout and which keys physically touch each other.
+#[derive(Debug, Clone)]
+pub struct Keyboard {
+    /// (unshifted, shifted) character of each key.
+    keys: Vec<(char, char)>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Keyboard {
+    pub fn new(layout: KeyboardLayout) -> Self {
+        let mut keys = Vec::new();
+        let mut positions = Vec::new();
+        for (row, (base, shifted, offset)) in layout.rows().iter().enumerate() {
+            for (col, key) in base.chars().zip(shifted.chars()).enumerate() {
+                keys.push(key);
+                positions.push((row as f32, offset + col as f32));
+            }
+        }
+
+        // Keys touch when they sit in the same or an adjacent row and their
+        // centres are less than one key width apart horizontally.
+        let neighbors = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &(row, x))| {
+                positions
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, &(other_row, other_x))| {
+                        let dx = (x - other_x).abs();
+                        i != j
+                            && if row == other_row {
+                                dx == 1.0
+                            } else {
+                                (row - other_row).abs() == 1.0 && dx < 1.0
+                            }
+                    })
+                    .map(|(j, _)| j)
+                    .collect()
+            })
+            .collect();
+
+        Keyboard { keys, neighbors }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Number of distinct key paths of `length` keys where every step moves to an adjacent key.
+    pub fn path_count(&self, length: usize) -> u64 {
+        if length == 0 {
+            return 0;
+        }
+        // ending[k]: paths of the current length that end on key k.
+        let mut ending = vec![1u64; self.len()];
+        for _ in 1..length {
+            ending = self
+                .neighbors
+                .iter()
+                .map(|adjacent| {
+                    adjacent
+                        .iter()
+                        .fold(0u64, |sum, &k| sum.saturating_add(ending[k]))
+                })
+                .collect();
+        }
+        ending.iter().fold(0u64, |sum, &n| sum.saturating_add(n))
+    }
+}
+
+/// How the shift key participates in a walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftMode {
+    /// Only unshifted characters.
+    None,
+    /// Each walk once unshifted and once fully shifted.
+    All,
+    /// Every combination of shifted and unshifted keys within a walk.
+    Mixed,
+}
+
+/// Keyboard-adjacency walks between `min_len` and `max_len` keys long.
+#[derive(Debug, Clone)]
+pub struct Walks {
+    pub keyboard: Keyboard,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub shift: ShiftMode,
+}
+
+impl Walks {
+    fn variants(&self, length: usize) -> u64 {
+        match self.shift {
+            ShiftMode::None => 1,
+            ShiftMode::All => 2,
+            ShiftMode::Mixed => 2u64.saturating_pow(length as u32),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        (self.min_len..=self.max_len).fold(0u64, |total, length| {
+            total.saturating_add(
+                self.keyboard
+                    .path_count(length)
+                    .saturating_mul(self.variants(length)),
+            )
+        })
+    }
+
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        for length in self.min_len.max(1)..=self.max_len {
+            let mut path = Vec::with_capacity(length);
+            // choice[d]: index of the next neighbor to try at depth d.
+            let mut choice = vec![0usize; length];
+            let mut start = 0;
+            while start < self.keyboard.len() {
+                if path.is_empty() {
+                    path.push(start);
+                    choice[0] = 0;
+                }
+                if path.len() == length {
+                    self.emit(&path, writer, progress, line_ending)?;
+                }
+
+                // Step deeper if possible, otherwise backtrack.
+                loop {
+                    let depth = path.len() - 1;
+                    let key = path[depth];
+                    if path.len() < length && choice[depth] < self.keyboard.neighbors[key].len() {
+                        let next = self.keyboard.neighbors[key][choice[depth]];
+                        choice[depth] += 1;
+                        path.push(next);
+                        choice[depth + 1] = 0;
+                        break;
+                    }
+                    path.pop();
+                    if path.is_empty() {
+                        start += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn emit<W: Write + ?Sized>(
+        &self,
+        path: &[usize],
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        let masks: Vec<u64> = match self.shift {
+            ShiftMode::None => vec![0],
+            ShiftMode::All => vec![0, u64::MAX],
+            ShiftMode::Mixed => (0..1u64 << path.len()).collect(),
+        };
+        let mut word = String::new();
+        for mask in masks {
+            word.clear();
+            for (i, &key) in path.iter().enumerate() {
+                let (base, shifted) = self.keyboard.keys[key];
+                word.push(if mask >> i & 1 == 1 { shifted } else { base });
+            }
+            write_word(writer, word.as_bytes(), progress, line_ending)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use crate::charset::Charset;
+use crate::config::{Config, LengthPolicy, PadSide, Padding};
+use crate::template::parse_template;
+use std::collections::HashSet;
+use std::io;
+
+/// One charset per output position. Every run enumerates a sequence of layouts:
+/// one per length in plain mode, or those a template expands to.
+pub type Layout = Vec<Charset>;
+
+pub fn layouts(config: &Config) -> io::Result<Vec<Layout>> {
+    let charset = config.charset()?;
+    let excluded = config.excluded()?;
+    let layouts = if let Some(template) = &config.template {
+        parse_template(template, &charset, &config.placeholders, &config.wordlist)?
+    } else {
+        (config.min_len..=config.max_len)
+            .map(|len| vec![charset.clone(); len])
+            .collect()
+    };
+
+    // Excluded symbols also apply to literals and custom placeholders.
+    // Suffixes are one more position after them, and padding fixed
+    // positions that every count and rank then includes. The required
+    // start and end are pinned on the word as written, padding and all;
+    // layouts that cannot take them are dropped.
+    let shortest = layouts.iter().map(Vec::len).min().unwrap_or(0);
+    Ok(layouts
+        .into_iter()
+        .map(|layout| {
+            layout
+                .into_iter()
+                .enumerate()
+                .map(|(pos, charset)| {
+                    let charset = charset.without(&excluded);
+                    match &config.frequencies {
+                        Some(frequencies) => frequencies.order(&charset, pos),
+                        None => charset,
+                    }
+                })
+                .collect()
+        })
+        .map(|mut layout: Layout| {
+            // Every length of plain mode has the same charset, so a longer
+            // word that starts with the padding is a shorter one padded.
+            let overlaps = config.template.is_none() && layout.len() > shortest;
+            if !config.suffixes.is_empty() {
+                layout.push(Charset::from_symbols(config.suffixes.clone()));
+            }
+            match &config.padding {
+                Some(padding) => pad(layout, padding, overlaps),
+                None => layout,
+            }
+        })
+        .flat_map(|layout| pin_affixes(layout, config.starts_with.as_deref(), config.ends_with.as_deref()))
+        .fold(Vec::new(), |mut bounded, layout| {
+            match config.length_policy {
+                Some(policy) => bound(layout, config.min_len, config.max_len, policy, &mut bounded),
+                None => bounded.push(layout),
+            }
+            bounded
+        }))
+}
+
+/// Characters in `symbol`, or bytes if it is not UTF-8.
+fn symbol_width(symbol: &[u8]) -> usize {
+    std::str::from_utf8(symbol).map_or(symbol.len(), |symbol| symbol.chars().count())
+}
+
+/// The fewest and most characters a word of `layout` has; positions drawn
+/// from a wordlist vary with the word.
+pub fn word_lengths(layout: &[Charset]) -> (usize, usize) {
+    layout.iter().fold((0, 0), |(shortest, longest), charset| {
+        let widths = charset.symbols().iter().map(|symbol| symbol_width(symbol));
+        (
+            shortest + widths.clone().min().unwrap_or(0),
+            longest + widths.max().unwrap_or(0),
+        )
+    })
+}
+
+/// The most characters the positions after each one of `layout` can add,
+/// which is what filters pruning a prefix have left to work with.
+pub fn room_after(layout: &[&Charset]) -> Vec<usize> {
+    let mut room = vec![0; layout.len()];
+    for pos in (1..layout.len()).rev() {
+        let widest = layout[pos].symbols().iter().map(|symbol| symbol_width(symbol)).max();
+        room[pos - 1] = room[pos] + widest.unwrap_or(0);
+    }
+    room
+}
+
+/// `charset` split into the symbols of each width, narrowest first.
+fn by_width(charset: &Charset) -> Vec<Charset> {
+    let mut widths: Vec<usize> = charset.symbols().iter().map(|symbol| symbol_width(symbol)).collect();
+    widths.sort_unstable();
+    widths.dedup();
+    widths
+        .into_iter()
+        .map(|width| {
+            let symbols = charset.symbols().iter().filter(|symbol| symbol_width(symbol) == width);
+            Charset::from_symbols(symbols.cloned().collect())
+        })
+        .collect()
+}
+
+fn varies(charset: &Charset) -> bool {
+    charset
+        .symbols()
+        .windows(2)
+        .any(|pair| symbol_width(&pair[0]) != symbol_width(&pair[1]))
+}
+
+/// Adds the words of `layout` that are `min..=max` characters long to
+/// `bounded` as layouts of their own, cutting longer ones down first under
+/// [`LengthPolicy::Truncate`]. Order is kept unless more than one position
+/// varies in width, which are then taken a width at a time.
+fn bound(layout: Layout, min: usize, max: usize, policy: LengthPolicy, bounded: &mut Vec<Layout>) {
+    let (shortest, longest) = word_lengths(&layout);
+    if longest > max && policy == LengthPolicy::Truncate {
+        for layout in truncate(layout, max) {
+            bound(layout, min, max, LengthPolicy::Skip, bounded);
+        }
+        return;
+    }
+    if longest < min || shortest > max {
+        return;
+    }
+    if shortest >= min && longest <= max {
+        if !bounded.contains(&layout) {
+            bounded.push(layout);
+        }
+        return;
+    }
+    let varying: Vec<usize> = (0..layout.len()).filter(|&pos| varies(&layout[pos])).collect();
+    if let [pos] = varying[..] {
+        // The other positions are fixed, so the words that fit are those
+        // with a symbol of the right width here.
+        let fixed = shortest - word_lengths(&layout[pos..=pos]).0;
+        let fits = |symbol: &Vec<u8>| (min..=max).contains(&(fixed + symbol_width(symbol)));
+        let symbols: Vec<Vec<u8>> = layout[pos].symbols().iter().filter(|symbol| fits(symbol)).cloned().collect();
+        if !symbols.is_empty() {
+            let mut layout = layout;
+            layout[pos] = Charset::from_symbols(symbols);
+            bounded.push(layout);
+        }
+        return;
+    }
+    for charset in by_width(&layout[varying[0]]) {
+        let mut narrowed = layout.clone();
+        narrowed[varying[0]] = charset;
+        bound(narrowed, min, max, policy, bounded);
+    }
+}
+
+/// `layout` with every word cut to at most `max` characters, as layouts
+/// whose positions before the cut each have a single width.
+fn truncate(mut layout: Layout, max: usize) -> Vec<Layout> {
+    let mut used = 0;
+    for pos in 0..layout.len() {
+        if varies(&layout[pos]) {
+            return by_width(&layout[pos])
+                .into_iter()
+                .flat_map(|charset| {
+                    let mut narrowed = layout.clone();
+                    narrowed[pos] = charset;
+                    truncate(narrowed, max)
+                })
+                .collect();
+        }
+        let width = word_lengths(&layout[pos..=pos]).1;
+        if used + width > max {
+            let keep = max - used;
+            layout.truncate(pos + usize::from(keep > 0));
+            if keep > 0 {
+                let mut seen = HashSet::new();
+                let cut: Vec<Vec<u8>> = layout[pos]
+                    .symbols()
+                    .iter()
+                    .map(|symbol| cut_symbol(symbol, keep))
+                    .filter(|symbol| seen.insert(symbol.clone()))
+                    .collect();
+                layout[pos] = Charset::from_symbols(cut);
+            }
+            break;
+        }
+        used += width;
+    }
+    vec![layout]
+}
+
+/// The first `chars` characters of `symbol`, or bytes if it is not UTF-8.
+fn cut_symbol(symbol: &[u8], chars: usize) -> Vec<u8> {
+    match std::str::from_utf8(symbol) {
+        Ok(text) => text.chars().take(chars).collect::<String>().into_bytes(),
+        Err(_) => symbol[..chars.min(symbol.len())].to_vec(),
+    }
+}
+
+/// `layout` narrowed to the words that start with `prefix` and end with
+/// `suffix`, as one layout per way of lining them up with the positions.
+/// Empty if no word can take them.
+fn pin_affixes(layout: Layout, prefix: Option<&str>, suffix: Option<&str>) -> Vec<Layout> {
+    let mut pinned = Vec::new();
+    pin(layout, 0, prefix.unwrap_or("").as_bytes(), false, &mut pinned);
+    let mut both = Vec::new();
+    for layout in pinned {
+        let last = layout.len();
+        pin(layout, last, suffix.unwrap_or("").as_bytes(), true, &mut both);
+    }
+    both
+}
+
+/// Adds `layout` to `pinned` with its words made to start with `affix` from
+/// position `pos` on, or, `from_end`, to end with it before position `pos`.
+///
+/// Symbols that take all that is left of `affix` keep their position
+/// together; one that only takes its start fixes its position and the rest
+/// goes on to the next, so positions of several widths split the layout.
+fn pin(layout: Layout, pos: usize, affix: &[u8], from_end: bool, pinned: &mut Vec<Layout>) {
+    if affix.is_empty() {
+        pinned.push(layout);
+        return;
+    }
+    let at = if from_end { pos.checked_sub(1) } else { Some(pos) };
+    let Some(at) = at.filter(|&at| at < layout.len()) else {
+        return;
+    };
+    let covers = |symbol: &[u8]| match from_end {
+        false => symbol.starts_with(affix),
+        true => symbol.ends_with(affix),
+    };
+    let part_of = |symbol: &[u8]| match from_end {
+        false => affix.starts_with(symbol),
+        true => affix.ends_with(symbol),
+    };
+    // Runs of symbols that cover the affix stay one position, in their order.
+    let mut covering = Vec::new();
+    for symbol in layout[at].symbols() {
+        if covers(symbol) {
+            covering.push(symbol.to_vec());
+            continue;
+        }
+        if !covering.is_empty() {
+            let mut narrowed = layout.clone();
+            narrowed[at] = Charset::from_symbols(std::mem::take(&mut covering));
+            pinned.push(narrowed);
+        }
+        if part_of(symbol) {
+            let mut narrowed = layout.clone();
+            narrowed[at] = Charset::literal(symbol);
+            let rest = match from_end {
+                false => &affix[symbol.len()..],
+                true => &affix[..affix.len() - symbol.len()],
+            };
+            let next = if from_end { at } else { at + 1 };
+            pin(narrowed, next, rest, from_end, pinned);
+        }
+    }
+    if !covering.is_empty() {
+        let mut narrowed = layout;
+        narrowed[at] = Charset::from_symbols(covering);
+        pinned.push(narrowed);
+    }
+}
+
+/// `layout` widened to `padding.width` positions with the padding symbol.
+/// With `overlaps`, the position next to the padding loses the padding
+/// symbol, so the words a shorter layout pads to are not made twice.
+fn pad(mut layout: Layout, padding: &Padding, overlaps: bool) -> Layout {
+    if overlaps && layout.len() <= padding.width {
+        let next = match padding.side {
+            PadSide::Left => layout.first_mut(),
+            PadSide::Right => layout.last_mut(),
+        };
+        if let Some(next) = next {
+            *next = next.without(&Charset::literal(&padding.symbol));
+        }
+    }
+    let missing = padding.width.saturating_sub(layout.len());
+    let fill = std::iter::repeat_n(Charset::literal(&padding.symbol), missing);
+    match padding.side {
+        PadSide::Left => fill.chain(layout).collect(),
+        PadSide::Right => {
+            layout.extend(fill);
+            layout
+        }
+    }
+}
@@ -0,0 +1,48 @@
+pub mod analyze;
+pub mod bench;
+pub mod bloom;
+pub mod charset;
+pub mod classes;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod compress;
+pub mod config;
+pub mod dedup;
+pub mod entropy;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod format;
+pub mod frequency;
+pub mod generator;
+pub mod input;
+pub mod interleave;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod interrupt;
+pub mod keyboard;
+pub mod layout;
+pub mod merge;
+pub mod mutate;
+pub mod omen;
+pub mod output;
+pub mod patterns;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod prealloc;
+pub mod prince;
+pub mod profile;
+pub mod progress;
+#[cfg(feature = "python")]
+mod python;
+pub mod rank;
+pub mod serve;
+pub mod shuffle;
+pub mod size;
+pub mod stream;
+pub mod suffix;
+pub mod target;
+pub mod template;
+pub mod transform;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use config::{Config, LengthBounds, LengthPolicy, LineEnding, PadSide, Padding};
@@ -1,344 +1,2494 @@
-use clap::{Arg, Command};
-use std::fs::File;
-use std::io::{self, BufWriter, Write};
-use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use crunch_rs::analyze::Analysis;
+use crunch_rs::bench::{run_bench, BenchOutput};
+use crunch_rs::bloom::{BloomFilter, DEFAULT_FALSE_POSITIVE_RATE};
+use crunch_rs::charset::{aliases, escape, repeated_symbols, resolve_alias, unescape, Charset};
+use crunch_rs::classes::{parse_requirements, ClassRequirement};
+use crunch_rs::compress::{
+    extract, index_path, Compression, ParallelEncoder, SeekIndex, DEFAULT_BLOCK_SIZE, DEFAULT_FRAME_WORDS,
+};
+use crunch_rs::dedup::{SeenWords, Unique, DEFAULT_UNIQUE_MEMORY};
+use crunch_rs::error::Error;
+use crunch_rs::filter::Filters;
+use crunch_rs::format::{RecordFormat, Records};
+use crunch_rs::frequency::Frequencies;
+use crunch_rs::generator::{generate_words_window, write_word, Window};
+use crunch_rs::input::{for_each_line, open_input, read_lines, DEFAULT_BUFFER_SIZE};
+use crunch_rs::interleave::DEFAULT_INTERLEAVE_BATCH;
+use crunch_rs::interrupt;
+use crunch_rs::keyboard::{Keyboard, KeyboardLayout, ShiftMode, Walks};
+use crunch_rs::merge::{MergeOrder, Merger, DEFAULT_MEMORY};
+use crunch_rs::mutate::{CaseMode, Mutator, Rule};
+use crunch_rs::omen::{Model, Omen, Trainer};
+use crunch_rs::output::{
+    available_space, check_writable, count_lines, parse_duration, parse_rate, parse_size, spawn_pipe, truncate_partial_line, OutputBuffer,
+    Interruptible, LengthSplitter, Rate, SizeLimit, SplitLimits, Splitter, Tee, Throttle,
+    DEFAULT_NAME_TEMPLATE, DEFAULT_WRITE_BUFFER_SIZE,
+};
+use crunch_rs::patterns::{Date, DateRange, PhoneNumbers, PinRange};
+use crunch_rs::prealloc::{self, write_preallocated};
+use crunch_rs::prince::Prince;
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::serve::serve;
+use crunch_rs::profile::{load_profile, Profile};
+use crunch_rs::progress::{format_duration, FileCounts, Progress, ProgressFormat, Report, Reporter};
+use crunch_rs::size::{calculate_size, estimate, estimate_is_exact, format_size, Estimate};
+use crunch_rs::suffix::{parse_numbers, suffixes};
+use crunch_rs::target::{wordlist, Target, TargetRules};
+use crunch_rs::transform::{TransformCommand, DEFAULT_TRANSFORM_BATCH};
+use crunch_rs::{Config, LengthBounds, LengthPolicy, LineEnding, PadSide, Padding};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration;
 
-const DIGITS: &str = "0123456789";
+fn parse_placeholder(value: &str) -> Result<(char, String), String> {
+    let (key, chars) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=CHARS, got '{}'", value))?;
+    let mut key_chars = key.chars();
+    match (key_chars.next(), key_chars.next()) {
+        (Some(key), None) if key != '?' => Ok((key, chars.to_string())),
+        _ => Err(format!("placeholder key must be a single character other than '?', got '{}'", key)),
+    }
+}
+
+#[cfg(unix)]
+fn progress_sink(fd: Option<i32>) -> io::Result<Box<dyn Write + Send>> {
+    use std::os::unix::io::FromRawFd;
+
+    match fd {
+        // SAFETY: the caller hands us ownership of an open descriptor, e.g. `3>progress.log`.
+        Some(fd) => Ok(Box::new(unsafe { File::from_raw_fd(fd) })),
+        None => Ok(Box::new(io::stderr())),
+    }
+}
+
+#[cfg(not(unix))]
+fn progress_sink(fd: Option<i32>) -> io::Result<Box<dyn Write + Send>> {
+    match fd {
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "--progress-fd is only supported on unix",
+        )),
+        None => Ok(Box::new(io::stderr())),
+    }
+}
+
+fn line_ending_args() -> Vec<Arg> {
+    vec![
+        Arg::new("line-ending")
+            .long("line-ending")
+            .value_parser(["lf", "crlf", "nul", "none"])
+            .default_value("lf")
+            .help("What follows each word"),
+        Arg::new("null")
+            .short('0')
+            .long("null")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("line-ending")
+            .help("Separate words with NUL bytes (same as --line-ending nul), e.g. for xargs -0"),
+    ]
+}
 
-#[derive(Debug)]
-struct Config {
-    min_len: usize,
-    max_len: usize,
-    charset: String,
-    template: Option<String>,
+/// Whether a switch is on: `--<on>` or `--<off>` from the command line, or
+/// else the profile's setting, or else off.
+fn switch(matches: &ArgMatches, on: &str, off: &str, profile: Option<bool>) -> bool {
+    if matches.get_flag(on) {
+        true
+    } else if matches.get_flag(off) {
+        false
+    } else {
+        profile.unwrap_or(false)
+    }
+}
+
+/// The line ending from [`line_ending_args`], or the profile's when neither was given.
+fn resolve_line_ending(matches: &ArgMatches, profile: &Profile) -> io::Result<LineEnding> {
+    if matches.get_flag("null") {
+        return Ok(LineEnding::Nul);
+    }
+    let name = match &profile.line_ending {
+        Some(name) if matches.value_source("line-ending") != Some(ValueSource::CommandLine) => name,
+        _ => matches.get_one::<String>("line-ending").unwrap(),
+    };
+    LineEnding::parse(name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("unknown line ending '{}'", name)))
+}
+
+/// Options shared by every command that writes a wordlist.
+fn output_args() -> Vec<Arg> {
+    let mut args = vec![
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .action(clap::ArgAction::Append)
+            .help("Output file name, repeatable to write several copies at once; '-' is stdout (with -b/-c: directory for the chunk files)"),
+        Arg::new("archive")
+            .long("archive")
+            .conflicts_with("output")
+            .help("Write into a .zip or .7z archive, one member per -b/-c chunk"),
+        Arg::new("pipe")
+            .long("pipe")
+            .conflicts_with_all(["archive", "split-bytes", "split-lines"])
+            .help("Stream words into the stdin of this shell command, e.g. 'aircrack-ng -w - capture.cap', alongside any -o"),
+        Arg::new("buffer-size")
+            .long("buffer-size")
+            .value_parser(parse_size)
+            .help("Output buffer size, e.g. 64KiB or 4MiB (default 1 MiB)"),
+        Arg::new("max-size")
+            .long("max-size")
+            .value_parser(parse_size)
+            .help("Stop cleanly once this much has been written, e.g. 100gb"),
+        Arg::new("duration")
+            .long("duration")
+            .value_parser(parse_duration)
+            .help("Stop cleanly after this much wall-clock time, e.g. 2h or 1h30m"),
+        Arg::new("force")
+            .long("force")
+            .action(clap::ArgAction::SetTrue)
+            .help("Write even if the output is projected not to fit on disk"),
+        Arg::new("rate")
+            .long("rate")
+            .value_parser(parse_rate)
+            .help("Throttle output to this many words (50000/s) or bytes (10MB/s) per second"),
+        Arg::new("split-bytes")
+            .short('b')
+            .long("split-bytes")
+            .value_parser(parse_size)
+            .help("Start a new chunk file once this many bytes are written, e.g. 100mb or 1GiB"),
+        Arg::new("split-lines")
+            .short('c')
+            .long("split-lines")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help("Start a new chunk file every this many words"),
+        Arg::new("split-by-length")
+            .long("split-by-length")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("no-split-by-length")
+            .conflicts_with_all(["split-bytes", "split-lines", "archive", "pipe"])
+            .help("Write the words of each length to their own file: -o out.txt makes out-4.txt, out-5.txt, ..."),
+        Arg::new("no-split-by-length")
+            .long("no-split-by-length")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("split-by-length")
+            .help("Write every length to the same output, overriding a profile's split-by-length"),
+        Arg::new("name-template")
+            .long("name-template")
+            .default_value(DEFAULT_NAME_TEMPLATE)
+            .help("Chunk file name using {first}, {last}, {range}, {index} and {timestamp}"),
+        Arg::new("compress")
+            .short('z')
+            .long("compress")
+            .value_parser(["gzip", "zstd"])
+            .help("Compress -o files (adding .gz or .zst) or stdout, on several threads"),
+        Arg::new("compress-threads")
+            .long("compress-threads")
+            .value_parser(clap::value_parser!(usize))
+            .help("Threads compressing -z output (default: every available core)"),
+        Arg::new("index-every")
+            .long("index-every")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help("Words per frame of -z zstd files, listed in the FILE.idx written beside them for `extract` (default 100000)"),
+        Arg::new("unique")
+            .long("unique")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("no-unique")
+            .help("Drop words already written in this run, e.g. identical mutations of different inputs"),
+        Arg::new("no-unique")
+            .long("no-unique")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("unique")
+            .help("Keep repeated words, overriding a profile's unique"),
+        Arg::new("unique-memory")
+            .long("unique-memory")
+            .value_parser(parse_size)
+            .help("Memory --unique keeps seen words in before spilling them to disk, e.g. 1GiB (default 256 MiB)"),
+        Arg::new("record-format")
+            .long("format")
+            .value_parser(["jsonl", "csv"])
+            .help("Write each word as a record with its index, length, mask and composition"),
+        Arg::new("transform-cmd")
+            .long("transform-cmd")
+            .value_name("COMMAND")
+            .help("Pipe the words through COMMAND in batches; the lines it prints replace them, e.g. 'sed s/a/@/'"),
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("no-quiet")
+            .help("Suppress the size estimate and progress output on stderr"),
+        Arg::new("no-quiet")
+            .long("no-quiet")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("quiet")
+            .help("Show the size estimate and progress, overriding a profile's quiet"),
+        Arg::new("progress-format")
+            .long("progress-format")
+            .value_parser(["text", "json"])
+            .default_value("text")
+            .help("Progress output format (json emits one event per line plus a final summary)"),
+        Arg::new("progress-fd")
+            .long("progress-fd")
+            .value_parser(clap::value_parser!(i32))
+            .help("Write progress to this already-open file descriptor instead of stderr (unix only)"),
+        Arg::new("stats-file")
+            .long("stats-file")
+            .help("Also write the end-of-run report to this file as JSON, even with -q"),
+    ];
+    args.extend(line_ending_args());
+    args
+}
+
+/// Where and how a command writes its wordlist, from [`output_args`]
+/// optionally overlaid with a profile.
+#[derive(Clone)]
+struct OutputSettings {
     output: Option<String>,
-    no_duplicates: bool,
+    /// Further `-o` destinations, written alongside `output` through a [`Tee`].
+    tee: Vec<String>,
+    pipe: Option<String>,
+    archive: Option<String>,
+    rate: Option<Rate>,
+    buffer_size: usize,
+    max_size: Option<u64>,
+    duration: Option<Duration>,
+    force: bool,
+    limits: SplitLimits,
+    split_by_length: bool,
+    name_template: String,
+    compression: Option<Compression>,
+    compress_threads: usize,
+    /// Words per frame of indexed zstd files.
+    frame_words: u64,
+    /// With `--unique`, the memory for seen words before they spill to disk.
+    unique: Option<usize>,
+    /// Program every word is piped through before it is written.
+    transform_cmd: Option<String>,
+    /// Records to encode the words as instead of plain lines.
+    format: Option<RecordFormat>,
+    quiet: bool,
+    progress_format: ProgressFormat,
+    progress_fd: Option<i32>,
+    stats_file: Option<String>,
+    line_ending: LineEnding,
+    /// Add to an existing output file instead of replacing it.
+    append: bool,
+    /// Where in the keyspace a command that accepts `--continue` starts, for
+    /// the hint printed when it stops early and the report's resume index.
+    resume_from: Option<u64>,
 }
 
-struct Progress {
-    current: Arc<AtomicU64>,
-    total: u64,
-    last_percentage: Arc<AtomicU64>,
+fn parse_progress_format(value: &str) -> ProgressFormat {
+    match value {
+        "json" => ProgressFormat::Json,
+        _ => ProgressFormat::Text,
+    }
 }
 
-impl Progress {
-    fn new(total: u64) -> Self {
-        Progress {
-            current: Arc::new(AtomicU64::new(0)),
-            total,
-            last_percentage: Arc::new(AtomicU64::new(0)),
+impl OutputSettings {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let mut outputs = matches.get_many::<String>("output").unwrap_or_default().cloned();
+        OutputSettings {
+            output: outputs.next(),
+            tee: outputs.collect(),
+            pipe: matches.get_one::<String>("pipe").cloned(),
+            archive: matches.get_one::<String>("archive").cloned(),
+            rate: matches.get_one::<Rate>("rate").copied(),
+            buffer_size: matches
+                .get_one::<u64>("buffer-size")
+                .map_or(DEFAULT_WRITE_BUFFER_SIZE, |&size| size as usize),
+            max_size: matches.get_one::<u64>("max-size").copied(),
+            duration: matches.get_one::<Duration>("duration").copied(),
+            force: matches.get_flag("force"),
+            limits: SplitLimits {
+                max_lines: matches.get_one::<u64>("split-lines").copied(),
+                max_bytes: matches.get_one::<u64>("split-bytes").copied(),
+            },
+            split_by_length: matches.get_flag("split-by-length"),
+            name_template: matches.get_one::<String>("name-template").unwrap().clone(),
+            compression: matches.get_one::<String>("compress").and_then(|name| Compression::parse(name)),
+            compress_threads: matches.get_one::<usize>("compress-threads").copied().unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |cores| cores.get())
+            }),
+            frame_words: matches.get_one::<u64>("index-every").copied().unwrap_or(DEFAULT_FRAME_WORDS),
+            unique: matches.get_flag("unique").then(|| {
+                matches
+                    .get_one::<u64>("unique-memory")
+                    .map_or(DEFAULT_UNIQUE_MEMORY, |&memory| memory as usize)
+            }),
+            transform_cmd: matches.get_one::<String>("transform-cmd").cloned(),
+            // `patterns dates` has a --format of its own.
+            format: matches
+                .try_get_one::<String>("record-format")
+                .ok()
+                .flatten()
+                .and_then(|name| RecordFormat::parse(name)),
+            quiet: matches.get_flag("quiet"),
+            progress_format: parse_progress_format(matches.get_one::<String>("progress-format").unwrap()),
+            progress_fd: matches.get_one::<i32>("progress-fd").copied(),
+            stats_file: matches.get_one::<String>("stats-file").cloned(),
+            line_ending: resolve_line_ending(matches, &Profile::default()).unwrap(),
+            append: false,
+            resume_from: None,
+        }
+    }
+
+    /// Fills in everything not given on the command line from `profile`.
+    fn with_profile(mut self, matches: &ArgMatches, profile: &Profile) -> io::Result<Self> {
+        let from_cli = |id| matches.value_source(id) == Some(ValueSource::CommandLine);
+        // A destination on the command line replaces the profile's, whatever its kind.
+        if self.output.is_none() && self.pipe.is_none() && self.archive.is_none() {
+            self.output = profile.output.clone();
+            self.pipe = profile.pipe.clone();
+            self.archive = profile.archive.clone();
+        }
+        if let (None, Some(rate)) = (self.rate, &profile.rate) {
+            self.rate = Some(parse_rate(rate).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?);
+        }
+        if let (false, Some(size)) = (from_cli("buffer-size"), &profile.buffer_size) {
+            self.buffer_size =
+                parse_size(size).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))? as usize;
         }
+        if let (None, Some(size)) = (self.max_size, &profile.max_size) {
+            self.max_size = Some(parse_size(size).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?);
+        }
+        if let (None, Some(duration)) = (self.duration, &profile.duration) {
+            self.duration =
+                Some(parse_duration(duration).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?);
+        }
+        self.limits.max_lines = self.limits.max_lines.or(profile.split_lines);
+        if self.limits.max_bytes.is_none() {
+            if let Some(size) = &profile.split_bytes {
+                self.limits.max_bytes = Some(
+                    parse_size(size).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+                );
+            }
+        }
+        self.split_by_length = switch(matches, "split-by-length", "no-split-by-length", profile.split_by_length);
+        if let (false, Some(template)) = (from_cli("name-template"), &profile.name_template) {
+            self.name_template = template.clone();
+        }
+        if let (None, Some(name)) = (self.compression, &profile.compress) {
+            self.compression = Some(Compression::parse(name).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("unknown compression '{}'", name))
+            })?);
+        }
+        if let (false, Some(threads)) = (from_cli("compress-threads"), profile.compress_threads) {
+            self.compress_threads = threads;
+        }
+        if let (false, Some(words)) = (from_cli("index-every"), profile.index_every) {
+            self.frame_words = words.max(1);
+        }
+        if self.unique.is_none() && switch(matches, "unique", "no-unique", profile.unique) {
+            self.unique = Some(DEFAULT_UNIQUE_MEMORY);
+        }
+        if let (Some(unique), false, Some(memory)) =
+            (&mut self.unique, from_cli("unique-memory"), &profile.unique_memory)
+        {
+            *unique = parse_size(memory).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))? as usize;
+        }
+        if self.transform_cmd.is_none() {
+            self.transform_cmd = profile.transform_cmd.clone();
+        }
+        if let (None, Some(name)) = (self.format, &profile.format) {
+            self.format = Some(RecordFormat::parse(name).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("unknown format '{}'", name))
+            })?);
+        }
+        self.quiet = switch(matches, "quiet", "no-quiet", profile.quiet);
+        if let (false, Some(format)) = (from_cli("progress-format"), &profile.progress_format) {
+            self.progress_format = parse_progress_format(format);
+        }
+        if self.stats_file.is_none() {
+            self.stats_file = profile.stats_file.clone();
+        }
+        self.line_ending = resolve_line_ending(matches, profile)?;
+        Ok(self)
     }
 
-    fn increment(&self) {
-        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
-        let percentage = (current as f64 / self.total as f64 * 100.0) as u64;
-        let last_percentage = self.last_percentage.load(Ordering::SeqCst);
+    /// These settings with `-z`'s extension added to the output file names.
+    fn with_compressed_names(&self, compression: Compression) -> Self {
+        let rename = |output: &String| {
+            if output == "-" || output.ends_with(compression.extension()) {
+                output.clone()
+            } else {
+                format!("{}{}", output, compression.extension())
+            }
+        };
+        OutputSettings {
+            output: self.output.as_ref().map(rename),
+            tee: self.tee.iter().map(rename).collect(),
+            ..self.clone()
+        }
+    }
 
-        if percentage >= last_percentage + 5 {
-            self.last_percentage.store(percentage, Ordering::SeqCst);
-            println!("{}% done", percentage);
+    /// `inner` behind the output buffer, or with `-z` behind the compressor,
+    /// which collects its own blocks.
+    fn sink<W: Write + 'static>(&self, inner: W) -> Box<dyn Write> {
+        match self.compression {
+            Some(compression) => Box::new(ParallelEncoder::new(
+                inner,
+                compression,
+                self.compress_threads,
+                DEFAULT_BLOCK_SIZE,
+            )),
+            None => Box::new(OutputBuffer::with_capacity(self.buffer_size, inner)),
         }
     }
-}
 
-fn has_consecutive_duplicates(word: &str) -> bool {
-    let chars: Vec<char> = word.chars().collect();
-    for i in 0..chars.len() - 1 {
-        // Skip checking digits
-        if !chars[i].is_digit(10) && !chars[i + 1].is_digit(10) {
-            if chars[i] == chars[i + 1] {
-                return true;
+    /// The `-o` file `path` behind [`sink`](Self::sink). With `-z zstd` its
+    /// frames hold whole words and are listed in a seek index beside it.
+    fn file_sink(&self, path: &str) -> io::Result<Box<dyn Write>> {
+        let file = open_output(path, self.append)?;
+        match self.compression {
+            Some(compression @ Compression::Zstd) if self.line_ending != LineEnding::None => {
+                let index = index_path(Path::new(path));
+                let index_file =
+                    File::create(&index).map_err(|err| Error::output(index.display().to_string(), err))?;
+                Ok(Box::new(ParallelEncoder::indexed(
+                    file,
+                    compression,
+                    self.compress_threads,
+                    index_file,
+                    self.frame_words,
+                    self.line_ending,
+                )?))
             }
+            _ => Ok(self.sink(file)),
         }
     }
-    false
 }
 
-fn calculate_template_size_no_duplicates(template: &str, charset: &str) -> u64 {
-    let mut total: u64 = 1;
-    let mut last_was_char = false;
-    
-    for (_i, c) in template.chars().enumerate() {
-        match c {
-            '@' => {
-                if last_was_char {
-                    // If previous position was also a character,
-                    // we can't use the same character as the previous position
-                    total *= charset.len() as u64 - 1;
+/// Refuses to start, or with `--force` warns, when the projected output will
+/// not fit on the filesystem it is written to. Compressed output is not
+/// checked, as its size cannot be told in advance.
+fn check_disk_space(settings: &OutputSettings, total: Estimate) -> io::Result<()> {
+    if settings.compression.is_some() {
+        return Ok(());
+    }
+    let targets = settings
+        .archive
+        .iter()
+        .chain(&settings.output)
+        .chain(&settings.tee)
+        .filter(|target| *target != "-");
+    let needed = settings.max_size.map_or(total.bytes, |max_size| total.bytes.min(max_size));
+    for target in targets {
+        let free = available_space(Path::new(target))?;
+        if needed <= free {
+            continue;
+        }
+        let message = format!(
+            "{} needs about {} but only {} is free",
+            target,
+            format_size(needed),
+            format_size(free)
+        );
+        if !settings.force {
+            let full = io::Error::new(
+                io::ErrorKind::StorageFull,
+                format!("{} (use --max-size to cap the output or --force to write anyway)", message),
+            );
+            return Err(Error::output(target.as_str(), full).into());
+        }
+        eprintln!("Warning: {}", message);
+    }
+    Ok(())
+}
+
+/// Shows `progress` on stderr or `--progress-fd` until finished, unless quiet.
+fn spawn_reporter(settings: &OutputSettings, progress: &Arc<Progress>) -> io::Result<Option<Reporter>> {
+    if settings.quiet {
+        return Ok(None);
+    }
+    let sink = progress_sink(settings.progress_fd)?;
+    Ok(Some(Reporter::spawn(
+        Arc::clone(progress),
+        settings.progress_format,
+        sink,
+        Duration::from_millis(500),
+    )))
+}
+
+/// Writes `report` to `--stats-file`, if one was given.
+fn write_stats(settings: &OutputSettings, report: &Report) -> io::Result<()> {
+    settings.stats_file.as_ref().map_or(Ok(()), |path| {
+        fs::write(path, format!("{}\n", report.to_json())).map_err(|err| Error::output(path.as_str(), err).into())
+    })
+}
+
+/// Prints the size estimate, runs `generate` against the selected output and
+/// reports progress while it runs. `total` is `None` for streamed input.
+///
+/// A reader closing our stdout early (`| head`) ends the run quietly.
+fn run_generation<F>(settings: &OutputSettings, total: Option<Estimate>, generate: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write, &Progress) -> io::Result<()>,
+{
+    let renamed;
+    let settings = match settings.compression {
+        Some(compression) => {
+            if settings.archive.is_some() || settings.pipe.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "-z compresses -o files and stdout; it cannot be combined with --archive or --pipe",
+                ));
+            }
+            renamed = settings.with_compressed_names(compression);
+            &renamed
+        }
+        None => settings,
+    };
+    let output = settings.output.as_ref();
+    let quiet = settings.quiet;
+    let progress_format = settings.progress_format;
+    let limits = settings.limits;
+    let splitting = limits.max_lines.is_some() || limits.max_bytes.is_some();
+    // The splitters take their lines for words, to bucket, count and name files by.
+    if settings.format.is_some() && (splitting || settings.split_by_length || settings.archive.is_some()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--format writes records, which cannot be split; leave out -b, -c, --split-by-length and --archive",
+        ));
+    }
+    if splitting && output.is_none() && settings.archive.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "splitting output requires an output directory (-o) or --archive",
+        ));
+    }
+    if splitting && !settings.tee.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "splitting output writes to a single directory; give -o only once",
+        ));
+    }
+    if splitting && settings.compression.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "-z cannot compress -b/-c chunks; use --archive for a compressed set of chunks",
+        ));
+    }
+    if settings.split_by_length {
+        let single_output = output.is_some_and(|output| output != "-") && settings.tee.is_empty();
+        if !single_output || splitting || settings.archive.is_some() || settings.pipe.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--split-by-length needs a single -o file name and no other splitting, --archive or --pipe",
+            ));
+        }
+    }
+    if (splitting || settings.archive.is_some() || settings.split_by_length)
+        && settings.line_ending == LineEnding::None
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "splitting output needs a line ending to find word boundaries",
+        ));
+    }
+
+    if let Some(archive) = &settings.archive {
+        check_writable(Path::new(archive), false)?;
+    }
+    for target in settings.output.iter().chain(&settings.tee).filter(|target| *target != "-") {
+        check_writable(Path::new(target), splitting && settings.archive.is_none())?;
+    }
+
+    if let (Some(total), false, ProgressFormat::Text) = (total, quiet, progress_format) {
+        eprintln!("Will create approx: {} ({} combinations)", format_size(total.bytes), total.words);
+    }
+    if let Some(total) = total {
+        check_disk_space(settings, total)?;
+    }
+
+    let progress = Arc::new(total.map_or_else(Progress::unbounded, |total| Progress::new(total.words)));
+    let reporter = spawn_reporter(settings, &progress)?;
+
+    // Words are transformed, repeats dropped and the rest encoded first, so the size and rate limits between the
+    // generator and whichever sink is chosen only count words passed on.
+    let mut rewritten = None;
+    let transformed = |writer: &mut dyn Write| match &settings.transform_cmd {
+        Some(command) => {
+            let mut transform =
+                TransformCommand::new(writer, command, DEFAULT_TRANSFORM_BATCH, settings.line_ending)?;
+            let result = generate(&mut transform, &progress).and_then(|()| transform.flush());
+            rewritten = Some(transform.counts());
+            result
+        }
+        None => generate(writer, &progress),
+    };
+    let mut dropped = None;
+    let unique = |writer: &mut dyn Write| match settings.unique {
+        Some(memory) => {
+            let seen = SeenWords::new(memory, std::env::temp_dir());
+            let mut unique = Unique::new(writer, seen, settings.line_ending)?;
+            let result = transformed(&mut unique);
+            dropped = Some((unique.duplicates(), unique.duplicate_bytes()));
+            result
+        }
+        None => transformed(writer),
+    };
+    let mut encoded = None;
+    let records = |writer: &mut dyn Write| match settings.format {
+        Some(format) => {
+            let mut records = Records::new(writer, format, settings.line_ending)?;
+            let result = unique(&mut records);
+            encoded = Some((records.bytes_in(), records.bytes_out()));
+            result
+        }
+        None => unique(writer),
+    };
+    let throttled = |writer: &mut dyn Write| match settings.rate {
+        Some(rate) => records(&mut Throttle::new(writer, rate, settings.line_ending)?),
+        None => records(writer),
+    };
+    let mut stopped = None;
+    let limited = |writer: &mut dyn Write| match settings.max_size {
+        Some(max_size) => {
+            let mut limited = SizeLimit::new(writer, max_size, settings.line_ending)?;
+            let result = throttled(&mut limited);
+            if !limited.is_full() {
+                return result;
+            }
+            stopped = Some((limited.words(), limited.bytes(), limited.last_word().to_vec()));
+            Ok(())
+        }
+        None => throttled(writer),
+    };
+    // Ctrl-C, SIGTERM and --duration stop the words at a boundary, so the
+    // sink is still flushed and finalized below as if the run had ended there.
+    let interruptible = interrupt::install().is_ok() || settings.duration.is_some();
+    if let Some(duration) = settings.duration {
+        interrupt::stop_after(duration);
+    }
+    let mut interrupted = false;
+    let generate = |writer: &mut dyn Write| {
+        if !interruptible {
+            return limited(writer);
+        }
+        let mut writer = Interruptible::new(writer, interrupt::flag(), settings.line_ending);
+        let result = limited(&mut writer);
+        interrupted = writer.is_interrupted();
+        if interrupted {
+            Ok(())
+        } else {
+            result
+        }
+    };
+
+    let mut output_files: Vec<String> = output
+        .into_iter()
+        .chain(&settings.tee)
+        .filter(|output| *output != "-")
+        .cloned()
+        .collect();
+    let mut counts = Vec::new();
+    // Appended to, a file is reported by what this run added.
+    let sizes_before: Vec<(String, u64)> = output_files
+        .iter()
+        .filter(|_| settings.append)
+        .filter_map(|path| Some((path.clone(), fs::metadata(path).ok()?.len())))
+        .collect();
+    let teeing = !settings.tee.is_empty() || (output.is_some() && settings.pipe.is_some());
+    let result = match output {
+        _ if teeing => {
+            let mut tee = Tee::new();
+            for output in output.into_iter().chain(&settings.tee) {
+                if output == "-" {
+                    tee.add("stdout", settings.sink(io::stdout().lock()));
                 } else {
-                    // If previous position was not a character (or first position),
-                    // we can use any character
-                    total *= charset.len() as u64;
+                    tee.add(output.clone(), settings.file_sink(output)?);
                 }
-                last_was_char = true;
             }
-            '%' => {
-                // For digits, we can always use all possibilities
-                total *= 10;
-                last_was_char = false;
+            let mut child = None;
+            if let Some(command) = &settings.pipe {
+                let mut spawned = spawn_pipe(command)?;
+                tee.add(
+                    format!("'{}'", command),
+                    OutputBuffer::with_capacity(settings.buffer_size, spawned.stdin.take().unwrap()),
+                );
+                child = Some((command, spawned));
+            }
+            let written = generate(&mut tee).and_then(|()| tee.flush());
+            // Closes the pipe so the child sees end of input.
+            let mut failed = tee.finish();
+            if let Some((command, mut child)) = child {
+                let status = child.wait()?;
+                if !status.success() {
+                    failed.push((format!("'{}'", command), io::Error::other(format!("exited with {}", status))));
+                }
             }
-            _ => {
-                last_was_char = false;
+            // Like a single output, a reader leaving early is not worth a warning.
+            failed.retain(|(_, err)| err.kind() != io::ErrorKind::BrokenPipe);
+            for (name, err) in &failed {
+                eprintln!("Warning: stopped writing to {}: {}", name, err);
+            }
+            match written {
+                Ok(()) if !failed.is_empty() => Err(io::Error::other(format!(
+                    "{} of the outputs did not receive every word",
+                    failed.len()
+                ))),
+                written => written,
             }
         }
-    }
-    total
-}
+        None if settings.archive.is_some() => {
+            let archive = settings.archive.as_ref().unwrap();
+            let mut splitter =
+                Splitter::into_archive(Path::new(archive), &settings.name_template, limits, settings.line_ending)?;
+            output_files = vec![archive.clone()];
+            generate(&mut splitter).and_then(|()| splitter.finish().map(drop))
+        }
+        None if settings.pipe.is_some() => {
+            let command = settings.pipe.as_deref().unwrap();
+            let mut child = spawn_pipe(command)?;
+            let mut writer = OutputBuffer::with_capacity(settings.buffer_size, child.stdin.take().unwrap());
+            let written = generate(&mut writer).and_then(|()| writer.flush());
+            // Closing stdin lets the child see end of input.
+            drop(writer);
+            let status = child.wait()?;
+            if status.success() {
+                written
+            } else {
+                Err(io::Error::other(format!("'{}' exited with {}", command, status)))
+            }
+        }
+        Some(output) if settings.split_by_length => {
+            let open = |path: &Path| settings.file_sink(&path.to_string_lossy());
+            let mut splitter = LengthSplitter::new(Path::new(output), settings.line_ending, open)?;
+            generate(&mut splitter).and_then(|()| {
+                let files = splitter.finish()?;
+                output_files = files.iter().map(|file| file.path.display().to_string()).collect();
+                counts = files
+                    .into_iter()
+                    .map(|file| FileCounts {
+                        path: file.path.display().to_string(),
+                        words: Some(file.words),
+                        bytes: file.bytes,
+                    })
+                    .collect();
+                Ok(())
+            })
+        }
+        Some(output) if splitting => {
+            let mut splitter = Splitter::new(
+                Path::new(output),
+                &settings.name_template,
+                limits,
+                settings.line_ending,
+            )?;
+            generate(&mut splitter).and_then(|()| {
+                let files = splitter.finish()?;
+                output_files = files.iter().map(|file| file.display().to_string()).collect();
+                Ok(())
+            })
+        }
+        Some(output) if output != "-" => {
+            let mut writer = settings.file_sink(output)?;
+            generate(&mut writer).and_then(|()| writer.flush())
+        }
+        _ => {
+            // Lock once for the whole run instead of on every write.
+            let mut writer = settings.sink(io::stdout().lock());
+            generate(&mut writer).and_then(|()| writer.flush())
+        }
+    };
 
-fn calculate_combinations_no_duplicates(length: u32, charset_len: u32) -> u64 {
-    if length == 0 {
-        return 1;
+    // Files that were not counted as they were written are measured, so -z
+    // output is reported at its compressed size.
+    if counts.is_empty() {
+        counts = output_files
+            .iter()
+            .filter_map(|path| {
+                let before = sizes_before.iter().find(|(before, _)| before == path).map_or(0, |(_, size)| *size);
+                Some(FileCounts {
+                    path: path.clone(),
+                    words: None,
+                    bytes: fs::metadata(path).ok()?.len().saturating_sub(before),
+                })
+            })
+            .collect();
     }
-    if length == 1 {
-        return charset_len as u64;
+    let mut report = progress.report(counts, None);
+    if let Some(rewritten) = rewritten {
+        report.words = report.words - rewritten.words_in + rewritten.words_out;
+        report.bytes = report.bytes - rewritten.bytes_in + rewritten.bytes_out;
     }
-
-    // For each position after the first:
-    // - If we use a different character than the previous position, we have (charset_len - 1) choices
-    // First position can use any character (charset_len)
-    let mut total = charset_len as u64;
-    for _ in 1..length {
-        total *= (charset_len - 1) as u64;
+    if let Some((duplicates, bytes)) = dropped {
+        report.words -= duplicates;
+        report.bytes -= bytes;
+        report.duplicates = Some(duplicates);
+    }
+    if let Some((bytes_in, bytes_out)) = encoded {
+        report.bytes = report.bytes - bytes_in + bytes_out;
+    }
+    // Past --max-size the generator counted words that were held back.
+    if let Some((words, bytes, _)) = &stopped {
+        report.words = *words;
+        report.bytes = *bytes;
+    }
+    report.resume_index = settings.resume_from.map(|start| start + report.words);
+    let stats = write_stats(settings, &report);
+    if let Some(reporter) = reporter {
+        reporter.finish(report);
+    }
+    let single_file = settings.tee.is_empty()
+        && settings.pipe.is_none()
+        && !splitting
+        && !settings.split_by_length
+        && settings.compression.is_none();
+    let resume_hint = || {
+        if let (true, Some(output), true) = (settings.resume_from.is_some(), output, single_file) {
+            if output != "-" {
+                eprintln!("Resume with: {}", resume_command(output));
+            }
+        }
+    };
+    if let (Some((words, _, last_word)), Ok(())) = (&stopped, &result) {
+        eprintln!(
+            "Reached --max-size after {} words; the last was '{}'",
+            words,
+            escape(last_word)
+        );
+        resume_hint();
+    }
+    if let (true, Ok(())) = (interrupted, &result) {
+        if interrupt::timed_out() {
+            eprintln!(
+                "Reached --duration after {} words ({})",
+                progress.words(),
+                format_duration(progress.elapsed())
+            );
+            resume_hint();
+        } else {
+            eprintln!("Interrupted after {} words", progress.words());
+            resume_hint();
+            stats?;
+            std::process::exit(130);
+        }
+    }
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => stats,
+        Ok(()) => stats,
+        result => result,
     }
-    
-    total
 }
 
-fn calculate_size(config: &Config) -> u64 {
-    if let Some(template) = &config.template {
-        if config.no_duplicates {
-            calculate_template_size_no_duplicates(template, &config.charset)
-        } else {
-            let char_positions = template.chars().filter(|&c| c == '@').count();
-            let num_positions = template.chars().filter(|&c| c == '%').count();
-            
-            let char_combinations = config.charset.len().pow(char_positions as u32);
-            let num_combinations = 10u64.pow(num_positions as u32);
-            
-            char_combinations as u64 * num_combinations
+/// This invocation with its output option replaced by `--continue output`.
+/// `--prealloc`, which cannot append, is left out.
+fn resume_command(output: &str) -> String {
+    let mut args = std::env::args();
+    let mut command = vec![args.next().unwrap_or_else(|| "crunch-rs".to_string())];
+    let mut replaced = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--prealloc" => continue,
+            "--prealloc-threads" => {
+                args.next();
+                continue;
+            }
+            arg if arg.starts_with("--prealloc-threads=") => continue,
+            _ => {}
         }
-    } else {
-        if config.no_duplicates {
-            let mut total = 0u64;
-            for len in config.min_len..=config.max_len {
-                total += calculate_combinations_no_duplicates(len as u32, config.charset.len() as u32);
+        let output_option = match arg.as_str() {
+            "-o" | "--output" | "--continue" => {
+                args.next();
+                true
             }
-            total
-        } else {
-            let charset_len = config.charset.len() as u64;
-            let mut total = 0u64;
-            for len in config.min_len..=config.max_len {
-                total += charset_len.pow(len as u32);
+            arg => {
+                arg.starts_with("--output=")
+                    || arg.starts_with("--continue=")
+                    || (arg.starts_with("-o") && arg.len() > 2)
             }
-            total
+        };
+        if !output_option {
+            command.push(arg);
+        } else if !replaced {
+            command.extend(["--continue".to_string(), output.to_string()]);
+            replaced = true;
         }
     }
+    command.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")
 }
 
-fn format_size(size: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
 
-    let size_with_newlines = size * 8; // Approximate average line length
-    if size_with_newlines >= GB {
-        format!("{:.2} GB", size_with_newlines as f64 / GB as f64)
-    } else if size_with_newlines >= MB {
-        format!("{:.2} MB", size_with_newlines as f64 / MB as f64)
-    } else if size_with_newlines >= KB {
-        format!("{:.2} KB", size_with_newlines as f64 / KB as f64)
+fn open_output(path: &str, append: bool) -> io::Result<File> {
+    let file = if append {
+        OpenOptions::new().append(true).create(true).open(path)
     } else {
-        format!("{} B", size_with_newlines)
+        File::create(path)
+    };
+    Ok(file.map_err(|err| Error::output(path, err))?)
+}
+
+fn patterns_command() -> Command {
+    Command::new("patterns")
+        .about("Generate common structured targets: dates, phone numbers and PINs")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("dates")
+                .about("Every date in a range, in one or more formats")
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .required(true)
+                        .help("First date (YYYY-MM-DD)"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .required(true)
+                        .help("Last date (YYYY-MM-DD)"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .action(clap::ArgAction::Append)
+                        .default_value("DDMMYYYY")
+                        .help("Output format using YYYY, YY, MM, DD, M, D tokens (repeatable)"),
+                )
+                .args(output_args().into_iter().filter(|arg| arg.get_id() != "record-format")),
+        )
+        .subcommand(
+            Command::new("phone")
+                .about("Phone numbers built from country and area prefixes")
+                .arg(
+                    Arg::new("country")
+                        .long("country")
+                        .action(clap::ArgAction::Append)
+                        .help("Country prefix, e.g. +1 (repeatable)"),
+                )
+                .arg(
+                    Arg::new("area")
+                        .long("area")
+                        .action(clap::ArgAction::Append)
+                        .help("Area code (repeatable)"),
+                )
+                .arg(
+                    Arg::new("digits")
+                        .long("digits")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of subscriber digits after the prefix"),
+                )
+                .args(output_args()),
+        )
+        .subcommand(
+            Command::new("pins")
+                .about("Zero-padded numeric PINs")
+                .arg(
+                    Arg::new("length")
+                        .long("length")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of digits"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("First PIN (default: all zeros)"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Last PIN (default: all nines)"),
+                )
+                .args(output_args()),
+        )
+}
+
+fn walks_command() -> Command {
+    Command::new("walks")
+        .about("Generate keyboard-adjacency walks such as qwerty or 1qaz2wsx")
+        .arg(
+            Arg::new("layout")
+                .long("layout")
+                .value_parser(["qwerty", "azerty", "qwertz"])
+                .default_value("qwerty")
+                .help("Keyboard layout to walk"),
+        )
+        .arg(
+            Arg::new("min-len")
+                .long("min-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("3")
+                .help("Minimum number of keys in a walk"),
+        )
+        .arg(
+            Arg::new("max-len")
+                .long("max-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("6")
+                .help("Maximum number of keys in a walk"),
+        )
+        .arg(
+            Arg::new("shift")
+                .long("shift")
+                .value_parser(["none", "all", "mixed"])
+                .default_value("none")
+                .help("Shift variants: none, whole walk shifted, or every key independently"),
+        )
+        .args(output_args())
+}
+
+fn run_walks(matches: &ArgMatches) -> io::Result<()> {
+    let layout = KeyboardLayout::parse(matches.get_one::<String>("layout").unwrap()).unwrap();
+    let shift = match matches.get_one::<String>("shift").unwrap().as_str() {
+        "all" => ShiftMode::All,
+        "mixed" => ShiftMode::Mixed,
+        _ => ShiftMode::None,
+    };
+    let walks = Walks {
+        keyboard: Keyboard::new(layout),
+        min_len: *matches.get_one::<usize>("min-len").unwrap(),
+        max_len: *matches.get_one::<usize>("max-len").unwrap(),
+        shift,
+    };
+    if shift == ShiftMode::Mixed && walks.max_len > 32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--shift mixed supports walks of at most 32 keys",
+        ));
     }
+    let settings = OutputSettings::from_matches(matches);
+    let total = Estimate::approximate(walks.size(), settings.line_ending);
+    run_generation(&settings, Some(total), |writer, progress| {
+        walks.generate(writer, progress, settings.line_ending)
+    })
 }
 
-fn generate_from_template<W: Write>(
-    config: &Config, 
-    template: &str, 
-    writer: &mut W,
-    progress: &Progress,
-) -> io::Result<()> {
-    let positions: Vec<(usize, char)> = template
-        .chars()
-        .enumerate()
-        .filter(|&(_, c)| c == '@' || c == '%')
-        .collect();
+fn input_buffer_arg() -> Arg {
+    Arg::new("input-buffer-size")
+        .long("input-buffer-size")
+        .value_parser(clap::value_parser!(usize))
+        .help("Read buffer size in bytes for wordlist input (default 64 KiB)")
+}
+
+fn mutate_command() -> Command {
+    Command::new("mutate")
+        .about("Transform base words with rules, case and leet variants")
+        .arg(
+            Arg::new("input")
+                .required(true)
+                .help("Base wordlist ('-' for stdin)"),
+        )
+        .arg(
+            Arg::new("rules")
+                .short('r')
+                .long("rules")
+                .action(clap::ArgAction::Append)
+                .help("Hashcat-style rule file (repeatable)"),
+        )
+        .arg(
+            Arg::new("case")
+                .long("case")
+                .value_parser(["simple", "all"])
+                .help("Emit case variants: lower/upper/capitalized, or every combination"),
+        )
+        .arg(
+            Arg::new("leet")
+                .long("leet")
+                .action(clap::ArgAction::SetTrue)
+                .help("Emit every leetspeak substitution of each word"),
+        )
+        .args(suffix_args())
+        .arg(
+            Arg::new("min-len")
+                .long("min-len")
+                .value_parser(clap::value_parser!(usize))
+                .help("Leave out words shorter than this"),
+        )
+        .arg(
+            Arg::new("max-len")
+                .long("max-len")
+                .value_parser(clap::value_parser!(usize))
+                .help("Leave out words longer than this, or cut them down with --length-policy truncate"),
+        )
+        .arg(
+            Arg::new("length-policy")
+                .long("length-policy")
+                .value_parser(["skip", "truncate"])
+                .default_value("skip")
+                .help("What happens to words longer than --max-len"),
+        )
+        .arg(input_buffer_arg())
+        .args(output_args())
+}
 
-    let mut current = vec![0; positions.len()];
-    let mut word = template.to_string();
+fn run_mutate(matches: &ArgMatches) -> io::Result<()> {
+    let mut rules = Vec::new();
+    for path in matches.get_many::<String>("rules").unwrap_or_default() {
+        let contents = std::fs::read_to_string(path).map_err(|err| Error::input(path.as_str(), err))?;
+        rules.extend(Rule::parse_file(&contents)?);
+    }
+    let mutator = Mutator {
+        rules,
+        case: matches.get_one::<String>("case").map(|mode| match mode.as_str() {
+            "all" => CaseMode::All,
+            _ => CaseMode::Simple,
+        }),
+        leet: matches.get_flag("leet"),
+        suffixes: appended_suffixes(matches, &Profile::default())?,
+        length: match (matches.get_one::<usize>("min-len"), matches.get_one::<usize>("max-len")) {
+            (None, None) => None,
+            (min, max) => Some(LengthBounds {
+                min: min.copied().unwrap_or(0),
+                max: max.copied().unwrap_or(usize::MAX),
+                policy: LengthPolicy::parse(matches.get_one::<String>("length-policy").unwrap()).unwrap(),
+            }),
+        },
+    };
+    let mut input = open_input(
+        matches.get_one::<String>("input").unwrap(),
+        matches
+            .get_one::<usize>("input-buffer-size")
+            .copied()
+            .unwrap_or(DEFAULT_BUFFER_SIZE),
+    )?;
+    let settings = OutputSettings::from_matches(matches);
+    run_generation(&settings, None, |writer, progress| {
+        for_each_line(&mut input, |word| {
+            for mutated in mutator.mutate(word) {
+                write_word(writer, mutated.as_bytes(), progress, settings.line_ending)?;
+            }
+            Ok(())
+        })
+    })
+}
 
-    loop {
-        // Create the word based on current indices
-        for (pos_idx, (template_idx, template_char)) in positions.iter().enumerate() {
-            let charset = if *template_char == '@' {
-                &config.charset
-            } else {
-                DIGITS
+fn profile_command() -> Command {
+    Command::new("profile")
+        .about("Targeted wordlist from what is known about a person (CUPP style)")
+        .arg(
+            Arg::new("answers")
+                .long("answers")
+                .help("TOML file of answers (first-name, birthday, pet, keywords, ...); asks interactively if omitted"),
+        )
+        .arg(
+            Arg::new("years")
+                .long("years")
+                .value_parser(parse_year_range)
+                .help("Years appended to words, as FROM-TO (default: the last 20 years)"),
+        )
+        .arg(
+            Arg::new("numbers")
+                .long("numbers")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("99")
+                .help("Append the numbers from 0 up to this"),
+        )
+        .arg(
+            Arg::new("specials")
+                .long("specials")
+                .default_value("!@#$%&*")
+                .help("Characters appended to words, dates and years"),
+        )
+        .arg(
+            Arg::new("leet")
+                .long("leet")
+                .action(clap::ArgAction::SetTrue)
+                .help("Also try every leetspeak spelling of the names and keywords"),
+        )
+        .arg(
+            Arg::new("min-len")
+                .long("min-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("6")
+                .help("Minimum candidate length"),
+        )
+        .arg(
+            Arg::new("max-len")
+                .long("max-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("16")
+                .help("Maximum candidate length"),
+        )
+        .args(output_args())
+}
+
+/// Parses `FROM-TO` years for `profile --years`.
+fn parse_year_range(value: &str) -> Result<RangeInclusive<u32>, String> {
+    let invalid = || format!("invalid year range '{}', expected FROM-TO such as 1990-2024", value);
+    let (from, to) = value.split_once('-').ok_or_else(invalid)?;
+    let (from, to): (u32, u32) = (from.parse().map_err(|_| invalid())?, to.parse().map_err(|_| invalid())?);
+    if from > to {
+        return Err(invalid());
+    }
+    Ok(from..=to)
+}
+
+fn run_profile(matches: &ArgMatches) -> io::Result<()> {
+    let target = match matches.get_one::<String>("answers") {
+        Some(path) => Target::load(Path::new(path))?,
+        None => {
+            eprintln!("Answer what you know about the target; leave the rest blank.");
+            Target::ask(io::stdin().lock(), &mut io::stderr())?
+        }
+    };
+    let years = matches.get_one::<RangeInclusive<u32>>("years").cloned().unwrap_or_else(|| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        // Seconds in an average Gregorian year.
+        let year = 1970 + (now.as_secs() / 31_556_952) as u32;
+        year - 19..=year
+    });
+    let rules = TargetRules {
+        years,
+        numbers: *matches.get_one::<u32>("numbers").unwrap(),
+        specials: matches.get_one::<String>("specials").unwrap().clone(),
+        leet: matches.get_flag("leet"),
+        min_len: *matches.get_one::<usize>("min-len").unwrap(),
+        max_len: *matches.get_one::<usize>("max-len").unwrap(),
+    };
+    let words = wordlist(&target, &rules)?;
+    let settings = OutputSettings::from_matches(matches);
+    let total = Estimate {
+        words: words.len() as u64,
+        bytes: words
+            .iter()
+            .map(|word| (word.len() + settings.line_ending.as_bytes().len()) as u64)
+            .sum(),
+    };
+    run_generation(&settings, Some(total), |writer, progress| {
+        for word in &words {
+            write_word(writer, word.as_bytes(), progress, settings.line_ending)?;
+        }
+        Ok(())
+    })
+}
+
+fn prince_command() -> Command {
+    Command::new("prince")
+        .about("PRINCE-style chains: concatenations of words from a wordlist")
+        .arg(
+            Arg::new("wordlist")
+                .required(true)
+                .help("Wordlist supplying the chain elements ('-' for stdin)"),
+        )
+        .arg(input_buffer_arg())
+        .arg(
+            Arg::new("min-len")
+                .long("min-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .help("Minimum candidate length"),
+        )
+        .arg(
+            Arg::new("max-len")
+                .long("max-len")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("16")
+                .help("Maximum candidate length"),
+        )
+        .arg(
+            Arg::new("max-elements")
+                .long("max-elements")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("8")
+                .help("Maximum number of words joined into one candidate"),
+        )
+        .args(output_args())
+}
+
+fn run_prince(matches: &ArgMatches) -> io::Result<()> {
+    let words = read_lines(
+        matches.get_one::<String>("wordlist").unwrap(),
+        matches
+            .get_one::<usize>("input-buffer-size")
+            .copied()
+            .unwrap_or(DEFAULT_BUFFER_SIZE),
+    )?;
+    let prince = Prince::new(
+        words,
+        *matches.get_one::<usize>("min-len").unwrap(),
+        *matches.get_one::<usize>("max-len").unwrap(),
+        *matches.get_one::<usize>("max-elements").unwrap(),
+    );
+    let settings = OutputSettings::from_matches(matches);
+    let total = Estimate::approximate(prince.size(), settings.line_ending);
+    run_generation(&settings, Some(total), |writer, progress| {
+        prince.generate(writer, progress, settings.line_ending)
+    })
+}
+
+fn train_command() -> Command {
+    Command::new("train")
+        .about("Learn an n-gram model from a wordlist for the omen subcommand")
+        .arg(
+            Arg::new("wordlist")
+                .required(true)
+                .help("Training wordlist ('-' for stdin)"),
+        )
+        .arg(Arg::new("model").required(true).help("File to write the model to"))
+        .arg(
+            Arg::new("ngram")
+                .long("ngram")
+                .value_parser(clap::value_parser!(u64).range(2..=4))
+                .default_value("3")
+                .help("Predict each character from the ngram - 1 characters before it"),
+        )
+        .arg(input_buffer_arg())
+}
+
+fn run_train(matches: &ArgMatches) -> io::Result<()> {
+    let mut input = open_input(
+        matches.get_one::<String>("wordlist").unwrap(),
+        matches
+            .get_one::<usize>("input-buffer-size")
+            .copied()
+            .unwrap_or(DEFAULT_BUFFER_SIZE),
+    )?;
+    let mut trainer = Trainer::new(*matches.get_one::<u64>("ngram").unwrap() as usize);
+    for_each_line(&mut input, |word| {
+        trainer.add(word);
+        Ok(())
+    })?;
+    let words = trainer.words();
+    let model = trainer.finish();
+    let path = matches.get_one::<String>("model").unwrap();
+    let mut file = OutputBuffer::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, open_output(path, false)?);
+    model.save(&mut file)?;
+    file.flush()?;
+    eprintln!(
+        "Trained on {} words: {} characters, {} starts, {} n-grams; wrote {}",
+        words,
+        model.alphabet.len(),
+        model.initial.len(),
+        model.conditional.len(),
+        path
+    );
+    Ok(())
+}
+
+fn omen_command() -> Command {
+    Command::new("omen")
+        .about("Candidates in decreasing probability under a model from the train subcommand")
+        .arg(Arg::new("model").required(true).help("Model written by the train subcommand"))
+        .arg(
+            Arg::new("min-len")
+                .long("min-len")
+                .value_parser(clap::value_parser!(usize))
+                .help("Minimum candidate length (default: the shortest trained length)"),
+        )
+        .arg(
+            Arg::new("max-len")
+                .long("max-len")
+                .value_parser(clap::value_parser!(usize))
+                .help("Maximum candidate length (default: the longest trained length)"),
+        )
+        .arg(
+            Arg::new("max-level")
+                .long("max-level")
+                .value_parser(clap::value_parser!(u32))
+                .help("Stop after the candidates of this total level (0 is the likeliest)"),
+        )
+        .args(output_args())
+}
+
+fn run_omen(matches: &ArgMatches) -> io::Result<()> {
+    let path = matches.get_one::<String>("model").unwrap();
+    let file = File::open(path).map_err(|err| Error::input(path.as_str(), err))?;
+    let model = Model::load(io::BufReader::new(file))?;
+    let min_len = matches
+        .get_one::<usize>("min-len")
+        .copied()
+        .or_else(|| model.lengths.keys().next().copied())
+        .unwrap_or(0);
+    let max_len = matches
+        .get_one::<usize>("max-len")
+        .copied()
+        .or_else(|| model.lengths.keys().next_back().copied())
+        .unwrap_or(0);
+    let max_level = matches.get_one::<u32>("max-level").copied();
+    let omen = Omen::new(&model);
+    let settings = OutputSettings::from_matches(matches);
+    run_generation(&settings, None, |writer, progress| {
+        omen.generate(min_len, max_len, max_level, writer, progress, settings.line_ending)
+    })
+}
+
+fn bench_command() -> Command {
+    Command::new("bench")
+        .about("Measure generation throughput into a null sink")
+        .arg(
+            Arg::new("charset")
+                .long("charset")
+                .default_value("abcdefghijklmnopqrstuvwxyz")
+                .help("Characters to generate from, or a named charset such as lalpha-numeric"),
+        )
+        .arg(
+            Arg::new("length")
+                .long("length")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("8")
+                .help("Word length"),
+        )
+        .arg(
+            Arg::new("compress")
+                .short('z')
+                .long("compress")
+                .value_parser(["gzip", "zstd"])
+                .help("Measure -z output with each of --compress-threads instead of each buffer size"),
+        )
+        .arg(
+            Arg::new("compress-threads")
+                .long("compress-threads")
+                .value_parser(clap::value_parser!(usize))
+                .value_delimiter(',')
+                .requires("compress")
+                .help("Comma-separated --compress-threads to try with -z (default: 1 and every available core)"),
+        )
+        .arg(
+            Arg::new("buffer-sizes")
+                .long("buffer-sizes")
+                .value_parser(parse_size)
+                .value_delimiter(',')
+                .default_value("8KiB,64KiB,1MiB")
+                .help("Comma-separated output buffer sizes to try"),
+        )
+        .arg(
+            Arg::new("seconds")
+                .long("seconds")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("3")
+                .help("How long to run each configuration"),
+        )
+}
+
+fn run_bench_command(matches: &ArgMatches) -> io::Result<()> {
+    let charset = Charset::resolve(matches.get_one::<String>("charset").unwrap())?;
+    let layout = vec![&charset; *matches.get_one::<usize>("length").unwrap()];
+    let duration = Duration::from_secs(*matches.get_one::<u64>("seconds").unwrap());
+    let outputs: Vec<BenchOutput> = match matches.get_one::<String>("compress") {
+        Some(name) => {
+            let compression = Compression::parse(name).unwrap();
+            let threads: Vec<usize> = match matches.get_many::<usize>("compress-threads") {
+                Some(threads) => threads.copied().collect(),
+                None => {
+                    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+                    if cores > 1 { vec![1, cores] } else { vec![1] }
+                }
             };
-            let char_idx = current[pos_idx];
-            word.replace_range(
-                *template_idx..*template_idx + 1,
-                &charset.chars().nth(char_idx).unwrap().to_string(),
-            );
+            threads
+                .into_iter()
+                .map(|threads| BenchOutput::Compressed { compression, threads })
+                .collect()
         }
+        None => matches
+            .get_many::<u64>("buffer-sizes")
+            .unwrap()
+            .map(|&buffer_size| BenchOutput::Buffered {
+                buffer_size: buffer_size as usize,
+            })
+            .collect(),
+    };
 
-        if !config.no_duplicates || !has_consecutive_duplicates(&word) {
-            writeln!(writer, "{}", word)?;
+    for (i, &output) in outputs.iter().enumerate() {
+        let result = run_bench(&layout, output, duration)?;
+        let setting = match result.output {
+            BenchOutput::Buffered { buffer_size } => ("buffer", buffer_size),
+            BenchOutput::Compressed { threads, .. } => ("compress-threads", threads),
+        };
+        if i == 0 {
+            println!("{:>16}  {:>14}  {:>10}", setting.0, "words/s", "MB/s");
         }
-        progress.increment();
+        println!("{:>16}  {:>14.0}  {:>10.1}", setting.1, result.words_per_sec(), result.mb_per_sec());
+    }
+    Ok(())
+}
 
-        // Increment indices
-        let mut idx = positions.len() - 1;
-        loop {
-            let charset_len = if positions[idx].1 == '@' {
-                config.charset.len()
-            } else {
-                DIGITS.len()
+fn analyze_command() -> Command {
+    Command::new("analyze")
+        .about("Report lengths, character classes and the hashcat masks that cover a wordlist")
+        .arg(
+            Arg::new("input")
+                .required(true)
+                .help("Wordlist to analyze ('-' for stdin)"),
+        )
+        .arg(
+            Arg::new("coverage")
+                .long("coverage")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("90")
+                .help("List the most common masks until they cover this percentage of the words"),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_parser(clap::value_parser!(usize))
+                .help("List at most this many masks"),
+        )
+        .arg(
+            Arg::new("hcmask")
+                .long("hcmask")
+                .help("Also write the listed masks to this .hcmask file"),
+        )
+        .arg(input_buffer_arg())
+}
+
+fn run_analyze(matches: &ArgMatches) -> io::Result<()> {
+    let mut input = open_input(
+        matches.get_one::<String>("input").unwrap(),
+        matches
+            .get_one::<usize>("input-buffer-size")
+            .copied()
+            .unwrap_or(DEFAULT_BUFFER_SIZE),
+    )?;
+    let mut analysis = Analysis::default();
+    for_each_line(&mut input, |word| {
+        analysis.add(word);
+        Ok(())
+    })?;
+    let masks = analysis.top_masks(
+        *matches.get_one::<f64>("coverage").unwrap(),
+        matches.get_one::<usize>("top").copied(),
+    );
+    if let Some(path) = matches.get_one::<String>("hcmask") {
+        let mut file = OutputBuffer::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, open_output(path, false)?);
+        for (mask, _) in &masks {
+            writeln!(file, "{}", mask)?;
+        }
+        file.flush()?;
+    }
+
+    let percent = |count: u64| count as f64 * 100.0 / analysis.words.max(1) as f64;
+    let mut out = io::stdout().lock();
+    writeln!(out, "Analyzed {} words", analysis.words)?;
+    writeln!(out, "\n{:>6}  {:>12}  {:>7}", "length", "words", "percent")?;
+    for (&length, &count) in &analysis.lengths {
+        writeln!(out, "{:>6}  {:>12}  {:>6.2}%", length, count, percent(count))?;
+    }
+    writeln!(out, "\n{:<22}  {:>12}  {:>7}", "composition", "words", "percent")?;
+    for (name, count) in analysis.top_compositions() {
+        writeln!(out, "{:<22}  {:>12}  {:>6.2}%", name, count, percent(count))?;
+    }
+    writeln!(out, "\n{:<32}  {:>12}  {:>7}  {:>7}", "mask", "words", "percent", "covered")?;
+    let mut covered = 0;
+    for (mask, count) in masks {
+        covered += count;
+        writeln!(out, "{:<32}  {:>12}  {:>6.2}%  {:>6.2}%", mask, count, percent(count), percent(covered))?;
+    }
+    Ok(())
+}
+
+fn merge_command() -> Command {
+    Command::new("merge")
+        .about("Merge wordlists into one sorted list without duplicates")
+        .arg(
+            Arg::new("inputs")
+                .required(true)
+                .num_args(1..)
+                .help("Wordlists to merge ('-' for stdin)"),
+        )
+        .arg(
+            Arg::new("by-length")
+                .long("by-length")
+                .action(clap::ArgAction::SetTrue)
+                .help("Sort shorter words first instead of purely bytewise"),
+        )
+        .arg(
+            Arg::new("memory")
+                .long("memory")
+                .value_parser(parse_size)
+                .help("Memory for buffered words before a sorted run is spilled to disk, e.g. 512MiB (default 256 MiB)"),
+        )
+        .arg(
+            Arg::new("temp-dir")
+                .long("temp-dir")
+                .help("Directory for spilled runs (default: the system temp directory)"),
+        )
+        .arg(input_buffer_arg())
+        .args(output_args())
+}
+
+fn run_merge(matches: &ArgMatches) -> io::Result<()> {
+    let buffer_size = matches
+        .get_one::<usize>("input-buffer-size")
+        .copied()
+        .unwrap_or(DEFAULT_BUFFER_SIZE);
+    let order = if matches.get_flag("by-length") {
+        MergeOrder::Length
+    } else {
+        MergeOrder::Lexicographic
+    };
+    let memory = matches
+        .get_one::<u64>("memory")
+        .map_or(DEFAULT_MEMORY, |&bytes| usize::try_from(bytes).unwrap_or(usize::MAX));
+    let temp_dir = matches
+        .get_one::<String>("temp-dir")
+        .map_or_else(std::env::temp_dir, PathBuf::from);
+
+    let mut merger = Merger::new(order, memory, temp_dir);
+    for path in matches.get_many::<String>("inputs").unwrap() {
+        for_each_line(&mut open_input(path, buffer_size)?, |word| merger.push(word))?;
+    }
+    let settings = OutputSettings::from_matches(matches);
+    run_generation(&settings, None, |writer, progress| {
+        merger.finish(|word| write_word(writer, word.as_bytes(), progress, settings.line_ending))
+    })
+}
+
+fn run_patterns(matches: &ArgMatches) -> io::Result<()> {
+    match matches.subcommand() {
+        Some(("dates", matches)) => {
+            let range = DateRange {
+                from: Date::parse(matches.get_one::<String>("from").unwrap())?,
+                to: Date::parse(matches.get_one::<String>("to").unwrap())?,
+                formats: matches.get_many::<String>("format").unwrap().cloned().collect(),
+            };
+            let settings = OutputSettings::from_matches(matches);
+            let total = Estimate::approximate(range.size(), settings.line_ending);
+            run_generation(&settings, Some(total), |writer, progress| {
+                range.generate(writer, progress, settings.line_ending)
+            })
+        }
+        Some(("phone", matches)) => {
+            let strings = |id| -> Vec<String> {
+                matches.get_many::<String>(id).unwrap_or_default().cloned().collect()
             };
+            let numbers = PhoneNumbers::new(
+                &strings("country"),
+                &strings("area"),
+                *matches.get_one::<usize>("digits").unwrap(),
+            );
+            let settings = OutputSettings::from_matches(matches);
+            let total = Estimate::approximate(numbers.size(), settings.line_ending);
+            run_generation(&settings, Some(total), |writer, progress| {
+                numbers.generate(writer, progress, settings.line_ending)
+            })
+        }
+        Some(("pins", matches)) => {
+            let width = *matches.get_one::<usize>("length").unwrap();
+            let pins = PinRange {
+                from: matches.get_one::<u64>("from").copied().unwrap_or(0),
+                to: matches
+                    .get_one::<u64>("to")
+                    .copied()
+                    .unwrap_or_else(|| 10u64.saturating_pow(width as u32) - 1),
+                width,
+            };
+            let settings = OutputSettings::from_matches(matches);
+            let total = Estimate::approximate(pins.size(), settings.line_ending);
+            run_generation(&settings, Some(total), |writer, progress| {
+                pins.generate(writer, progress, settings.line_ending)
+            })
+        }
+        _ => unreachable!("clap requires a patterns subcommand"),
+    }
+}
 
-            current[idx] += 1;
-            if current[idx] < charset_len {
-                break;
-            }
+/// The keyspace definition shared by the root command and `serve`.
+fn keyspace_args() -> Vec<Arg> {
+    let mut args = vec![
+        Arg::new("min_len")
+            .required_unless_present_any(["config", "profile"])
+            .value_parser(clap::value_parser!(usize))
+            .help("Minimum length of generated words"),
+        Arg::new("max_len")
+            .required_unless_present_any(["config", "profile"])
+            .value_parser(clap::value_parser!(usize))
+            .help("Maximum length of generated words"),
+        Arg::new("charset")
+            .required_unless_present_any(["config", "profile", "charset-file"])
+            .help("Characters to use in generation, or a named charset such as mixalpha-numeric (see --list-charsets); \\t, \\0, \\xHH and \\\\ are escapes"),
+        Arg::new("charset-file")
+            .long("charset-file")
+            .value_name("FILE")
+            .conflicts_with("charset")
+            .help("Use every byte of FILE, as is, as the charset, for non-text keyspaces"),
+        Arg::new("template")
+            .short('t')
+            .long("template")
+            .help("Template for generation (@ for charset, % for digits, ?N for a --set placeholder, * for a --wordlist word, [...] for an optional part)"),
+        Arg::new("set")
+            .long("set")
+            .action(clap::ArgAction::Append)
+            .value_parser(parse_placeholder)
+            .help("Define a template placeholder, e.g. --set '1=abc' makes ?1 draw from abc"),
+        Arg::new("wordlist")
+            .long("wordlist")
+            .value_name("FILE")
+            .help("Words a template's * stands for, one per line, e.g. -t '*%%%%' --wordlist names.txt ('-' for stdin)"),
+        Arg::new("no-duplicates")
+            .long("no-duplicates")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("allow-duplicates")
+            .help("Avoid consecutive duplicate characters (except digits)"),
+        Arg::new("allow-duplicates")
+            .long("allow-duplicates")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("no-duplicates")
+            .help("Allow consecutive duplicate characters, overriding a profile's no-duplicates"),
+        Arg::new("exclude-chars")
+            .long("exclude-chars")
+            .help("Characters that must never appear, e.g. 'l1O0' (escapes as in <charset>)"),
+        Arg::new("exclude-substr")
+            .long("exclude-substr")
+            .action(clap::ArgAction::Append)
+            .help("Substring that must never appear (repeatable)"),
+        Arg::new("startswith")
+            .long("startswith")
+            .help("Only generate words starting with this, e.g. 'adm'"),
+        Arg::new("endswith")
+            .long("endswith")
+            .help("Only generate words ending with this, e.g. '2024'"),
+        Arg::new("contains")
+            .long("contains")
+            .action(clap::ArgAction::Append)
+            .help("Substring that must appear somewhere in every word (repeatable)"),
+        Arg::new("require")
+            .long("require")
+            .action(clap::ArgAction::Append)
+            .value_parser(parse_requirements)
+            .help("Character classes every word must have, e.g. 'digit:2,symbol:1+,upper:1-3' (lower, upper, digit, symbol)"),
+        Arg::new("max-char-count")
+            .long("max-char-count")
+            .value_parser(clap::value_parser!(usize))
+            .help("Maximum number of times any single character may appear in a word"),
+        Arg::new("min-entropy")
+            .long("min-entropy")
+            .value_parser(clap::value_parser!(f64))
+            .help("Only keep words with at least this many bits of Shannon entropy, e.g. to skip 'aaaaaa'"),
+        Arg::new("max-entropy")
+            .long("max-entropy")
+            .value_parser(clap::value_parser!(f64))
+            .help("Only keep words with at most this many bits of Shannon entropy"),
+        Arg::new("not-in")
+            .long("not-in")
+            .action(clap::ArgAction::Append)
+            .help("Skip words already in this wordlist (repeatable), e.g. to generate only what rockyou.txt lacks"),
+        Arg::new("not-in-fp-rate")
+            .long("not-in-fp-rate")
+            .value_parser(parse_false_positive_rate)
+            .help("Share of new words --not-in may wrongly skip, trading memory for accuracy (default 0.001)"),
+        Arg::new("order")
+            .long("order")
+            .value_parser(["charset", "freq", "interleave"])
+            .help("Try each position's characters in charset order or most frequent first (freq), or alternate between lengths (interleave)"),
+        Arg::new("interleave-batch")
+            .long("interleave-batch")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help("Candidates of one length written before moving to the next with --order interleave (default 10000)"),
+        Arg::new("train")
+            .long("train")
+            .help("Wordlist to learn per-position character frequencies from for --order freq (default: built-in ranking)"),
+        Arg::new("shuffle")
+            .long("shuffle")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("no-shuffle")
+            .help("Emit every word exactly once, in a pseudo-random order fixed by --seed"),
+        Arg::new("no-shuffle")
+            .long("no-shuffle")
+            .action(clap::ArgAction::SetTrue)
+            .overrides_with("shuffle")
+            .help("Emit the words in order, overriding a profile's shuffle"),
+        Arg::new("seed")
+            .long("seed")
+            .value_parser(clap::value_parser!(u64))
+            .help("Seed choosing the --shuffle order (default: 0)"),
+        Arg::new("pad-to")
+            .long("pad-to")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Pad words shorter than N characters up to N, e.g. 7 to 0007 for fixed-width IDs"),
+        Arg::new("pad-char")
+            .long("pad-char")
+            .help("Character --pad-to fills with (default 0; escapes as in <charset>)"),
+        Arg::new("pad-side")
+            .long("pad-side")
+            .value_parser(["left", "right"])
+            .help("Which end --pad-to fills (default left)"),
+        Arg::new("length-policy")
+            .long("length-policy")
+            .value_parser(["skip", "truncate"])
+            .help("Hold template and suffixed words to <min_len>..<max_len> too, dropping or cutting down the rest"),
+        Arg::new("config")
+            .long("config")
+            .help("Profiles file (default: ~/.config/crunch-rs/profiles.toml)"),
+        Arg::new("profile")
+            .long("profile")
+            .help("Named profile to load from the profiles file (default: 'default')"),
+    ];
+    args.extend(suffix_args());
+    args
+}
+
+/// Options that end every word with a year or number, for `generate` and `mutate`.
+fn suffix_args() -> Vec<Arg> {
+    vec![
+        Arg::new("append-years")
+            .long("append-years")
+            .value_name("YEARS")
+            .value_parser(parse_numbers)
+            .help("Write each word once with every year in this range or list, e.g. 1990-2025"),
+        Arg::new("append-numbers")
+            .long("append-numbers")
+            .value_name("NUMBERS")
+            .value_parser(parse_numbers)
+            .help("Write each word once with every number in this range or list, e.g. 0-999 or 00-99 for two digits"),
+        Arg::new("append-separators")
+            .long("append-separators")
+            .value_name("CHARS")
+            .help("Also put each of these characters before the year or number, e.g. '_!' for word_2024 and word!2024"),
+    ]
+}
 
-            current[idx] = 0;
-            if idx == 0 {
-                return Ok(());
+/// The suffixes asked for by [`suffix_args`], each number as it is and
+/// after every separator.
+fn appended_suffixes(matches: &ArgMatches, profile: &Profile) -> io::Result<Vec<String>> {
+    let mut values = Vec::new();
+    for (id, spec) in [
+        ("append-years", &profile.append_years),
+        ("append-numbers", &profile.append_numbers),
+    ] {
+        match (matches.get_one::<Vec<String>>(id), spec) {
+            (Some(numbers), _) => values.extend(numbers.iter().cloned()),
+            (None, Some(spec)) => {
+                values.extend(parse_numbers(spec).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?)
             }
-            idx -= 1;
+            (None, None) => {}
         }
     }
+    let separators = matches
+        .get_one::<String>("append-separators")
+        .or(profile.append_separators.as_ref());
+    if values.is_empty() && separators.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--append-separators needs --append-years or --append-numbers",
+        ));
+    }
+    Ok(suffixes(&values, separators.map_or("", String::as_str)))
 }
 
-fn generate_all_combinations<W: Write>(
-    current: &mut String,
-    length: usize,
-    charset: &str,
-    writer: &mut W,
-    progress: &Progress,
-    no_duplicates: bool,
-) -> io::Result<()> {
-    if length == 0 {
-        if !no_duplicates || !has_consecutive_duplicates(current) {
-            writeln!(writer, "{}", current)?;
-        }
-        progress.increment();
-        return Ok(());
+/// The distinct non-empty lines of `path`, in order, for a template's `*`.
+fn template_words(path: &str) -> io::Result<Vec<Vec<u8>>> {
+    let mut seen = HashSet::new();
+    Ok(read_lines(path, DEFAULT_BUFFER_SIZE)?
+        .into_iter()
+        .filter(|word| !word.is_empty() && seen.insert(word.clone()))
+        .map(String::into_bytes)
+        .collect())
+}
+
+/// Loads the profile selected by `--config`/`--profile`, or an empty one.
+fn selected_profile(matches: &ArgMatches) -> io::Result<Profile> {
+    Ok(load_profile(
+        matches.get_one::<String>("config").map(Path::new),
+        matches.get_one::<String>("profile").map(String::as_str),
+    )?
+    .unwrap_or_default())
+}
+
+/// Parses `--not-in-fp-rate`, a share strictly between 0 and 1.
+fn parse_false_positive_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate < 1.0 => Ok(rate),
+        _ => Err(format!("expected a rate between 0 and 1, got '{}'", value)),
     }
+}
 
-    for c in charset.chars() {
-        if no_duplicates && !current.is_empty() {
-            let last_char = current.chars().last().unwrap();
-            // Allow duplicate digits
-            if c == last_char && !c.is_digit(10) {
-                continue;
+/// The `--pad-to width` padding, with its character and side from the
+/// command line, the profile or their defaults.
+fn padding(matches: &ArgMatches, profile: &Profile, width: usize) -> io::Result<Padding> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let pad_char = matches
+        .get_one::<String>("pad-char")
+        .or(profile.pad_char.as_ref())
+        .map_or("0", String::as_str);
+    let symbol = match unescape(pad_char)?.as_slice() {
+        [symbol] => symbol.clone(),
+        _ => return Err(invalid(format!("--pad-char '{}' must be a single character", pad_char))),
+    };
+    let side = match matches.get_one::<String>("pad-side").or(profile.pad_side.as_ref()) {
+        Some(name) => PadSide::parse(name).ok_or_else(|| invalid(format!("unknown --pad-side '{}'", name)))?,
+        None => PadSide::default(),
+    };
+    Ok(Padding { width, symbol, side })
+}
+
+/// Builds the keyspace from [`keyspace_args`], falling back to `profile`.
+fn keyspace_config(matches: &ArgMatches, profile: &Profile, line_ending: LineEnding) -> io::Result<Config> {
+    let missing = |what: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} must be given on the command line or in the profile", what),
+        )
+    };
+    let mut placeholders = profile.set.clone();
+    placeholders.extend(matches.get_many::<(char, String)>("set").unwrap_or_default().cloned());
+    // A charset on the command line overrides a charset file in the profile.
+    let charset_file = matches
+        .get_one::<String>("charset-file")
+        .or_else(|| profile.charset_file.as_ref().filter(|_| !matches.contains_id("charset")));
+    let charset_bytes = match charset_file {
+        Some(path) => Some(std::fs::read(path).map_err(|err| Error::input(path.as_str(), err))?),
+        None => None,
+    };
+    let order = matches.get_one::<String>("order").or(profile.order.as_ref()).map(String::as_str);
+    let config = Config {
+        min_len: matches
+            .get_one::<usize>("min_len")
+            .copied()
+            .or(profile.min_len)
+            .ok_or_else(|| missing("<min_len>"))?,
+        max_len: matches
+            .get_one::<usize>("max_len")
+            .copied()
+            .or(profile.max_len)
+            .ok_or_else(|| missing("<max_len>"))?,
+        charset: match charset_bytes {
+            Some(_) => String::new(),
+            None => matches
+                .get_one::<String>("charset")
+                .cloned()
+                .or_else(|| profile.charset.clone())
+                .ok_or_else(|| missing("<charset>"))?,
+        },
+        charset_bytes,
+        template: matches
+            .get_one::<String>("template")
+            .cloned()
+            .or_else(|| profile.template.clone()),
+        placeholders,
+        wordlist: match matches.get_one::<String>("wordlist").or(profile.wordlist.as_ref()) {
+            Some(path) => template_words(path)?,
+            None => Vec::new(),
+        },
+        output: None,
+        no_duplicates: switch(matches, "no-duplicates", "allow-duplicates", profile.no_duplicates),
+        exclude_chars: matches
+            .get_one::<String>("exclude-chars")
+            .cloned()
+            .or_else(|| profile.exclude_chars.clone())
+            .unwrap_or_default(),
+        exclude_substrings: match matches.get_many::<String>("exclude-substr") {
+            Some(substrings) => substrings.cloned().collect(),
+            None => profile.exclude_substr.clone().unwrap_or_default(),
+        },
+        max_char_count: matches.get_one::<usize>("max-char-count").copied().or(profile.max_char_count),
+        starts_with: matches
+            .get_one::<String>("startswith")
+            .cloned()
+            .or_else(|| profile.startswith.clone()),
+        ends_with: matches
+            .get_one::<String>("endswith")
+            .cloned()
+            .or_else(|| profile.endswith.clone()),
+        contains: match matches.get_many::<String>("contains") {
+            Some(substrings) => substrings.cloned().collect(),
+            None => profile.contains.clone().unwrap_or_default(),
+        },
+        require: match matches.get_many::<Vec<ClassRequirement>>("require") {
+            Some(specs) => specs.flatten().copied().collect(),
+            None => match &profile.require {
+                Some(spec) => parse_requirements(spec).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+                None => Vec::new(),
+            },
+        },
+        min_entropy: matches.get_one::<f64>("min-entropy").copied().or(profile.min_entropy),
+        max_entropy: matches.get_one::<f64>("max-entropy").copied().or(profile.max_entropy),
+        not_in: match matches.get_many::<String>("not-in") {
+            Some(paths) => Some(paths.cloned().collect()),
+            None => profile.not_in.clone(),
+        }
+        .map(|paths: Vec<String>| {
+            let rate = matches
+                .get_one::<f64>("not-in-fp-rate")
+                .copied()
+                .or(profile.not_in_fp_rate)
+                .unwrap_or(DEFAULT_FALSE_POSITIVE_RATE);
+            BloomFilter::from_wordlists(&paths, rate).map(Arc::new)
+        })
+        .transpose()?,
+        frequencies: match order {
+            Some("freq") => Some(Arc::new(
+                match matches.get_one::<String>("train").or(profile.train.as_ref()) {
+                    Some(path) => Frequencies::learn(path)?,
+                    None => Frequencies::builtin(),
+                },
+            )),
+            Some("charset" | "interleave") | None if matches.contains_id("train") => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "--train only applies to --order freq"))
             }
+            Some("charset" | "interleave") | None => None,
+            Some(order) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unknown order '{}'", order)))
+            }
+        },
+        interleave: match (order, matches.get_one::<u64>("interleave-batch").copied().or(profile.interleave_batch)) {
+            (Some("interleave"), batch) => Some(batch.unwrap_or(DEFAULT_INTERLEAVE_BATCH)),
+            (_, Some(_)) if matches.contains_id("interleave-batch") => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--interleave-batch only applies to --order interleave",
+                ))
+            }
+            _ => None,
+        },
+        shuffle_seed: switch(matches, "shuffle", "no-shuffle", profile.shuffle).then(|| {
+            matches
+                .get_one::<u64>("seed")
+                .copied()
+                .or(profile.seed)
+                .unwrap_or_default()
+        }),
+        suffixes: appended_suffixes(matches, profile)?
+            .into_iter()
+            .map(String::into_bytes)
+            .collect(),
+        padding: match matches.get_one::<usize>("pad-to").copied().or(profile.pad_to) {
+            Some(width) => Some(padding(matches, profile, width)?),
+            None => None,
+        },
+        length_policy: match matches.get_one::<String>("length-policy").or(profile.length_policy.as_ref()) {
+            Some(name) => Some(LengthPolicy::parse(name).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("unknown --length-policy '{}'", name))
+            })?),
+            None => None,
+        },
+        line_ending,
+    };
+    config.validate()?;
+    // Named charsets and charset files are taken as they are.
+    let listed = config.charset_bytes.is_none() && resolve_alias(&config.charset) == config.charset;
+    for (what, chars) in listed
+        .then(|| ("the charset".to_string(), &config.charset))
+        .into_iter()
+        .chain(config.placeholders.iter().map(|(key, chars)| (format!("placeholder ?{}", key), chars)))
+    {
+        let repeated = repeated_symbols(chars)?;
+        if !repeated.is_empty() {
+            eprintln!(
+                "Warning: {} repeats '{}'; each character is used once",
+                what,
+                repeated.iter().map(|symbol| escape(symbol)).collect::<String>()
+            );
         }
-        current.push(c);
-        generate_all_combinations(current, length - 1, charset, writer, progress, no_duplicates)?;
-        current.pop();
     }
-    Ok(())
+    Ok(config)
 }
 
-fn generate_words<W: Write>(
-    config: &Config, 
-    writer: &mut W,
-    progress: &Progress,
-) -> io::Result<()> {
-    if let Some(template) = &config.template {
-        generate_from_template(config, template, writer, progress)?;
-    } else {
-        let mut current = String::new();
-        for len in config.min_len..=config.max_len {
-            generate_all_combinations(
-                &mut current, 
-                len, 
-                &config.charset, 
-                writer, 
-                progress, 
-                config.no_duplicates
-            )?;
-        }
+/// Number of words an interrupted run already wrote to `path`, after cutting
+/// off a partially written last word. A missing file means starting afresh.
+fn resume_point(config: &Config, path: &Path) -> io::Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let Some(last) = truncate_partial_line(path, config.line_ending)? else {
+        return Ok(0);
+    };
+    if Filters::from_config(config).is_active() {
+        // Filtered keyspaces cannot be ranked, but every line is one of their words.
+        return count_lines(path, config.line_ending);
+    }
+    match rank(config, &last)? {
+        Some(index) => Ok(index + 1),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the last word of {} ('{}') is not in this keyspace",
+                path.display(),
+                escape(&last)
+            ),
+        )),
     }
-    Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let matches = Command::new("crunch-rs")
-        .version("1.0")
-        .author("lurg0th")
-        .about("A Rust clone of the crunch wordlist generator")
+fn serve_command() -> Command {
+    Command::new("serve")
+        .about("Serve a keyspace over HTTP: GET /words?skip=N&limit=M streams a slice, GET /info its size")
         .arg(
-            Arg::new("min_len")
-                .required(true)
-                .help("Minimum length of generated words"),
+            Arg::new("bind")
+                .long("bind")
+                .default_value("127.0.0.1:8080")
+                .help("Address to listen on, e.g. 0.0.0.0:8080"),
         )
+        .args(keyspace_args())
+        .args(line_ending_args())
+}
+
+fn run_serve(matches: &ArgMatches) -> io::Result<()> {
+    let profile = selected_profile(matches)?;
+    let line_ending = resolve_line_ending(matches, &profile)?;
+    let config = keyspace_config(matches, &profile, line_ending)?;
+    let listener = TcpListener::bind(matches.get_one::<String>("bind").unwrap())?;
+    eprintln!(
+        "Serving {} words on http://{}",
+        estimate(&config)?.words,
+        listener.local_addr()?
+    );
+    serve(listener, config)
+}
+
+fn index_command() -> Command {
+    Command::new("index")
+        .about("Print the position of a word in the keyspace, counting from zero")
+        .arg(Arg::new("word").required(true).help("Word to look up"))
+        .args(keyspace_args())
+}
+
+fn word_command() -> Command {
+    Command::new("word")
+        .about("Print the word at a position in the keyspace, counting from zero")
         .arg(
-            Arg::new("max_len")
+            Arg::new("index")
                 .required(true)
-                .help("Maximum length of generated words"),
+                .value_parser(clap::value_parser!(u64))
+                .help("Position to look up"),
         )
+        .args(keyspace_args())
+}
+
+fn run_index(matches: &ArgMatches) -> io::Result<()> {
+    let config = keyspace_config(matches, &selected_profile(matches)?, LineEnding::default())?;
+    let word = matches.get_one::<String>("word").unwrap();
+    match rank(&config, word.as_bytes())? {
+        Some(index) => {
+            println!("{}", index);
+            Ok(())
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("'{}' is not in the keyspace", word),
+        )),
+    }
+}
+
+fn run_word(matches: &ArgMatches) -> io::Result<()> {
+    let config = keyspace_config(matches, &selected_profile(matches)?, LineEnding::default())?;
+    let index = *matches.get_one::<u64>("index").unwrap();
+    match unrank(&config, index)? {
+        Some(word) => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&word)?;
+            stdout.write_all(b"\n")
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("the keyspace has only {} words", calculate_size(&config)?),
+        )),
+    }
+}
+
+fn extract_command() -> Command {
+    Command::new("extract")
+        .about("Print a range of words from a -z zstd file, decompressing only the frames that hold them")
+        .arg(Arg::new("input").required(true).help("File written with -z zstd"))
         .arg(
-            Arg::new("charset")
-                .required(true)
-                .help("Characters to use in generation"),
+            Arg::new("index")
+                .long("index")
+                .help("Seek index to use (default: INPUT.idx)"),
         )
         .arg(
-            Arg::new("template")
-                .short('t')
-                .long("template")
-                .help("Template for generation (@ for charset, % for digits)"),
+            Arg::new("skip")
+                .long("skip")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("0")
+                .help("Words to leave out before the range"),
         )
         .arg(
-            Arg::new("output")
-                .short('o')
-                .long("output")
-                .help("Output file name"),
+            Arg::new("limit")
+                .long("limit")
+                .value_parser(clap::value_parser!(u64))
+                .help("Words to print (default: every word after --skip)"),
         )
+}
+
+fn run_extract(matches: &ArgMatches) -> io::Result<()> {
+    let input = matches.get_one::<String>("input").unwrap();
+    let index = match matches.get_one::<String>("index") {
+        Some(index) => PathBuf::from(index),
+        None => index_path(Path::new(input)),
+    };
+    let index = SeekIndex::load(&index)?;
+    let mut file = File::open(input).map_err(|err| Error::input(input.as_str(), err))?;
+    let skip = *matches.get_one::<u64>("skip").unwrap();
+    let limit = matches.get_one::<u64>("limit").copied();
+    let mut stdout = OutputBuffer::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, io::stdout().lock());
+    match extract(&mut file, &index, skip, limit, &mut stdout).and_then(|_| stdout.flush()) {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        extracted => extracted,
+    }
+}
+
+fn generate_command() -> Command {
+    Command::new("generate")
+        .about("Write every word of a keyspace (the default without a command, as in crunch)")
+        .args(keyspace_args())
+        .mut_arg("min_len", |arg| arg.required_unless_present_any(["config", "profile", "list-charsets"]))
+        .mut_arg("max_len", |arg| arg.required_unless_present_any(["config", "profile", "list-charsets"]))
+        .mut_arg("charset", |arg| {
+            arg.required_unless_present_any(["config", "profile", "charset-file", "list-charsets"])
+        })
         .arg(
-            Arg::new("no-duplicates")
-                .long("no-duplicates")
+            Arg::new("list-charsets")
+                .long("list-charsets")
                 .action(clap::ArgAction::SetTrue)
-                .help("Avoid consecutive duplicate characters (except digits)"),
+                .exclusive(true)
+                .help("List the named charsets accepted in place of <charset> and exit"),
         )
-        .get_matches();
+        .arg(
+            Arg::new("continue")
+                .long("continue")
+                .conflicts_with_all(["output", "pipe", "archive", "split-bytes", "split-lines"])
+                .help("Resume an interrupted run: append to this file after its last complete word"),
+        )
+        .arg(
+            Arg::new("prealloc")
+                .long("prealloc")
+                .action(clap::ArgAction::SetTrue)
+                .requires("output")
+                .conflicts_with_all(["continue", "pipe", "archive", "split-bytes", "split-lines", "split-by-length"])
+                .help("Size the -o file exactly up front and fill it from several threads at once (unfiltered keyspaces only)"),
+        )
+        .arg(
+            Arg::new("prealloc-threads")
+                .long("prealloc-threads")
+                .value_parser(clap::value_parser!(usize))
+                .requires("prealloc")
+                .help("Threads writing with --prealloc (default: every available core)"),
+        )
+        .args(output_args())
+}
+
+fn run_generate(matches: &ArgMatches) -> io::Result<()> {
+    if matches.get_flag("list-charsets") {
+        let width = aliases().iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let mut stdout = io::stdout().lock();
+        let listed = aliases()
+            .into_iter()
+            .try_for_each(|(name, chars)| writeln!(stdout, "{:width$} = [{}]", name, chars, width = width));
+        return match listed {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            listed => listed,
+        };
+    }
 
+    let profile = selected_profile(matches)?;
+    let mut settings = OutputSettings::from_matches(matches).with_profile(matches, &profile)?;
+    let resume = matches.get_one::<String>("continue");
+    if let Some(path) = resume {
+        if settings.compression.is_some() || settings.split_by_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--continue resumes a single uncompressed file; leave out -z and --split-by-length",
+            ));
+        }
+        if settings.transform_cmd.is_some() || settings.format.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--continue finds its place from the last word written, which --transform-cmd and --format rewrite",
+            ));
+        }
+        settings.output = Some(path.clone());
+        settings.tee.clear();
+        settings.pipe = None;
+        settings.archive = None;
+        settings.limits = SplitLimits::default();
+        settings.append = true;
+    }
     let config = Config {
-        min_len: matches
-            .get_one::<String>("min_len")
-            .unwrap()
-            .parse()
-            .expect("Invalid minimum length"),
-        max_len: matches
-            .get_one::<String>("max_len")
-            .unwrap()
-            .parse()
-            .expect("Invalid maximum length"),
-        charset: matches.get_one::<String>("charset").unwrap().to_string(),
-        template: matches.get_one::<String>("template").cloned(),
-        output: matches.get_one::<String>("output").cloned(),
-        no_duplicates: matches.get_flag("no-duplicates"),
+        output: settings.output.clone(),
+        ..keyspace_config(matches, &profile, settings.line_ending)?
     };
 
-    let total_combinations = calculate_size(&config);
-    println!("Will create approx: {} ({} combinations)", format_size(total_combinations), total_combinations);
-    
-    let progress = Progress::new(total_combinations);
-    println!("0% done");
+    if matches.get_flag("prealloc") {
+        let threads = matches.get_one::<usize>("prealloc-threads").copied().unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |cores| cores.get())
+        });
+        return run_preallocated(&settings, &config, threads);
+    }
 
-    if let Some(output) = &config.output {
-        let file = File::create(Path::new(output))?;
-        let mut writer = BufWriter::new(file);
-        generate_words(&config, &mut writer, &progress)?;
-    } else {
-        let mut stdout = io::stdout();
-        generate_words(&config, &mut stdout, &progress)?;
+    let mut window = Window::default();
+    if let Some(path) = resume {
+        window.skip = resume_point(&config, Path::new(path))?;
+        if !settings.quiet && window.skip > 0 {
+            eprintln!("Resuming {} after {} words", path, window.skip);
+        }
+    }
+    // Rewritten words give no place in the keyspace to resume from.
+    if settings.transform_cmd.is_none() && settings.format.is_none() {
+        settings.resume_from = Some(window.skip);
+    }
+
+    let total = estimate(&config)?.remaining(window.skip);
+    run_generation(&settings, Some(total), |writer, progress| {
+        generate_words_window(&config, writer, progress, &mut window)
+    })
+}
+
+/// `generate --prealloc`: the whole keyspace written into its `-o` file,
+/// grown to the exact size first, by `threads` threads at once.
+fn run_preallocated(settings: &OutputSettings, config: &Config, threads: usize) -> io::Result<()> {
+    let plain = settings.tee.is_empty()
+        && settings.pipe.is_none()
+        && settings.archive.is_none()
+        && settings.compression.is_none()
+        && settings.limits.max_lines.is_none()
+        && settings.limits.max_bytes.is_none()
+        && !settings.split_by_length
+        && settings.rate.is_none()
+        && settings.max_size.is_none()
+        && settings.unique.is_none()
+        && settings.transform_cmd.is_none()
+        && settings.format.is_none();
+    let output = match &settings.output {
+        Some(output) if output != "-" && plain => output,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--prealloc writes a single uncompressed -o file; leave out -z, splitting, --rate, --max-size, --unique, --transform-cmd and --format",
+            ))
+        }
+    };
+    let regions = prealloc::regions(config, threads)?;
+    let total = prealloc::total(&regions);
+    check_writable(Path::new(output), false)?;
+    if let (false, ProgressFormat::Text) = (settings.quiet, settings.progress_format) {
+        eprintln!("Will create: {} ({} combinations)", format_size(total.bytes), total.words);
     }
+    check_disk_space(settings, total)?;
 
-    println!("100% done");
+    let progress = Arc::new(Progress::new(total.words));
+    let reporter = spawn_reporter(settings, &progress)?;
+    let interruptible = interrupt::install().is_ok() || settings.duration.is_some();
+    if let Some(duration) = settings.duration {
+        interrupt::stop_after(duration);
+    }
+    let written = write_preallocated(
+        &regions,
+        config.line_ending,
+        Path::new(output),
+        threads,
+        settings.buffer_size,
+        &progress,
+        interruptible.then(interrupt::flag),
+    )?;
+
+    // An interrupted run keeps only the words in front of the first gap.
+    let file = FileCounts {
+        path: output.clone(),
+        words: Some(written.words),
+        bytes: written.bytes,
+    };
+    let mut report = progress.report(vec![file], Some(written.words));
+    report.words = written.words;
+    report.bytes = written.bytes;
+    let stats = write_stats(settings, &report);
+    if let Some(reporter) = reporter {
+        reporter.finish(report);
+    }
+    if !written.complete {
+        let stopped = if interrupt::timed_out() { "Reached --duration" } else { "Interrupted" };
+        eprintln!("{} after {} words", stopped, written.words);
+        eprintln!("Resume with: {}", resume_command(output));
+        if !interrupt::timed_out() {
+            stats?;
+            std::process::exit(130);
+        }
+    }
+    stats
+}
+
+fn estimate_command() -> Command {
+    Command::new("estimate")
+        .about("Print how many words and bytes a keyspace would produce, without generating it")
+        .args(keyspace_args())
+        .args(line_ending_args())
+}
+
+fn run_estimate(matches: &ArgMatches) -> io::Result<()> {
+    let profile = selected_profile(matches)?;
+    let line_ending = resolve_line_ending(matches, &profile)?;
+    let config = keyspace_config(matches, &profile, line_ending)?;
+    let total = estimate(&config)?;
+    println!("Words: {}", total.words);
+    println!("Size:  {} ({} bytes)", format_size(total.bytes), total.bytes);
+    if Filters::from_config(&config).judges_whole_words() {
+        println!("Entropy bounds and --not-in are not counted; the output may be smaller.");
+    }
+    if !estimate_is_exact(&config)? {
+        println!("--max-char-count is approximated for this keyspace; the output may differ.");
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            let err = Error::from(err);
+            eprintln!("crunch-rs: {}", err);
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn run() -> io::Result<()> {
+    let generate = generate_command();
+    let matches = Command::new("crunch-rs")
+        .version("1.0")
+        .author("lurg0th")
+        .about("A Rust clone of the crunch wordlist generator")
+        .override_usage("crunch-rs <min_len> <max_len> <charset> [OPTIONS]\n       crunch-rs <COMMAND>")
+        .after_help("Without a command the arguments are those of `generate`, so crunch invocations work unchanged.")
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(generate.clone())
+        .subcommand(estimate_command())
+        .subcommand(merge_command())
+        .subcommand(analyze_command())
+        .subcommand(mutate_command())
+        .subcommand(bench_command())
+        .subcommand(serve_command())
+        .subcommand(patterns_command())
+        .subcommand(walks_command())
+        .subcommand(prince_command())
+        .subcommand(profile_command())
+        .subcommand(train_command())
+        .subcommand(omen_command())
+        .subcommand(index_command())
+        .subcommand(word_command())
+        .subcommand(extract_command())
+        // The bare `crunch-rs MIN MAX CHARSET` form; documented under `generate`.
+        .args(generate.get_arguments().map(|arg| arg.clone().hide(true)))
+        .get_matches();
+
+    match matches.subcommand() {
+        Some(("generate", matches)) => run_generate(matches),
+        Some(("estimate", matches)) => run_estimate(matches),
+        Some(("merge", matches)) => run_merge(matches),
+        Some(("analyze", matches)) => run_analyze(matches),
+        Some(("mutate", matches)) => run_mutate(matches),
+        Some(("bench", matches)) => run_bench_command(matches),
+        Some(("serve", matches)) => run_serve(matches),
+        Some(("patterns", matches)) => run_patterns(matches),
+        Some(("walks", matches)) => run_walks(matches),
+        Some(("prince", matches)) => run_prince(matches),
+        Some(("profile", matches)) => run_profile(matches),
+        Some(("train", matches)) => run_train(matches),
+        Some(("omen", matches)) => run_omen(matches),
+        Some(("index", matches)) => run_index(matches),
+        Some(("word", matches)) => run_word(matches),
+        Some(("extract", matches)) => run_extract(matches),
+        _ => run_generate(&matches),
+    }
+}
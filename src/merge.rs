@@ -0,0 +1,182 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default number of bytes of words buffered before a sorted run is spilled.
+pub const DEFAULT_MEMORY: usize = 256 * 1024 * 1024;
+
+/// How merged words are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeOrder {
+    /// Bytewise, as `sort` does with `LC_ALL=C`.
+    #[default]
+    Lexicographic,
+    /// Shorter words first, equal lengths bytewise.
+    Length,
+}
+
+/// Sort key: the length only counts in [`MergeOrder::Length`].
+type Key = (usize, String);
+
+/// Merges words from any number of wordlists into one sorted list without
+/// duplicates, using an external sort so the input may exceed memory.
+///
+/// Words are collected until `memory` bytes are buffered; each full buffer is
+/// sorted, deduplicated and written to a temporary run file. [`finish`]
+/// then streams a k-way merge of the runs. Inputs that fit in memory never
+/// touch the disk.
+///
+/// [`finish`]: Merger::finish
+pub struct Merger {
+    order: MergeOrder,
+    memory: usize,
+    temp_dir: PathBuf,
+    buffer: Vec<Key>,
+    buffered: usize,
+    runs: Option<Runs>,
+}
+
+impl Merger {
+    pub fn new(order: MergeOrder, memory: usize, temp_dir: impl Into<PathBuf>) -> Self {
+        Merger {
+            order,
+            memory,
+            temp_dir: temp_dir.into(),
+            buffer: Vec::new(),
+            buffered: 0,
+            runs: None,
+        }
+    }
+
+    pub fn push(&mut self, word: &str) -> io::Result<()> {
+        self.buffered += word.len() + std::mem::size_of::<Key>();
+        let key = self.key(word.to_string());
+        self.buffer.push(key);
+        if self.buffered >= self.memory {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Number of runs spilled to disk so far.
+    pub fn runs(&self) -> usize {
+        self.runs.as_ref().map_or(0, |runs| runs.files.len())
+    }
+
+    /// Calls `emit` with every distinct word in order.
+    pub fn finish<F>(mut self, mut emit: F) -> io::Result<()>
+    where
+        F: FnMut(&str) -> io::Result<()>,
+    {
+        if self.runs.is_none() {
+            for (_, word) in sorted(std::mem::take(&mut self.buffer)) {
+                emit(&word)?;
+            }
+            return Ok(());
+        }
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        let runs = self.runs.as_ref().expect("runs were just spilled");
+
+        let mut readers = Vec::with_capacity(runs.files.len());
+        let mut heap = BinaryHeap::with_capacity(runs.files.len());
+        for (index, path) in runs.files.iter().enumerate() {
+            let mut reader = BufReader::new(File::open(path)?);
+            if let Some(word) = read_word(&mut reader)? {
+                heap.push(Reverse((self.key(word), index)));
+            }
+            readers.push(reader);
+        }
+
+        let mut last: Option<String> = None;
+        while let Some(Reverse(((_, word), index))) = heap.pop() {
+            if let Some(next) = read_word(&mut readers[index])? {
+                heap.push(Reverse((self.key(next), index)));
+            }
+            if last.as_deref() != Some(word.as_str()) {
+                emit(&word)?;
+                last = Some(word);
+            }
+        }
+        Ok(())
+    }
+
+    fn key(&self, word: String) -> Key {
+        match self.order {
+            MergeOrder::Lexicographic => (0, word),
+            MergeOrder::Length => (word.chars().count(), word),
+        }
+    }
+
+    /// Writes the buffered words out as one sorted run.
+    fn spill(&mut self) -> io::Result<()> {
+        if self.runs.is_none() {
+            self.runs = Some(Runs::create(&self.temp_dir, "merge")?);
+        }
+        let runs = self.runs.as_mut().expect("run directory was just created");
+        let path = runs.next_path();
+        let mut file = BufWriter::new(File::create(&path)?);
+        for (_, word) in sorted(std::mem::take(&mut self.buffer)) {
+            file.write_all(word.as_bytes())?;
+            file.write_all(b"\n")?;
+        }
+        file.flush()?;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+fn sorted(mut words: Vec<Key>) -> Vec<Key> {
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+fn read_word<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut word = String::new();
+    if reader.read_line(&mut word)? == 0 {
+        return Ok(None);
+    }
+    if word.ends_with('\n') {
+        word.pop();
+    }
+    Ok(Some(word))
+}
+
+/// Temporary directory holding the spilled runs, removed when dropped.
+pub(crate) struct Runs {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl Runs {
+    /// A new directory under `parent` named after `kind` and this process.
+    pub(crate) fn create(parent: &Path, kind: &str) -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = parent.join(format!(
+            "crunch-rs-{}-{}-{}",
+            kind,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&dir)?;
+        Ok(Runs { dir, files: Vec::new() })
+    }
+
+    /// The path of one more run file, counted in `files`.
+    pub(crate) fn next_path(&mut self) -> PathBuf {
+        let path = self.dir.join(format!("run-{}", self.files.len()));
+        self.files.push(path.clone());
+        path
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
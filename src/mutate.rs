@@ -0,0 +1,306 @@
+use crate::config::LengthBounds;
+use std::io;
+
+/// One hashcat-compatible rule function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Op {
+    Noop,
+    Lower,
+    Upper,
+    Capitalize,
+    InvertCapitalize,
+    ToggleAll,
+    ToggleAt(usize),
+    Reverse,
+    Duplicate,
+    Reflect,
+    RotateLeft,
+    RotateRight,
+    Append(char),
+    Prepend(char),
+    DeleteFirst,
+    DeleteLast,
+    DeleteAt(usize),
+    Truncate(usize),
+    Replace(char, char),
+    Purge(char),
+    DuplicateFirst(usize),
+    DuplicateLast(usize),
+    DuplicateEach,
+}
+
+/// A line of a hashcat rule file: rule functions applied left to right.
+///
+/// Only the commonly used, position-light subset of the hashcat rule language
+/// is supported; unknown functions are reported when the rule is parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    ops: Vec<Op>,
+}
+
+/// Rule positions are written `0`-`9` then `A`-`Z` for 10-35.
+fn position(c: Option<char>) -> Option<usize> {
+    match c? {
+        c @ '0'..='9' => Some(c as usize - '0' as usize),
+        c @ 'A'..='Z' => Some(c as usize - 'A' as usize + 10),
+        _ => None,
+    }
+}
+
+impl Rule {
+    pub fn parse(line: &str) -> io::Result<Self> {
+        let invalid = |what: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} in rule '{}'", what, line),
+            )
+        };
+        let mut ops = Vec::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            let op = match c {
+                ' ' | '\t' => continue,
+                ':' => Op::Noop,
+                'l' => Op::Lower,
+                'u' => Op::Upper,
+                'c' => Op::Capitalize,
+                'C' => Op::InvertCapitalize,
+                't' => Op::ToggleAll,
+                'r' => Op::Reverse,
+                'd' => Op::Duplicate,
+                'f' => Op::Reflect,
+                '{' => Op::RotateLeft,
+                '}' => Op::RotateRight,
+                '[' => Op::DeleteFirst,
+                ']' => Op::DeleteLast,
+                'q' => Op::DuplicateEach,
+                'T' | 'D' | '\'' | 'z' | 'Z' => {
+                    let n = position(chars.next()).ok_or_else(|| invalid("missing position"))?;
+                    match c {
+                        'T' => Op::ToggleAt(n),
+                        'D' => Op::DeleteAt(n),
+                        '\'' => Op::Truncate(n),
+                        'z' => Op::DuplicateFirst(n),
+                        _ => Op::DuplicateLast(n),
+                    }
+                }
+                '$' | '^' | '@' => {
+                    let x = chars.next().ok_or_else(|| invalid("missing character"))?;
+                    match c {
+                        '$' => Op::Append(x),
+                        '^' => Op::Prepend(x),
+                        _ => Op::Purge(x),
+                    }
+                }
+                's' => {
+                    let (Some(from), Some(to)) = (chars.next(), chars.next()) else {
+                        return Err(invalid("missing characters"));
+                    };
+                    Op::Replace(from, to)
+                }
+                other => return Err(invalid(&format!("unsupported function '{}'", other))),
+            };
+            ops.push(op);
+        }
+        Ok(Rule { ops })
+    }
+
+    /// Parses a rule file, skipping blank lines and `#` comments.
+    pub fn parse_file(contents: &str) -> io::Result<Vec<Rule>> {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(Rule::parse)
+            .collect()
+    }
+
+    pub fn apply(&self, word: &str) -> String {
+        let mut w: Vec<char> = word.chars().collect();
+        for op in &self.ops {
+            match *op {
+                Op::Noop => {}
+                Op::Lower => w.iter_mut().for_each(|c| *c = lower(*c)),
+                Op::Upper => w.iter_mut().for_each(|c| *c = upper(*c)),
+                Op::Capitalize | Op::InvertCapitalize => {
+                    let first_upper = *op == Op::Capitalize;
+                    for (i, c) in w.iter_mut().enumerate() {
+                        *c = if (i == 0) == first_upper { upper(*c) } else { lower(*c) };
+                    }
+                }
+                Op::ToggleAll => w.iter_mut().for_each(|c| *c = toggle(*c)),
+                Op::ToggleAt(n) => {
+                    if let Some(c) = w.get_mut(n) {
+                        *c = toggle(*c);
+                    }
+                }
+                Op::Reverse => w.reverse(),
+                Op::Duplicate => w.extend_from_within(..),
+                Op::Reflect => {
+                    let reversed: Vec<char> = w.iter().rev().copied().collect();
+                    w.extend(reversed);
+                }
+                Op::RotateLeft => {
+                    if !w.is_empty() {
+                        w.rotate_left(1);
+                    }
+                }
+                Op::RotateRight => {
+                    if !w.is_empty() {
+                        w.rotate_right(1);
+                    }
+                }
+                Op::Append(x) => w.push(x),
+                Op::Prepend(x) => w.insert(0, x),
+                Op::DeleteFirst => {
+                    if !w.is_empty() {
+                        w.remove(0);
+                    }
+                }
+                Op::DeleteLast => {
+                    w.pop();
+                }
+                Op::DeleteAt(n) => {
+                    if n < w.len() {
+                        w.remove(n);
+                    }
+                }
+                Op::Truncate(n) => w.truncate(n),
+                Op::Replace(from, to) => w.iter_mut().filter(|c| **c == from).for_each(|c| *c = to),
+                Op::Purge(x) => w.retain(|&c| c != x),
+                Op::DuplicateFirst(n) => {
+                    if let Some(&first) = w.first() {
+                        w.splice(0..0, std::iter::repeat_n(first, n));
+                    }
+                }
+                Op::DuplicateLast(n) => {
+                    if let Some(&last) = w.last() {
+                        w.extend(std::iter::repeat_n(last, n));
+                    }
+                }
+                Op::DuplicateEach => w = w.iter().flat_map(|&c| [c, c]).collect(),
+            }
+        }
+        w.into_iter().collect()
+    }
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn upper(c: char) -> char {
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+fn toggle(c: char) -> char {
+    if c.is_lowercase() {
+        upper(c)
+    } else {
+        lower(c)
+    }
+}
+
+/// Which case variants of each word to produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMode {
+    /// Lowercase, uppercase and capitalized.
+    Simple,
+    /// Every upper/lower combination of the word's letters.
+    All,
+}
+
+pub fn case_variants(word: &str, mode: CaseMode) -> Vec<String> {
+    match mode {
+        CaseMode::Simple => vec![
+            word.chars().map(lower).collect(),
+            word.chars().map(upper).collect(),
+            Rule {
+                ops: vec![Op::Capitalize],
+            }
+            .apply(word),
+        ],
+        CaseMode::All => {
+            let mut variants = vec![String::new()];
+            for c in word.chars() {
+                let (l, u) = (lower(c), upper(c));
+                variants = if l == u {
+                    variants.into_iter().map(|v| v + &c.to_string()).collect()
+                } else {
+                    variants
+                        .into_iter()
+                        .flat_map(|v| [format!("{}{}", v, l), format!("{}{}", v, u)])
+                        .collect()
+                };
+            }
+            variants
+        }
+    }
+}
+
+/// Common leetspeak substitutions.
+const LEET: &[(char, &[char])] = &[
+    ('a', &['4', '@']),
+    ('e', &['3']),
+    ('i', &['1', '!']),
+    ('o', &['0']),
+    ('s', &['5', '$']),
+    ('t', &['7']),
+    ('l', &['1']),
+    ('b', &['8']),
+    ('g', &['9']),
+];
+
+/// Every combination of keeping or leet-substituting each letter.
+pub fn leet_variants(word: &str) -> Vec<String> {
+    let mut variants = vec![String::new()];
+    for c in word.chars() {
+        let mut options = vec![c];
+        if let Some((_, subs)) = LEET.iter().find(|(letter, _)| *letter == lower(c)) {
+            options.extend_from_slice(subs);
+        }
+        variants = variants
+            .into_iter()
+            .flat_map(|v| options.iter().map(move |&o| format!("{}{}", v, o)))
+            .collect();
+    }
+    variants
+}
+
+/// The transforms `mutate` applies to every base word, in order: rules, then
+/// case variants, then leet variants, then suffixes.
+#[derive(Debug, Clone, Default)]
+pub struct Mutator {
+    pub rules: Vec<Rule>,
+    pub case: Option<CaseMode>,
+    pub leet: bool,
+    /// Each word is written once with every one of these; as it is when empty.
+    pub suffixes: Vec<String>,
+    /// Bounds every finished word is held to.
+    pub length: Option<LengthBounds>,
+}
+
+impl Mutator {
+    pub fn mutate(&self, word: &str) -> Vec<String> {
+        let mut words: Vec<String> = if self.rules.is_empty() {
+            vec![word.to_string()]
+        } else {
+            self.rules.iter().map(|rule| rule.apply(word)).collect()
+        };
+        if let Some(mode) = self.case {
+            words = words.iter().flat_map(|w| case_variants(w, mode)).collect();
+        }
+        if self.leet {
+            words = words.iter().flat_map(|w| leet_variants(w)).collect();
+        }
+        if !self.suffixes.is_empty() {
+            words = words
+                .iter()
+                .flat_map(|w| self.suffixes.iter().map(move |suffix| format!("{}{}", w, suffix)))
+                .collect();
+        }
+        if let Some(bounds) = &self.length {
+            words = words.iter().filter_map(|w| bounds.apply(w)).map(str::to_string).collect();
+        }
+        words
+    }
+}
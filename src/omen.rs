@@ -0,0 +1,316 @@
+use crate::config::LineEnding;
+use crate::generator::write_word;
+use crate::progress::Progress;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, BufRead, Write};
+
+/// Default n-gram size: each character is predicted from the two before it.
+pub const DEFAULT_NGRAM: usize = 3;
+
+/// Levels run from 0, the most likely, to `MAX_LEVEL`, which is what n-grams
+/// never seen in training get.
+pub const MAX_LEVEL: u32 = 10;
+
+/// Bits of improbability per level.
+const LEVEL_BITS: f64 = 1.5;
+
+/// An OMEN-style Markov model of a wordlist: how likely each word length is,
+/// each `ngram - 1` character start ("initial probability") and each
+/// character after the `ngram - 1` before it ("conditional probability"),
+/// with every probability rounded to a small integer level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    pub ngram: usize,
+    pub alphabet: Vec<char>,
+    pub lengths: BTreeMap<usize, u32>,
+    pub initial: HashMap<String, u32>,
+    pub conditional: HashMap<String, u32>,
+}
+
+/// Counts n-grams of training words; see [`Trainer::finish`].
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    ngram: usize,
+    words: u64,
+    alphabet: BTreeSet<char>,
+    lengths: BTreeMap<usize, u64>,
+    initial: HashMap<String, u64>,
+    conditional: HashMap<String, u64>,
+    contexts: HashMap<String, u64>,
+}
+
+impl Trainer {
+    pub fn new(ngram: usize) -> Self {
+        Trainer {
+            ngram: ngram.max(2),
+            words: 0,
+            alphabet: BTreeSet::new(),
+            lengths: BTreeMap::new(),
+            initial: HashMap::new(),
+            conditional: HashMap::new(),
+            contexts: HashMap::new(),
+        }
+    }
+
+    /// Counts `word`; words shorter than `ngram - 1` characters are skipped.
+    pub fn add(&mut self, word: &str) {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() < self.ngram - 1 {
+            return;
+        }
+        self.words += 1;
+        self.alphabet.extend(&chars);
+        *self.lengths.entry(chars.len()).or_default() += 1;
+        *self.initial.entry(chars[..self.ngram - 1].iter().collect()).or_default() += 1;
+        for gram in chars.windows(self.ngram) {
+            *self.conditional.entry(gram.iter().collect()).or_default() += 1;
+            *self.contexts.entry(gram[..self.ngram - 1].iter().collect()).or_default() += 1;
+        }
+    }
+
+    pub fn words(&self) -> u64 {
+        self.words
+    }
+
+    pub fn finish(self) -> Model {
+        let contexts = self.contexts;
+        let context_len = self.ngram - 1;
+        Model {
+            ngram: self.ngram,
+            alphabet: self.alphabet.into_iter().collect(),
+            lengths: self
+                .lengths
+                .into_iter()
+                .map(|(length, count)| (length, level(count, self.words)))
+                .collect(),
+            initial: self
+                .initial
+                .into_iter()
+                .map(|(gram, count)| (gram, level(count, self.words)))
+                .collect(),
+            conditional: self
+                .conditional
+                .into_iter()
+                .map(|(gram, count)| {
+                    let context: String = gram.chars().take(context_len).collect();
+                    let level = level(count, contexts[&context]);
+                    (gram, level)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The level of an event seen `count` times out of `total`.
+fn level(count: u64, total: u64) -> u32 {
+    let bits = -(count as f64 / total as f64).log2();
+    ((bits / LEVEL_BITS).floor() as u32).min(MAX_LEVEL - 1)
+}
+
+impl Model {
+    /// Writes the model as text: a header, the alphabet, then one
+    /// `ln`/`ip`/`cp` line per length, initial and conditional n-gram with
+    /// its level.
+    pub fn save<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "omen {}", self.ngram)?;
+        writeln!(out, "alphabet {}", self.alphabet.iter().collect::<String>())?;
+        for (length, level) in &self.lengths {
+            writeln!(out, "ln {} {}", level, length)?;
+        }
+        for (kind, grams) in [("ip", &self.initial), ("cp", &self.conditional)] {
+            let mut grams: Vec<_> = grams.iter().collect();
+            grams.sort();
+            for (gram, level) in grams {
+                writeln!(out, "{} {} {}", kind, level, gram)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a model written by [`Model::save`].
+    pub fn load<R: BufRead>(input: R) -> io::Result<Self> {
+        let mut model = Model {
+            ngram: 0,
+            alphabet: Vec::new(),
+            lengths: BTreeMap::new(),
+            initial: HashMap::new(),
+            conditional: HashMap::new(),
+        };
+        for (number, line) in input.lines().enumerate() {
+            let line = line?;
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid model line {}: '{}'", number + 1, line),
+                )
+            };
+            if number == 0 {
+                model.ngram = line
+                    .strip_prefix("omen ")
+                    .and_then(|ngram| ngram.parse().ok())
+                    .filter(|&ngram| ngram >= 2)
+                    .ok_or_else(invalid)?;
+                continue;
+            }
+            if let Some(alphabet) = line.strip_prefix("alphabet ") {
+                model.alphabet = alphabet.chars().collect();
+                continue;
+            }
+            let mut fields = line.splitn(3, ' ');
+            let (Some(kind), Some(level), Some(value)) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(invalid());
+            };
+            let level: u32 = level.parse().ok().filter(|&level| level <= MAX_LEVEL).ok_or_else(invalid)?;
+            let grams = match kind {
+                "ln" => {
+                    model.lengths.insert(value.parse().map_err(|_| invalid())?, level);
+                    continue;
+                }
+                "ip" => &mut model.initial,
+                "cp" => &mut model.conditional,
+                _ => return Err(invalid()),
+            };
+            grams.insert(value.to_string(), level);
+        }
+        if model.ngram == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty model file"));
+        }
+        Ok(model)
+    }
+}
+
+/// Enumerates candidates in order of increasing total level, i.e. roughly
+/// decreasing probability, as OMEN does.
+///
+/// A word's level is the sum of its length's, its start's and each later
+/// character's level. Every total from 0 up is visited in turn; within one,
+/// words are found depth first, trying the likeliest continuations first and
+/// pruning prefixes that can no longer meet the total exactly.
+pub struct Omen {
+    ngram: usize,
+    lengths: BTreeMap<usize, u32>,
+    /// Every possible start, likeliest first.
+    initial: Vec<(Vec<char>, u32)>,
+    /// The characters after each seen context, likeliest first.
+    next: HashMap<Vec<char>, Vec<(char, u32)>>,
+    /// The continuations of an unseen context: anything, at the top level.
+    unseen: Vec<(char, u32)>,
+}
+
+impl Omen {
+    pub fn new(model: &Model) -> Self {
+        let context_len = model.ngram - 1;
+        let mut starts: Vec<Vec<char>> = vec![Vec::new()];
+        for _ in 0..context_len {
+            starts = starts
+                .iter()
+                .flat_map(|start| model.alphabet.iter().map(move |&c| [start.as_slice(), &[c]].concat()))
+                .collect();
+        }
+        let mut initial: Vec<(Vec<char>, u32)> = starts
+            .into_iter()
+            .map(|start| {
+                let level = model.initial.get(&start.iter().collect::<String>()).copied();
+                (start, level.unwrap_or(MAX_LEVEL))
+            })
+            .collect();
+        initial.sort_by_key(|(_, level)| *level);
+
+        let mut next: HashMap<Vec<char>, Vec<(char, u32)>> = HashMap::new();
+        for gram in model.conditional.keys() {
+            let context: Vec<char> = gram.chars().take(context_len).collect();
+            next.entry(context).or_insert_with_key(|context| {
+                let mut options: Vec<(char, u32)> = model
+                    .alphabet
+                    .iter()
+                    .map(|&c| {
+                        let gram: String = context.iter().chain([&c]).collect();
+                        (c, model.conditional.get(&gram).copied().unwrap_or(MAX_LEVEL))
+                    })
+                    .collect();
+                options.sort_by_key(|(_, level)| *level);
+                options
+            });
+        }
+        Omen {
+            ngram: model.ngram,
+            lengths: model.lengths.clone(),
+            initial,
+            next,
+            unseen: model.alphabet.iter().map(|&c| (c, MAX_LEVEL)).collect(),
+        }
+    }
+
+    /// Writes every word of `min_len..=max_len` characters whose total level
+    /// is at most `max_level` (or every word at all), lowest levels first.
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        min_len: usize,
+        max_len: usize,
+        max_level: Option<u32>,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        let min_len = min_len.max(self.ngram - 1);
+        if min_len > max_len {
+            return Ok(());
+        }
+        let highest = MAX_LEVEL * (2 + (max_len + 1 - self.ngram) as u32);
+        let mut word = Vec::with_capacity(max_len);
+        for total in 0..=max_level.map_or(highest, |max_level| max_level.min(highest)) {
+            for len in min_len..=max_len {
+                let length_level = self.lengths.get(&len).copied().unwrap_or(MAX_LEVEL);
+                let Some(budget) = total.checked_sub(length_level) else {
+                    continue;
+                };
+                let positions = (len + 1 - self.ngram) as u32;
+                for (start, level) in &self.initial {
+                    let Some(rest) = budget.checked_sub(*level) else {
+                        break;
+                    };
+                    if rest > MAX_LEVEL * positions {
+                        continue;
+                    }
+                    word.clear();
+                    word.extend_from_slice(start);
+                    self.extend(&mut word, len, rest, writer, progress, line_ending)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn extend<W: Write + ?Sized>(
+        &self,
+        word: &mut Vec<char>,
+        len: usize,
+        budget: u32,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        if word.len() == len {
+            if budget == 0 {
+                let text: String = word.iter().collect();
+                write_word(writer, text.as_bytes(), progress, line_ending)?;
+            }
+            return Ok(());
+        }
+        let later = MAX_LEVEL * (len - word.len() - 1) as u32;
+        let context = &word[word.len() + 1 - self.ngram..];
+        let options = self.next.get(context).unwrap_or(&self.unseen);
+        for &(c, level) in options {
+            let Some(rest) = budget.checked_sub(level) else {
+                break;
+            };
+            if rest > later {
+                continue;
+            }
+            word.push(c);
+            self.extend(word, len, rest, writer, progress, line_ending)?;
+            word.pop();
+        }
+        Ok(())
+    }
+}
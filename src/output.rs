@@ -0,0 +1,1032 @@
+use crate::config::LineEnding;
+use crate::error::Error;
+use std::borrow::Cow;
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default size of the output buffer; see the `bench` subcommand for tuning.
+pub const DEFAULT_WRITE_BUFFER_SIZE: usize = 1 << 20;
+
+/// Default chunk file name: the first and last word the chunk contains.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{range}.txt";
+
+/// Parses a byte size such as `500`, `64kb`, `100MB` or `2GiB`.
+///
+/// Like crunch, `kb`/`mb`/`gb`/`tb` are powers of 1000 and `kib`/`mib`/`gib`/`tib`
+/// powers of 1024.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let number: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", value))?;
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(format!("unknown size unit '{}' in '{}'", unit, value)),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{}' is too large", value))
+}
+
+/// Collects output in one large reusable buffer and hands it to `inner` in
+/// big chunks, so the generators' many small writes cost a memcpy each rather
+/// than a system call.
+///
+/// Unlike `BufWriter`, a write never bypasses the buffer unless it is empty,
+/// and the buffer is only flushed once it has filled up.
+pub struct OutputBuffer<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+    capacity: usize,
+}
+
+impl<W: Write> OutputBuffer<W> {
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        let capacity = capacity.max(1);
+        OutputBuffer {
+            inner,
+            buf: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn write_buffered(&mut self) -> io::Result<()> {
+        let result = self.inner.write_all(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+impl<W: Write> Write for OutputBuffer<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.is_empty() && data.len() >= self.capacity {
+            self.inner.write_all(data)?;
+        } else {
+            self.buf.extend_from_slice(data);
+            if self.buf.len() >= self.capacity {
+                self.write_buffered()?;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write(data).map(|_| ())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for OutputBuffer<W> {
+    fn drop(&mut self) {
+        let _ = self.write_buffered();
+    }
+}
+
+/// Parses a wall-clock budget such as `90`, `45s`, `30m`, `2h` or `1h30m`;
+/// a bare number is seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}' (use e.g. 90s, 30m, 2h or 1h30m)", value);
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    let mut secs = 0u64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let number: u64 = rest[..split].parse().map_err(|_| invalid())?;
+        let unit_len = rest[split..].find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len() - split);
+        let unit: u64 = match &rest[split..split + unit_len] {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => return Err(invalid()),
+        };
+        secs = number
+            .checked_mul(unit)
+            .and_then(|part| secs.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = &rest[split + unit_len..];
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// An output rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+    WordsPerSec(u64),
+    BytesPerSec(u64),
+}
+
+/// Parses `50000/s` or `50k/s` as words per second and `10MB/s` or
+/// `512KiB/s` as bytes per second; the `/s` is optional.
+pub fn parse_rate(value: &str) -> Result<Rate, String> {
+    let amount = value.trim().strip_suffix("/s").unwrap_or(value.trim());
+    let count = parse_size(amount).map_err(|err| format!("invalid rate '{}': {}", value, err))?;
+    if count == 0 {
+        return Err(format!("rate '{}' must be positive", value));
+    }
+    if amount.to_ascii_lowercase().ends_with('b') {
+        Ok(Rate::BytesPerSec(count))
+    } else {
+        Ok(Rate::WordsPerSec(count))
+    }
+}
+
+/// Token-bucket rate limiter in front of another writer.
+///
+/// Tokens are words (counted by their terminator) or bytes; a write passes
+/// on as much as the bucket allows and sleeps when it runs dry, flushing the
+/// inner writer first so a consumer sees words as soon as they are released.
+pub struct Throttle<W: Write> {
+    inner: W,
+    per_sec: f64,
+    /// Where words end, when throttling by words.
+    framing: Option<Framing>,
+    tokens: f64,
+    capacity: f64,
+    refilled: Instant,
+}
+
+impl<W: Write> Throttle<W> {
+    pub fn new(inner: W, rate: Rate, line_ending: LineEnding) -> io::Result<Self> {
+        let (per_sec, framing) = match rate {
+            Rate::BytesPerSec(bytes) => (bytes as f64, None),
+            Rate::WordsPerSec(words) => (
+                words as f64,
+                Some(Framing::required(line_ending, "a words-per-second rate")?),
+            ),
+        };
+        // Allow bursts of up to 50ms worth of output.
+        let capacity = (per_sec / 20.0).max(1.0);
+        Ok(Throttle {
+            inner,
+            per_sec,
+            framing,
+            tokens: capacity,
+            capacity,
+            refilled: Instant::now(),
+        })
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned = now.duration_since(self.refilled).as_secs_f64() * self.per_sec;
+        self.tokens = (self.tokens + earned).min(self.capacity);
+        self.refilled = now;
+    }
+}
+
+impl<W: Write> Write for Throttle<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.refill();
+        if self.tokens < 1.0 {
+            self.inner.flush()?;
+            while self.tokens < 1.0 {
+                thread::sleep(Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec));
+                self.refill();
+            }
+        }
+
+        let allowed = self.tokens.floor() as usize;
+        let (len, spent) = match &self.framing {
+            None => (buf.len().min(allowed), buf.len().min(allowed)),
+            Some(framing) => {
+                // Pass whole words up to the allowance; a trailing partial word is free
+                // and gets charged when its terminator comes through.
+                let mut words = 0;
+                let mut len = buf.len();
+                for end in framing.ends(buf) {
+                    words += 1;
+                    if words == allowed {
+                        len = end;
+                        break;
+                    }
+                }
+                (len, words)
+            }
+        };
+        self.tokens -= spent as f64;
+        self.inner.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Stops the output at a byte budget without cutting a word in half.
+///
+/// Bytes are held back until their word's terminator arrives; the first
+/// word that would overshoot the budget fails the write, and everything
+/// before it has been passed on.
+pub struct SizeLimit<W: Write> {
+    inner: W,
+    max_bytes: u64,
+    remaining: u64,
+    framing: Framing,
+    words: u64,
+    last_word: Vec<u8>,
+    full: bool,
+}
+
+impl<W: Write> SizeLimit<W> {
+    pub fn new(inner: W, max_bytes: u64, line_ending: LineEnding) -> io::Result<Self> {
+        Ok(SizeLimit {
+            inner,
+            max_bytes,
+            remaining: max_bytes,
+            framing: Framing::required(line_ending, "a size limit")?,
+            words: 0,
+            last_word: Vec::new(),
+            full: false,
+        })
+    }
+
+    /// Whether the budget has run out.
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Words passed on so far.
+    pub fn words(&self) -> u64 {
+        self.words
+    }
+
+    /// Bytes passed on so far.
+    pub fn bytes(&self) -> u64 {
+        self.max_bytes - self.remaining
+    }
+
+    /// The last word passed on, without its terminator.
+    pub fn last_word(&self) -> &[u8] {
+        &self.last_word
+    }
+
+    /// Passes on as many of the whole words in `words` as the budget allows.
+    fn forward(&mut self, words: &[u8]) -> io::Result<()> {
+        let fits = if words.len() as u64 <= self.remaining {
+            words
+        } else {
+            self.full = true;
+            let end = self.framing.ends(&words[..self.remaining as usize]).last().unwrap_or(0);
+            &words[..end]
+        };
+        let Some(last) = self.framing.lines(fits).last() else {
+            return Ok(());
+        };
+        self.inner.write_all(fits)?;
+        self.remaining -= fits.len() as u64;
+        self.words += self.framing.ends(fits).count() as u64;
+        self.last_word.clear();
+        self.last_word.extend_from_slice(self.framing.word(last));
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SizeLimit<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.full {
+            return Err(io::Error::other("the size limit has been reached"));
+        }
+        let words = self.framing.complete(buf);
+        self.forward(&words)?;
+        if self.full {
+            return Err(io::Error::other("the size limit has been reached"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Stops the output between two words once `flag` is set.
+///
+/// Unlike [`SizeLimit`] nothing is held back: the flag is only checked at the
+/// start of a write that follows a complete word, which costs next to
+/// nothing per write. Without a line ending every write counts as complete.
+pub struct Interruptible<W: Write> {
+    inner: W,
+    flag: &'static AtomicBool,
+    framing: Option<Framing>,
+    at_boundary: bool,
+    interrupted: bool,
+}
+
+impl<W: Write> Interruptible<W> {
+    pub fn new(inner: W, flag: &'static AtomicBool, line_ending: LineEnding) -> Self {
+        Interruptible {
+            inner,
+            flag,
+            framing: Framing::of(line_ending),
+            at_boundary: true,
+            interrupted: false,
+        }
+    }
+
+    /// Whether the output stopped because the flag was set.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+}
+
+impl<W: Write> Write for Interruptible<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.interrupted || (self.at_boundary && self.flag.load(Ordering::Relaxed)) {
+            self.interrupted = true;
+            // Not `ErrorKind::Interrupted`, which `write_all` would retry.
+            return Err(io::Error::other("the output was interrupted"));
+        }
+        self.inner.write_all(buf)?;
+        match &self.framing {
+            Some(framing) if !buf.is_empty() => self.at_boundary = framing.ends_word(buf),
+            _ => {}
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Free bytes on the filesystem that `path` is, or would be, created on.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    // Walk up to the nearest directory that already exists.
+    let existing = path
+        .ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+        .unwrap_or(Path::new("."));
+    fs4::available_space(existing)
+}
+
+/// Fails before anything is generated when `path` cannot be written: as a
+/// file, or as the directory of chunks when `directory` is set. Nothing is
+/// created; a chunk directory that does not exist yet passes, as
+/// [`Splitter::new`] creates it along with its parents.
+pub fn check_writable(path: &Path, directory: bool) -> io::Result<()> {
+    let fail = |kind: io::ErrorKind, message: String| -> io::Result<()> {
+        Err(Error::output(path.display().to_string(), io::Error::new(kind, message)).into())
+    };
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() && !directory => {
+            fail(io::ErrorKind::IsADirectory, "it is a directory".to_string())
+        }
+        Ok(metadata) if !metadata.is_dir() && directory => fail(
+            io::ErrorKind::NotADirectory,
+            "it is a file, but split output needs a directory".to_string(),
+        ),
+        Ok(metadata) if metadata.permissions().readonly() => {
+            fail(io::ErrorKind::PermissionDenied, "it is read-only".to_string())
+        }
+        Ok(_) => Ok(()),
+        Err(_) if directory => Ok(()),
+        Err(_) => {
+            let parent = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            match fs::metadata(parent) {
+                Ok(metadata) if metadata.is_dir() => Ok(()),
+                Ok(_) => fail(
+                    io::ErrorKind::NotADirectory,
+                    format!("{} is not a directory", parent.display()),
+                ),
+                Err(_) => fail(
+                    io::ErrorKind::NotFound,
+                    format!("the directory {} does not exist", parent.display()),
+                ),
+            }
+        }
+    }
+}
+
+/// When to start a new chunk file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SplitLimits {
+    pub max_lines: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+/// The chunk currently being written, under a temporary name until its last
+/// word is known.
+struct Chunk {
+    writer: BufWriter<File>,
+    temp_path: PathBuf,
+    lines: u64,
+    bytes: u64,
+    first: Vec<u8>,
+    last: Vec<u8>,
+}
+
+/// Where finished chunks end up.
+enum Destination {
+    Directory(PathBuf),
+    Zip(zip::ZipWriter<File>),
+    SevenZ(sevenz_rust::SevenZWriter<File>),
+}
+
+fn archive_error(err: impl std::fmt::Display) -> io::Error {
+    io::Error::other(format!("archive: {}", err))
+}
+
+/// Splits a stream of terminated words into chunks, naming each after the
+/// words it contains via a template with `{first}`, `{last}`, `{range}`
+/// (`{first}-{last}`), `{index}` and `{timestamp}` placeholders. Chunks become
+/// files in a directory or members of a single zip/7z archive.
+pub struct Splitter {
+    destination: Destination,
+    /// Where chunks are staged until they are complete.
+    work_dir: PathBuf,
+    name_template: String,
+    limits: SplitLimits,
+    framing: Framing,
+    timestamp: u64,
+    chunk: Option<Chunk>,
+    files: Vec<PathBuf>,
+}
+
+impl Splitter {
+    /// Writes the chunks as files in `directory`, creating it if needed.
+    pub fn new(
+        directory: &Path,
+        name_template: &str,
+        limits: SplitLimits,
+        line_ending: LineEnding,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(directory).map_err(|err| Error::output(directory.display().to_string(), err))?;
+        let destination = Destination::Directory(directory.to_path_buf());
+        Self::with_destination(destination, directory, name_template, limits, line_ending)
+    }
+
+    /// Writes the chunks as members of a `.zip` or `.7z` archive at `path`.
+    pub fn into_archive(
+        path: &Path,
+        name_template: &str,
+        limits: SplitLimits,
+        line_ending: LineEnding,
+    ) -> io::Result<Self> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+        let destination = match extension.as_deref() {
+            Some("zip") => Destination::Zip(zip::ZipWriter::new(
+                File::create(path).map_err(|err| Error::output(path.display().to_string(), err))?,
+            )),
+            Some("7z") => Destination::SevenZ(sevenz_rust::SevenZWriter::create(path).map_err(archive_error)?),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported archive '{}', expected a .zip or .7z file", path.display()),
+                ))
+            }
+        };
+        let work_dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        Self::with_destination(destination, work_dir, name_template, limits, line_ending)
+    }
+
+    fn with_destination(
+        destination: Destination,
+        work_dir: &Path,
+        name_template: &str,
+        limits: SplitLimits,
+        line_ending: LineEnding,
+    ) -> io::Result<Self> {
+        let framing = Framing::required(line_ending, "split output")?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Ok(Splitter {
+            destination,
+            work_dir: work_dir.to_path_buf(),
+            name_template: name_template.to_string(),
+            limits,
+            framing,
+            timestamp,
+            chunk: None,
+            files: Vec::new(),
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let full = self.chunk.as_ref().is_some_and(|chunk| {
+            self.limits.max_lines.is_some_and(|max| chunk.lines >= max)
+                || self
+                    .limits
+                    .max_bytes
+                    .is_some_and(|max| chunk.lines > 0 && chunk.bytes + line.len() as u64 > max)
+        });
+        if full {
+            self.close_chunk()?;
+        }
+        if self.chunk.is_none() {
+            let temp_path = self.work_dir.join(format!(
+                ".crunch-rs-chunk-{}-{}.tmp",
+                std::process::id(),
+                self.files.len() + 1
+            ));
+            self.chunk = Some(Chunk {
+                writer: BufWriter::new(File::create(&temp_path)?),
+                temp_path,
+                lines: 0,
+                bytes: 0,
+                first: Vec::new(),
+                last: Vec::new(),
+            });
+        }
+
+        let chunk = self.chunk.as_mut().unwrap();
+        chunk.writer.write_all(line)?;
+        let word = self.framing.word(line);
+        if chunk.lines == 0 {
+            chunk.first = word.to_vec();
+        }
+        chunk.last.clear();
+        chunk.last.extend_from_slice(word);
+        chunk.lines += 1;
+        chunk.bytes += line.len() as u64;
+        Ok(())
+    }
+
+    fn close_chunk(&mut self) -> io::Result<()> {
+        let Some(mut chunk) = self.chunk.take() else {
+            return Ok(());
+        };
+        chunk.writer.flush()?;
+        drop(chunk.writer);
+        let index = self.files.len() + 1;
+        let mut name = PathBuf::from(self.chunk_name(&chunk.first, &chunk.last, index));
+        if let Destination::Directory(directory) = &self.destination {
+            name = directory.join(name);
+        }
+        if self.files.contains(&name) {
+            // The template does not tell chunks apart; keep both.
+            name.set_file_name(format!("{}.{}", name.file_name().unwrap().to_string_lossy(), index));
+        }
+
+        match &mut self.destination {
+            Destination::Directory(_) => fs::rename(&chunk.temp_path, &name)?,
+            Destination::Zip(archive) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(chunk.bytes >= u32::MAX as u64);
+                archive
+                    .start_file(name.to_string_lossy(), options)
+                    .map_err(archive_error)?;
+                io::copy(&mut File::open(&chunk.temp_path)?, archive)?;
+                fs::remove_file(&chunk.temp_path)?;
+            }
+            Destination::SevenZ(archive) => {
+                let entry = sevenz_rust::SevenZArchiveEntry::from_path(
+                    &chunk.temp_path,
+                    name.to_string_lossy().into_owned(),
+                );
+                archive
+                    .push_archive_entry(entry, Some(File::open(&chunk.temp_path)?))
+                    .map_err(archive_error)?;
+                fs::remove_file(&chunk.temp_path)?;
+            }
+        }
+        self.files.push(name);
+        Ok(())
+    }
+
+    fn chunk_name(&self, first: &[u8], last: &[u8], index: usize) -> String {
+        let first = sanitize(first);
+        let last = sanitize(last);
+        self.name_template
+            .replace("{range}", &format!("{}-{}", first, last))
+            .replace("{first}", &first)
+            .replace("{last}", &last)
+            .replace("{index}", &format!("{:04}", index))
+            .replace("{timestamp}", &self.timestamp.to_string())
+    }
+
+    /// Flushes any partial line, closes the last chunk and returns every file
+    /// written, or for an archive the names of its members.
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        let line = self.framing.take_pending();
+        if !line.is_empty() {
+            self.write_line(&line)?;
+        }
+        self.close_chunk()?;
+        match self.destination {
+            Destination::Directory(_) => {}
+            Destination::Zip(archive) => {
+                archive.finish().map_err(archive_error)?;
+            }
+            Destination::SevenZ(archive) => {
+                archive.finish()?;
+            }
+        }
+        Ok(self.files)
+    }
+}
+
+/// Where the words of a stream end, for the writers that work on whole
+/// words: each ends with the line ending, and a word cut across writes is
+/// held back until its terminator arrives.
+pub struct Framing {
+    terminator: &'static [u8],
+    /// Every terminator ends in this byte; a CR before an LF stays with its word.
+    delimiter: u8,
+    /// The start of a word whose terminator has not been written yet.
+    pending: Vec<u8>,
+}
+
+impl Framing {
+    /// Words ended by `line_ending`, if it has any bytes.
+    pub fn of(line_ending: LineEnding) -> Option<Self> {
+        let terminator = line_ending.as_bytes();
+        Some(Framing {
+            terminator,
+            delimiter: *terminator.last()?,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Like [`Framing::of`], but fails naming `option` without a line ending.
+    pub fn required(line_ending: LineEnding, option: &str) -> io::Result<Self> {
+        Self::of(line_ending).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} needs a line ending to find word boundaries", option),
+            )
+        })
+    }
+
+    pub fn terminator(&self) -> &'static [u8] {
+        self.terminator
+    }
+
+    /// `line` without its terminator.
+    pub fn word<'a>(&self, line: &'a [u8]) -> &'a [u8] {
+        line.strip_suffix(self.terminator).unwrap_or(line)
+    }
+
+    /// Offsets just past each terminator in `buf`.
+    pub fn ends<'a>(&self, buf: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let delimiter = self.delimiter;
+        buf.iter()
+            .enumerate()
+            .filter(move |&(_, &byte)| byte == delimiter)
+            .map(|(i, _)| i + 1)
+    }
+
+    /// Whether `buf` stops between two words.
+    pub fn ends_word(&self, buf: &[u8]) -> bool {
+        buf.last() == Some(&self.delimiter)
+    }
+
+    /// The whole words `buf` completes, the one begun by earlier writes
+    /// included; the unterminated rest is held back for the next call.
+    pub fn complete<'a>(&mut self, buf: &'a [u8]) -> Cow<'a, [u8]> {
+        let Some(last) = buf.iter().rposition(|&byte| byte == self.delimiter) else {
+            self.pending.extend_from_slice(buf);
+            return Cow::Borrowed(&[]);
+        };
+        let (complete, tail) = buf.split_at(last + 1);
+        let run = if self.pending.is_empty() {
+            Cow::Borrowed(complete)
+        } else {
+            let mut run = std::mem::take(&mut self.pending);
+            run.extend_from_slice(complete);
+            Cow::Owned(run)
+        };
+        self.pending.extend_from_slice(tail);
+        run
+    }
+
+    /// Each word of `run`, from [`Framing::complete`], with its terminator.
+    pub fn lines<'a>(&self, run: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a {
+        let delimiter = self.delimiter;
+        run.split_inclusive(move |&byte| byte == delimiter)
+    }
+
+    /// The unterminated last word, for a writer that is finishing.
+    pub fn take_pending(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl Write for Splitter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.write_line(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.chunk {
+            Some(chunk) => chunk.writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// One file of a [`LengthSplitter`] and what went into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthFile {
+    pub path: PathBuf,
+    pub length: usize,
+    pub words: u64,
+    pub bytes: u64,
+}
+
+/// `path` with `-{length}` added before the first dot of its file name, so
+/// `out.txt` becomes `out-4.txt` and `out.txt.gz` becomes `out-4.txt.gz`.
+pub fn length_file_name(path: &Path, length: usize) -> PathBuf {
+    let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    // A leading dot belongs to the name, not an extension.
+    let name = match name.char_indices().skip(1).find(|&(_, c)| c == '.') {
+        Some((dot, _)) => format!("{}-{}{}", &name[..dot], length, &name[dot..]),
+        None => format!("{}-{}", name, length),
+    };
+    path.with_file_name(name)
+}
+
+/// Sends each terminated word to a file for its length in characters
+/// (bytes, for words that are not UTF-8), named by [`length_file_name`].
+///
+/// Files are opened with `open` when their first word arrives and each
+/// keeps its own writer, so one length filling up its buffer does not
+/// flush the others.
+pub struct LengthSplitter<W: Write, F: FnMut(&Path) -> io::Result<W>> {
+    base: PathBuf,
+    open: F,
+    framing: Framing,
+    files: BTreeMap<usize, (LengthFile, W)>,
+}
+
+impl<W: Write, F: FnMut(&Path) -> io::Result<W>> LengthSplitter<W, F> {
+    pub fn new(base: &Path, line_ending: LineEnding, open: F) -> io::Result<Self> {
+        Ok(LengthSplitter {
+            base: base.to_path_buf(),
+            open,
+            framing: Framing::required(line_ending, "split output")?,
+            files: BTreeMap::new(),
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let word = self.framing.word(line);
+        let length = match std::str::from_utf8(word) {
+            Ok(word) => word.chars().count(),
+            Err(_) => word.len(),
+        };
+        let (file, writer) = match self.files.entry(length) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let path = length_file_name(&self.base, length);
+                let writer = (self.open)(&path)?;
+                let file = LengthFile {
+                    path,
+                    length,
+                    words: 0,
+                    bytes: 0,
+                };
+                entry.insert((file, writer))
+            }
+        };
+        writer.write_all(line)?;
+        file.words += 1;
+        file.bytes += line.len() as u64;
+        Ok(())
+    }
+
+    /// Writes any partial last word, flushes every file and returns them
+    /// shortest length first.
+    pub fn finish(mut self) -> io::Result<Vec<LengthFile>> {
+        let line = self.framing.take_pending();
+        if !line.is_empty() {
+            self.write_line(&line)?;
+        }
+        self.flush()?;
+        Ok(self.files.into_values().map(|(file, _)| file).collect())
+    }
+}
+
+impl<W: Write, F: FnMut(&Path) -> io::Result<W>> Write for LengthSplitter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.write_line(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.files.values_mut().try_for_each(|(_, writer)| writer.flush())
+    }
+}
+
+/// `command` run through the platform shell.
+pub(crate) fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Starts `command` through the platform shell with a pipe to its stdin.
+///
+/// Writes block while the child's pipe buffer is full, so generation runs at
+/// the consumer's pace; once the child exits they fail with `BrokenPipe`.
+pub fn spawn_pipe(command: &str) -> io::Result<Child> {
+    shell(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| Error::output(format!("to '{}'", command), err).into())
+}
+
+/// Copies everything written to several named sinks, each with its own
+/// buffering.
+///
+/// A sink that fails is dropped and its error kept for [`Tee::finish`], so a
+/// cracker exiting early does not stop the copy on disk. Writes only fail
+/// once every sink has, with the kind of the last error.
+#[derive(Default)]
+pub struct Tee {
+    sinks: Vec<(String, Box<dyn Write>)>,
+    failed: Vec<(String, io::Error)>,
+}
+
+impl Tee {
+    pub fn new() -> Self {
+        Tee::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, sink: impl Write + 'static) {
+        self.sinks.push((name.into(), Box::new(sink)));
+    }
+
+    /// Flushes and closes every sink, returning those that failed along the way.
+    pub fn finish(mut self) -> Vec<(String, io::Error)> {
+        for (name, mut sink) in self.sinks.drain(..) {
+            if let Err(err) = sink.flush() {
+                self.failed.push((name, err));
+            }
+        }
+        self.failed
+    }
+
+    fn detach(&mut self, index: usize, err: io::Error) {
+        let (name, _) = self.sinks.remove(index);
+        self.failed.push((name, err));
+    }
+
+    /// Err once no sink is left.
+    fn check_alive(&self) -> io::Result<()> {
+        match self.failed.last() {
+            Some((name, err)) if self.sinks.is_empty() => {
+                Err(io::Error::new(err.kind(), format!("{}: {}", name, err)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Write for Tee {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_all(data).map(|()| data.len())
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut index = 0;
+        while index < self.sinks.len() {
+            match self.sinks[index].1.write_all(data) {
+                Ok(()) => index += 1,
+                Err(err) => self.detach(index, err),
+            }
+        }
+        self.check_alive()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut index = 0;
+        while index < self.sinks.len() {
+            match self.sinks[index].1.flush() {
+                Ok(()) => index += 1,
+                Err(err) => self.detach(index, err),
+            }
+        }
+        self.check_alive()
+    }
+}
+
+/// Cuts a partially written last word off the end of the file at `path` and
+/// returns the last complete word, if there is one. This is how an interrupted
+/// run finds where to pick up again.
+pub fn truncate_partial_line(path: &Path, line_ending: LineEnding) -> io::Result<Option<Vec<u8>>> {
+    // The terminator's last byte never occurs inside a word.
+    let terminator = line_ending.as_bytes();
+    let Some(&marker) = terminator.last() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot find word boundaries without a line ending",
+        ));
+    };
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let Some(last) = rfind_byte(&mut file, len, marker)? else {
+        file.set_len(0)?;
+        return Ok(None);
+    };
+    file.set_len(last + 1)?;
+
+    let word_end = (last + 1).saturating_sub(terminator.len() as u64);
+    let word_start = rfind_byte(&mut file, word_end, marker)?.map_or(0, |pos| pos + 1);
+    let mut word = vec![0; (word_end - word_start) as usize];
+    file.seek(SeekFrom::Start(word_start))?;
+    file.read_exact(&mut word)?;
+    Ok(Some(word))
+}
+
+/// Number of words in a file of `line_ending`-terminated words.
+pub fn count_lines(path: &Path, line_ending: LineEnding) -> io::Result<u64> {
+    let marker = *line_ending.as_bytes().last().unwrap_or(&b'\n');
+    let mut file = File::open(path)?;
+    let mut buf = vec![0; 1 << 16];
+    let mut count = 0;
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            return Ok(count);
+        }
+        count += buf[..read].iter().filter(|&&byte| byte == marker).count() as u64;
+    }
+}
+
+/// Offset of the last `byte` in `file` before offset `end`, reading backwards.
+fn rfind_byte(file: &mut File, end: u64, byte: u8) -> io::Result<Option<u64>> {
+    let mut buf = vec![0; 1 << 16];
+    let mut end = end;
+    while end > 0 {
+        let start = end.saturating_sub(buf.len() as u64);
+        let block = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(block)?;
+        if let Some(pos) = block.iter().rposition(|&b| b == byte) {
+            return Ok(Some(start + pos as u64));
+        }
+        end = start;
+    }
+    Ok(None)
+}
+
+/// Makes a word safe to use inside a file name.
+fn sanitize(word: &[u8]) -> String {
+    String::from_utf8_lossy(word)
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
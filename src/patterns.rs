@@ -0,0 +1,197 @@
+use crate::charset::Charset;
+use crate::config::LineEnding;
+use crate::filter::Filters;
+use crate::generator::{generate_layout, write_word};
+use crate::progress::Progress;
+use std::io::{self, Write};
+
+/// A calendar date in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Parses `YYYY-MM-DD`.
+    pub fn parse(value: &str) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid date '{}', expected YYYY-MM-DD", value),
+            )
+        };
+        let mut parts = value.splitn(3, '-').map(|part| part.parse::<u32>());
+        let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+        Ok(Date { year, month, day })
+    }
+
+    pub fn next(self) -> Self {
+        if self.day < days_in_month(self.year, self.month) {
+            Date { day: self.day + 1, ..self }
+        } else if self.month < 12 {
+            Date { month: self.month + 1, day: 1, ..self }
+        } else {
+            Date { year: self.year + 1, month: 1, day: 1 }
+        }
+    }
+
+    /// Renders the date using `YYYY`, `YY`, `MM`, `DD`, `M` and `D` tokens;
+    /// everything else is copied literally.
+    pub fn format(&self, format: &str) -> String {
+        let mut out = String::with_capacity(format.len());
+        let mut rest = format;
+        while !rest.is_empty() {
+            let (rendered, len) = if rest.starts_with("YYYY") {
+                (format!("{:04}", self.year), 4)
+            } else if rest.starts_with("YY") {
+                (format!("{:02}", self.year % 100), 2)
+            } else if rest.starts_with("MM") {
+                (format!("{:02}", self.month), 2)
+            } else if rest.starts_with("DD") {
+                (format!("{:02}", self.day), 2)
+            } else if rest.starts_with('M') {
+                (self.month.to_string(), 1)
+            } else if rest.starts_with('D') {
+                (self.day.to_string(), 1)
+            } else {
+                let c = rest.chars().next().unwrap();
+                (c.to_string(), c.len_utf8())
+            };
+            out.push_str(&rendered);
+            rest = &rest[len..];
+        }
+        out
+    }
+}
+
+fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Every date from `from` to `to` inclusive, rendered in each of `formats`.
+#[derive(Debug, Clone)]
+pub struct DateRange {
+    pub from: Date,
+    pub to: Date,
+    pub formats: Vec<String>,
+}
+
+impl DateRange {
+    pub fn days(&self) -> u64 {
+        let mut days = 0;
+        let mut date = self.from;
+        while date <= self.to {
+            days += 1;
+            date = date.next();
+        }
+        days
+    }
+
+    pub fn size(&self) -> u64 {
+        self.days() * self.formats.len() as u64
+    }
+
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        for format in &self.formats {
+            let mut date = self.from;
+            while date <= self.to {
+                write_word(writer, date.format(format).as_bytes(), progress, line_ending)?;
+                date = date.next();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Phone numbers: every prefix followed by every `digits`-long subscriber number.
+#[derive(Debug, Clone)]
+pub struct PhoneNumbers {
+    pub prefixes: Vec<String>,
+    pub digits: usize,
+}
+
+impl PhoneNumbers {
+    /// Builds the prefixes as every country code combined with every area code.
+    pub fn new(countries: &[String], areas: &[String], digits: usize) -> Self {
+        let countries = if countries.is_empty() { vec![String::new()] } else { countries.to_vec() };
+        let areas = if areas.is_empty() { vec![String::new()] } else { areas.to_vec() };
+        let prefixes = countries
+            .iter()
+            .flat_map(|country| areas.iter().map(move |area| format!("{}{}", country, area)))
+            .collect();
+        PhoneNumbers { prefixes, digits }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.prefixes.len() as u64 * 10u64.saturating_pow(self.digits as u32)
+    }
+
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        let digits = Charset::digits();
+        for prefix in &self.prefixes {
+            let prefix = Charset::literal(prefix.as_bytes());
+            let mut layout = vec![&prefix];
+            layout.extend(std::iter::repeat_n(&digits, self.digits));
+            generate_layout(&layout, writer, progress, &Filters::default(), line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+/// Zero-padded numeric PINs from `from` to `to` inclusive, `width` digits wide.
+#[derive(Debug, Clone)]
+pub struct PinRange {
+    pub from: u64,
+    pub to: u64,
+    pub width: usize,
+}
+
+impl PinRange {
+    pub fn size(&self) -> u64 {
+        if self.to < self.from {
+            0
+        } else {
+            self.to - self.from + 1
+        }
+    }
+
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        for pin in self.from..=self.to {
+            let word = format!("{:0width$}", pin, width = self.width);
+            write_word(writer, word.as_bytes(), progress, line_ending)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+use crate::charset::Charset;
+use crate::config::{Config, LineEnding};
+use crate::error::Error;
+use crate::filter::Filters;
+use crate::generator::generate_layout;
+use crate::layout::{layouts, Layout};
+use crate::output::Interruptible;
+use crate::progress::Progress;
+use crate::size::Estimate;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Fewest words a region is split down to; smaller regions only add writes.
+const MIN_REGION_WORDS: u64 = 100_000;
+
+/// Regions handed to each thread on average, so a slow one holds up little.
+const REGIONS_PER_THREAD: u64 = 16;
+
+/// A stretch of the output made of every word of `layout`, which starts
+/// `offset` bytes into the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub layout: Layout,
+    pub offset: u64,
+    pub words: u64,
+    pub bytes: u64,
+}
+
+/// Exact words and bytes, terminators included, of every word of `layout`,
+/// or `None` past `u64::MAX`.
+pub fn layout_extent(layout: &[Charset], line_ending: &[u8]) -> Option<(u64, u64)> {
+    let words = layout
+        .iter()
+        .try_fold(1u64, |words, charset| words.checked_mul(charset.len() as u64))?;
+    let mut bytes = words.checked_mul(line_ending.len() as u64)?;
+    // Each symbol of a position appears in `words / len` of the words.
+    for charset in layout.iter().filter(|charset| !charset.is_empty()) {
+        let symbols: u64 = charset.symbols().iter().map(|symbol| symbol.len() as u64).sum();
+        bytes = bytes.checked_add(symbols.checked_mul(words / charset.len() as u64)?)?;
+    }
+    Some((words, bytes))
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "keyspace has more than 2^64 words or bytes")
+}
+
+/// Splits `layout` on its first position with a choice into slices of about
+/// `target` words, in output order.
+fn split(layout: Layout, target: u64, line_ending: &[u8], regions: &mut Vec<Region>) -> io::Result<()> {
+    let (words, bytes) = layout_extent(&layout, line_ending).ok_or_else(too_large)?;
+    let Some(pos) = layout.iter().position(|charset| charset.len() > 1).filter(|_| words > target) else {
+        let offset = regions.last().map_or(0, |last| last.offset + last.bytes);
+        regions.push(Region {
+            layout,
+            offset,
+            words,
+            bytes,
+        });
+        return Ok(());
+    };
+    let symbols = layout[pos].symbols();
+    let parts = words.div_ceil(target).min(symbols.len() as u64) as usize;
+    for share in symbols.chunks(symbols.len().div_ceil(parts)) {
+        let mut slice = layout.clone();
+        slice[pos] = Charset::from_symbols(share.to_vec());
+        split(slice, target, line_ending, regions)?;
+    }
+    Ok(())
+}
+
+/// The keyspace of `config` cut into regions that can be written
+/// independently, each given its exact place in the output.
+///
+/// Only unfiltered keyspaces in their usual order have places known in
+/// advance, so filters, `--shuffle` and `--order interleave` are refused.
+pub fn regions(config: &Config, threads: usize) -> io::Result<Vec<Region>> {
+    if Filters::from_config(config).is_active() || config.shuffle_seed.is_some() || config.interleave.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--prealloc needs the exact output size, so it cannot be combined with filters, --shuffle or --order interleave",
+        ));
+    }
+    let line_ending = config.line_ending.as_bytes();
+    let layouts = layouts(config)?;
+    let mut total = 0u64;
+    for layout in &layouts {
+        let (words, _) = layout_extent(layout, line_ending).ok_or_else(too_large)?;
+        total = total.checked_add(words).ok_or_else(too_large)?;
+    }
+    let target = (total / (threads.max(1) as u64 * REGIONS_PER_THREAD)).max(MIN_REGION_WORDS);
+    let mut regions = Vec::new();
+    for layout in layouts {
+        split(layout, target, line_ending, &mut regions)?;
+    }
+    Ok(regions)
+}
+
+/// Writes through a buffer to a fixed place in a file shared with others.
+struct RegionWriter<'a> {
+    file: &'a File,
+    offset: u64,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for RegionWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.capacity {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        write_all_at(self.file, &self.buffer, self.offset)?;
+        self.offset += self.buffer.len() as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &File, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_write(buf, offset)? {
+            0 => return Err(io::ErrorKind::WriteZero.into()),
+            written => {
+                buf = &buf[written..];
+                offset += written as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Words and bytes of `regions` together.
+pub fn total(regions: &[Region]) -> Estimate {
+    Estimate {
+        words: regions.iter().map(|region| region.words).sum(),
+        bytes: regions.last().map_or(0, |last| last.offset + last.bytes),
+    }
+}
+
+/// What a preallocated run left in its file: the first `words` words, in
+/// `bytes` bytes. `complete` is false when it stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preallocated {
+    pub words: u64,
+    pub bytes: u64,
+    pub complete: bool,
+}
+
+/// Writes `regions`, as cut by [`regions`], to `path`, which is first grown
+/// to their exact size, with `threads` threads each filling regions of its
+/// own through a `buffer_size` buffer.
+///
+/// Once `stop` is set the threads finish their current word and the file is
+/// cut back to the regions finished in front of every unfinished one, so it
+/// holds a prefix of the keyspace that `--continue` can resume.
+pub fn write_preallocated(
+    regions: &[Region],
+    line_ending: LineEnding,
+    path: &Path,
+    threads: usize,
+    buffer_size: usize,
+    progress: &Progress,
+    stop: Option<&'static AtomicBool>,
+) -> io::Result<Preallocated> {
+    let size = total(regions).bytes;
+    let fail = |err: io::Error| -> io::Error { Error::output(path.display().to_string(), err).into() };
+    let file = File::create(path).map_err(fail)?;
+    // Reserve the blocks where the filesystem can; a sparse file otherwise.
+    match fs4::fs_std::FileExt::allocate(&file, size) {
+        Err(err) if err.kind() == io::ErrorKind::StorageFull => return Err(fail(err)),
+        Err(_) => file.set_len(size).map_err(fail)?,
+        Ok(()) => {}
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let finished: Vec<AtomicBool> = regions.iter().map(|_| AtomicBool::new(false)).collect();
+    let error = Mutex::new(None);
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, regions.len().max(1)) {
+            scope.spawn(|| {
+                let mut buffer = Vec::with_capacity(buffer_size);
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= regions.len()
+                        || failed.load(Ordering::Relaxed)
+                        || stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+                    {
+                        return;
+                    }
+                    let region = &regions[index];
+                    let layout: Vec<&Charset> = region.layout.iter().collect();
+                    let mut writer = RegionWriter {
+                        file: &file,
+                        offset: region.offset,
+                        buffer,
+                        capacity: buffer_size,
+                    };
+                    let generate = |writer: &mut dyn Write| {
+                        generate_layout(&layout, writer, progress, &Filters::default(), line_ending)
+                            .and_then(|()| writer.flush())
+                    };
+                    let (result, interrupted) = match stop {
+                        Some(stop) => {
+                            let mut writer = Interruptible::new(&mut writer, stop, line_ending);
+                            let result = generate(&mut writer);
+                            (result, writer.is_interrupted())
+                        }
+                        None => (generate(&mut writer), false),
+                    };
+                    buffer = writer.buffer;
+                    match result {
+                        Ok(()) => finished[index].store(true, Ordering::Relaxed),
+                        Err(_) if interrupted => return,
+                        Err(err) => {
+                            failed.store(true, Ordering::Relaxed);
+                            error.lock().unwrap().get_or_insert(err);
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let done = finished.iter().take_while(|finished| finished.load(Ordering::Relaxed)).count();
+    let written = Preallocated {
+        words: regions[..done].iter().map(|region| region.words).sum(),
+        bytes: regions.get(done).map_or(size, |region| region.offset),
+        complete: done == regions.len(),
+    };
+    if !written.complete {
+        file.set_len(written.bytes).map_err(fail)?;
+    }
+    match error.into_inner().unwrap() {
+        Some(err) => Err(fail(err)),
+        None => Ok(written),
+    }
+}
@@ -0,0 +1,107 @@
+use crate::charset::Charset;
+use crate::filter::Filters;
+use crate::config::LineEnding;
+use crate::generator::generate_layout;
+use crate::progress::Progress;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+/// One chain shape: the element lengths concatenated to form a candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub lengths: Vec<usize>,
+    /// Number of candidates the chain produces.
+    pub keyspace: u64,
+}
+
+/// PRINCE-style candidates: concatenations of input words ("elements") whose
+/// total length falls within `min_len..=max_len`, emitted chain by chain in
+/// order of increasing keyspace so small, likely chains come first.
+#[derive(Debug, Clone)]
+pub struct Prince {
+    /// Distinct input words grouped by length in characters.
+    elements: BTreeMap<usize, Charset>,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub max_elements: usize,
+}
+
+impl Prince {
+    pub fn new<I>(words: I, min_len: usize, max_len: usize, max_elements: usize) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut by_length: BTreeMap<usize, BTreeSet<String>> = BTreeMap::new();
+        for word in words {
+            let length = word.chars().count();
+            if length > 0 && length <= max_len {
+                by_length.entry(length).or_default().insert(word);
+            }
+        }
+        let elements = by_length
+            .into_iter()
+            .map(|(length, words)| {
+                let symbols = words.into_iter().map(String::into_bytes).collect();
+                (length, Charset::from_symbols(symbols))
+            })
+            .collect();
+        Prince {
+            elements,
+            min_len,
+            max_len,
+            max_elements,
+        }
+    }
+
+    /// Every chain shape within the length bounds, smallest keyspace first.
+    pub fn chains(&self) -> Vec<Chain> {
+        let mut chains = Vec::new();
+        let mut lengths = Vec::new();
+        for total in self.min_len.max(1)..=self.max_len {
+            self.compose(total, &mut lengths, &mut chains);
+        }
+        chains.sort_by(|a, b| a.keyspace.cmp(&b.keyspace).then_with(|| a.lengths.cmp(&b.lengths)));
+        chains
+    }
+
+    /// Appends every way of splitting `remaining` characters into available element lengths.
+    fn compose(&self, remaining: usize, lengths: &mut Vec<usize>, chains: &mut Vec<Chain>) {
+        if remaining == 0 {
+            let keyspace = lengths.iter().fold(1u64, |total, length| {
+                total.saturating_mul(self.elements[length].len() as u64)
+            });
+            chains.push(Chain {
+                lengths: lengths.clone(),
+                keyspace,
+            });
+            return;
+        }
+        if lengths.len() == self.max_elements {
+            return;
+        }
+        for &length in self.elements.keys().take_while(|&&length| length <= remaining) {
+            lengths.push(length);
+            self.compose(remaining - length, lengths, chains);
+            lengths.pop();
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.chains()
+            .iter()
+            .fold(0u64, |total, chain| total.saturating_add(chain.keyspace))
+    }
+
+    pub fn generate<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        progress: &Progress,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        for chain in self.chains() {
+            let layout: Vec<&Charset> = chain.lengths.iter().map(|length| &self.elements[length]).collect();
+            generate_layout(&layout, writer, progress, &Filters::default(), line_ending)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A named set of generation and output settings from a profiles file.
+///
+/// Keys mirror the long command-line options (`min-len`, `exclude-substr`, ...);
+/// anything left out falls back to the command line or its default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    pub charset: Option<String>,
+    /// File whose bytes are the charset, as given to `--charset-file`.
+    pub charset_file: Option<String>,
+    pub template: Option<String>,
+    /// Template placeholders, e.g. `set = { "1" = "abc" }`.
+    #[serde(default)]
+    pub set: BTreeMap<char, String>,
+    /// Words a template's `*` stands for, as given to `--wordlist`.
+    pub wordlist: Option<String>,
+    pub no_duplicates: Option<bool>,
+    pub exclude_chars: Option<String>,
+    pub exclude_substr: Option<Vec<String>>,
+    pub max_char_count: Option<usize>,
+    pub startswith: Option<String>,
+    pub endswith: Option<String>,
+    pub contains: Option<Vec<String>>,
+    /// Class requirements such as `digit:2,symbol:1+`, as accepted by `--require`.
+    pub require: Option<String>,
+    pub min_entropy: Option<f64>,
+    pub max_entropy: Option<f64>,
+    /// Wordlists whose words are left out, as given to `--not-in`.
+    pub not_in: Option<Vec<String>>,
+    pub not_in_fp_rate: Option<f64>,
+    /// `charset`, `freq` or `interleave`, as accepted by `--order`.
+    pub order: Option<String>,
+    pub interleave_batch: Option<u64>,
+    /// Wordlist that `--order freq` learns its frequencies from.
+    pub train: Option<String>,
+    pub shuffle: Option<bool>,
+    pub seed: Option<u64>,
+    /// Numbers and ranges such as `1990-2025`, as accepted by `--append-years`.
+    pub append_years: Option<String>,
+    pub append_numbers: Option<String>,
+    pub append_separators: Option<String>,
+    pub pad_to: Option<usize>,
+    pub pad_char: Option<String>,
+    /// `left` or `right`, as accepted by `--pad-side`.
+    pub pad_side: Option<String>,
+    /// `skip` or `truncate`, as accepted by `--length-policy`.
+    pub length_policy: Option<String>,
+    pub output: Option<String>,
+    /// Shell command that receives the words on its stdin.
+    pub pipe: Option<String>,
+    /// `.zip` or `.7z` archive receiving each chunk as a member.
+    pub archive: Option<String>,
+    /// Rate limit such as `50000/s` or `10MB/s`, as accepted by `--rate`.
+    pub rate: Option<String>,
+    /// Output buffer size such as `4MiB`, as accepted by `--buffer-size`.
+    pub buffer_size: Option<String>,
+    /// Output budget such as `100gb`, as accepted by `--max-size`.
+    pub max_size: Option<String>,
+    /// Time budget such as `2h`, as accepted by `--duration`.
+    pub duration: Option<String>,
+    /// Chunk size such as `100mb`, as accepted by `--split-bytes`.
+    pub split_bytes: Option<String>,
+    pub split_lines: Option<u64>,
+    pub split_by_length: Option<bool>,
+    pub name_template: Option<String>,
+    /// `gzip` or `zstd`, as accepted by `-z`.
+    pub compress: Option<String>,
+    pub compress_threads: Option<usize>,
+    pub index_every: Option<u64>,
+    pub unique: Option<bool>,
+    /// Memory such as `1GiB` for `--unique`, as accepted by `--unique-memory`.
+    pub unique_memory: Option<String>,
+    pub transform_cmd: Option<String>,
+    /// `jsonl` or `csv`, as accepted by `--format`.
+    pub format: Option<String>,
+    pub quiet: Option<bool>,
+    pub progress_format: Option<String>,
+    pub stats_file: Option<String>,
+    /// `lf`, `crlf`, `nul` or `none`.
+    pub line_ending: Option<String>,
+}
+
+/// The `[profiles.NAME]` tables of a profiles file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileFile {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Profile used when a config file is given without `--profile`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+impl ProfileFile {
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        toml::from_str(contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| Error::input(path.display().to_string(), err))?;
+        Self::parse(&contents)
+            .map_err(|err| Error::config(format!("invalid config {}: {}", path.display(), err)).into())
+    }
+
+    /// Looks up `name`, or the `default` profile when no name is given.
+    pub fn profile(&self, name: Option<&str>) -> io::Result<&Profile> {
+        let wanted = name.unwrap_or(DEFAULT_PROFILE);
+        self.profiles.get(wanted).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no profile '{}' (available: {})", wanted, known.join(", ")),
+            )
+        })
+    }
+}
+
+/// `$XDG_CONFIG_HOME/crunch-rs/profiles.toml`, falling back to `~/.config`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("crunch-rs").join("profiles.toml"))
+}
+
+/// Loads the profile selected by `--config`/`--profile`, if either was given.
+pub fn load_profile(config: Option<&Path>, name: Option<&str>) -> io::Result<Option<Profile>> {
+    let path = match (config, name) {
+        (None, None) => return Ok(None),
+        (Some(path), _) => path.to_path_buf(),
+        (None, Some(_)) => default_config_path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "cannot locate the profiles file; pass --config")
+        })?,
+    };
+    ProfileFile::load(&path)?.profile(name).cloned().map(Some)
+}
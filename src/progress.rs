@@ -0,0 +1,459 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+const BAR_WIDTH: usize = 30;
+
+/// How progress events are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// Redrawn single-line bar for humans.
+    Text,
+    /// One JSON object per line, for orchestration tools.
+    Json,
+}
+
+/// One thread's counts. Only its own thread writes it, with plain loads and
+/// stores rather than read-modify-write atomics; the reporter adds them up.
+#[derive(Default)]
+#[repr(align(64))]
+struct Tally {
+    words: AtomicU64,
+    bytes: AtomicU64,
+    /// Candidates enumerated but dropped by a whole-word filter.
+    rejected: AtomicU64,
+}
+
+impl Tally {
+    fn add(counter: &AtomicU64, n: u64) {
+        counter.store(counter.load(Ordering::Relaxed) + n, Ordering::Relaxed);
+    }
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The tally this thread counts into, with the id of its `Progress`.
+    static TALLY: RefCell<Option<(u64, Arc<Tally>)>> = const { RefCell::new(None) };
+}
+
+/// Counters updated by the generator and read by the reporter thread.
+///
+/// Each generating thread counts into a tally of its own, found through a
+/// thread-local, so counting a word costs two uncontended stores and no
+/// cache line bounces between threads. Reading adds up every tally, which
+/// the reporter does a couple of times a second.
+pub struct Progress {
+    id: u64,
+    tallies: Mutex<Vec<(ThreadId, Arc<Tally>)>>,
+    /// Expected number of candidates, if known up front.
+    total: Option<u64>,
+    /// `None` where there is no clock, on wasm32-unknown-unknown.
+    start: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(total: u64) -> Self {
+        Progress {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            tallies: Mutex::new(Vec::new()),
+            total: Some(total),
+            start: (!cfg!(all(target_arch = "wasm32", target_os = "unknown"))).then(Instant::now),
+        }
+    }
+
+    /// Progress for a stream whose length is not known in advance.
+    pub fn unbounded() -> Self {
+        Progress {
+            total: None,
+            ..Progress::new(0)
+        }
+    }
+
+    /// Records one emitted word of `bytes` bytes (including the line terminator).
+    pub fn increment(&self, bytes: u64) {
+        self.add(1, bytes);
+    }
+
+    /// Records a batch of `words` emitted words totalling `bytes` bytes.
+    pub fn add(&self, words: u64, bytes: u64) {
+        self.count(|tally| {
+            Tally::add(&tally.words, words);
+            Tally::add(&tally.bytes, bytes);
+        });
+    }
+
+    /// Records `words` candidates that were enumerated but not emitted.
+    pub fn reject(&self, words: u64) {
+        self.count(|tally| Tally::add(&tally.rejected, words));
+    }
+
+    fn count(&self, update: impl FnOnce(&Tally)) {
+        TALLY.with(|cached| {
+            let mut cached = cached.borrow_mut();
+            match &*cached {
+                Some((id, tally)) if *id == self.id => update(tally),
+                _ => {
+                    let tally = self.register();
+                    update(&tally);
+                    *cached = Some((self.id, tally));
+                }
+            }
+        })
+    }
+
+    /// The calling thread's tally, created on its first count.
+    fn register(&self) -> Arc<Tally> {
+        let thread = thread::current().id();
+        let mut tallies = self.tallies.lock().unwrap();
+        if let Some((_, tally)) = tallies.iter().find(|(owner, _)| *owner == thread) {
+            return Arc::clone(tally);
+        }
+        let tally = Arc::new(Tally::default());
+        tallies.push((thread, Arc::clone(&tally)));
+        tally
+    }
+
+    fn sum(&self, counter: impl Fn(&Tally) -> &AtomicU64) -> u64 {
+        let tallies = self.tallies.lock().unwrap();
+        tallies.iter().map(|(_, tally)| counter(tally).load(Ordering::Relaxed)).sum()
+    }
+
+    pub fn words(&self) -> u64 {
+        self.sum(|tally| &tally.words)
+    }
+
+    /// Candidates looked at so far, emitted or not.
+    pub fn enumerated(&self) -> u64 {
+        self.words() + self.sum(|tally| &tally.rejected)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.sum(|tally| &tally.bytes)
+    }
+
+    /// Words, candidates enumerated and bytes, read together.
+    fn counts(&self) -> (u64, u64, u64) {
+        let tallies = self.tallies.lock().unwrap();
+        tallies.iter().fold((0, 0, 0), |(words, enumerated, bytes), (_, tally)| {
+            let written = tally.words.load(Ordering::Relaxed);
+            (
+                words + written,
+                enumerated + written + tally.rejected.load(Ordering::Relaxed),
+                bytes + tally.bytes.load(Ordering::Relaxed),
+            )
+        })
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.map_or(Duration::ZERO, |start| start.elapsed())
+    }
+
+    fn render(&self) -> String {
+        let (words, enumerated, bytes) = self.counts();
+        let elapsed = self.elapsed().as_secs_f64();
+        let (words_per_sec, bytes_per_sec, enumerated_per_sec) = if elapsed > 0.0 {
+            (words as f64 / elapsed, bytes as f64 / elapsed, enumerated as f64 / elapsed)
+        } else {
+            (0.0, 0.0, 0.0)
+        };
+        let count = if enumerated > words {
+            format!("{} words of {} enumerated", words, enumerated)
+        } else {
+            format!("{} words", words)
+        };
+
+        let Some(total) = self.total else {
+            return format!(
+                "{} ({} words/s, {}/s) elapsed {}",
+                count,
+                format_count(words_per_sec),
+                format_bytes(bytes_per_sec),
+                format_duration(self.elapsed())
+            );
+        };
+
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            (enumerated as f64 / total as f64).min(1.0)
+        };
+
+        let filled = (fraction * BAR_WIDTH as f64) as usize;
+        let bar = format!("{}{}", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled));
+
+        let eta = if enumerated > 0 && enumerated < total && enumerated_per_sec > 0.0 {
+            format_duration(Duration::from_secs_f64(
+                (total - enumerated) as f64 / enumerated_per_sec,
+            ))
+        } else {
+            "--:--:--".to_string()
+        };
+
+        format!(
+            "[{}] {:5.1}% {} ({} words/s, {}/s) elapsed {} ETA {}",
+            bar,
+            fraction * 100.0,
+            count,
+            format_count(words_per_sec),
+            format_bytes(bytes_per_sec),
+            format_duration(self.elapsed()),
+            eta
+        )
+    }
+
+    fn render_json(&self) -> String {
+        let (words, enumerated, bytes) = self.counts();
+        let elapsed = self.elapsed().as_secs_f64();
+        let (words_per_sec, bytes_per_sec, enumerated_per_sec) = if elapsed > 0.0 {
+            (words as f64 / elapsed, bytes as f64 / elapsed, enumerated as f64 / elapsed)
+        } else {
+            (0.0, 0.0, 0.0)
+        };
+        let (total, percent, eta) = match self.total {
+            None => ("null".to_string(), "null".to_string(), "null".to_string()),
+            Some(total) => {
+                let percent = if total == 0 {
+                    100.0
+                } else {
+                    (enumerated as f64 / total as f64 * 100.0).min(100.0)
+                };
+                let eta = if enumerated < total && enumerated_per_sec > 0.0 {
+                    format!("{:.3}", (total - enumerated) as f64 / enumerated_per_sec)
+                } else {
+                    "null".to_string()
+                };
+                (total.to_string(), format!("{:.2}", percent), eta)
+            }
+        };
+
+        format!(
+            "{{\"event\":\"progress\",\"words\":{},\"enumerated\":{},\"total\":{},\"bytes\":{},\"percent\":{},\"elapsed_secs\":{:.3},\"words_per_sec\":{:.1},\"bytes_per_sec\":{:.1},\"eta_secs\":{}}}",
+            words, enumerated, total, bytes, percent, elapsed, words_per_sec, bytes_per_sec, eta
+        )
+    }
+
+    /// The run so far as a [`Report`].
+    pub fn report(&self, files: Vec<FileCounts>, resume_index: Option<u64>) -> Report {
+        let (words, enumerated, bytes) = self.counts();
+        Report {
+            words,
+            skipped: enumerated - words,
+            duplicates: None,
+            bytes,
+            elapsed: self.elapsed(),
+            files,
+            resume_index,
+        }
+    }
+}
+
+/// What one output file received. `words` is only known for outputs that
+/// count per file, such as `--split-by-length`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCounts {
+    pub path: String,
+    pub words: Option<u64>,
+    pub bytes: u64,
+}
+
+/// What a run produced, printed when it ends and written by `--stats-file`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub words: u64,
+    /// Candidates enumerated but dropped by whole-word filters.
+    pub skipped: u64,
+    /// Repeated words dropped by `--unique`, when it is on.
+    pub duplicates: Option<u64>,
+    pub bytes: u64,
+    pub elapsed: Duration,
+    pub files: Vec<FileCounts>,
+    /// How many words of the keyspace precede the next one to generate, for
+    /// commands that can be resumed.
+    pub resume_index: Option<u64>,
+}
+
+impl Report {
+    fn rates(&self) -> (f64, f64) {
+        let elapsed = self.elapsed.as_secs_f64();
+        if elapsed > 0.0 {
+            (self.words as f64 / elapsed, self.bytes as f64 / elapsed)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// The report for people, one line per output file after the totals.
+    pub fn to_text(&self) -> String {
+        let (words_per_sec, bytes_per_sec) = self.rates();
+        let duplicates = self
+            .duplicates
+            .map_or(String::new(), |duplicates| format!(", {} duplicates dropped", duplicates));
+        let mut text = format!(
+            "{} words written, {} skipped by filters{}, {} in {} ({} words/s, {}/s)",
+            self.words,
+            self.skipped,
+            duplicates,
+            format_bytes(self.bytes as f64),
+            format_duration(self.elapsed),
+            format_count(words_per_sec),
+            format_bytes(bytes_per_sec)
+        );
+        for file in &self.files {
+            text.push_str(&format!("\n  {}: ", file.path));
+            if let Some(words) = file.words {
+                text.push_str(&format!("{} words, ", words));
+            }
+            text.push_str(&format_bytes(file.bytes as f64));
+        }
+        if let Some(index) = self.resume_index {
+            text.push_str(&format!("\nResume index: {}", index));
+        }
+        text
+    }
+
+    /// The report as the `summary` event of `--progress-format json`.
+    pub fn to_json(&self) -> String {
+        let (words_per_sec, bytes_per_sec) = self.rates();
+        let output_files: Vec<String> = self.files.iter().map(|file| json_string(&file.path)).collect();
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| {
+                format!(
+                    "{{\"path\":{},\"words\":{},\"bytes\":{}}}",
+                    json_string(&file.path),
+                    file.words.map_or("null".to_string(), |words| words.to_string()),
+                    file.bytes
+                )
+            })
+            .collect();
+        format!(
+            "{{\"event\":\"summary\",\"words\":{},\"skipped\":{}{},\"enumerated\":{},\"bytes\":{},\"duration_secs\":{:.3},\"words_per_sec\":{:.1},\"bytes_per_sec\":{:.1},\"resume_index\":{},\"output_files\":[{}],\"files\":[{}]}}",
+            self.words,
+            self.skipped,
+            self.duplicates
+                .map_or(String::new(), |duplicates| format!(",\"duplicates\":{}", duplicates)),
+            self.words + self.skipped + self.duplicates.unwrap_or(0),
+            self.bytes,
+            self.elapsed.as_secs_f64(),
+            words_per_sec,
+            bytes_per_sec,
+            self.resume_index.map_or("null".to_string(), |index| index.to_string()),
+            output_files.join(","),
+            files.join(",")
+        )
+    }
+}
+
+/// Background thread that emits progress to a sink at a fixed interval.
+pub struct Reporter {
+    stop: Sender<Report>,
+    handle: JoinHandle<()>,
+}
+
+impl Reporter {
+    pub fn spawn(
+        progress: Arc<Progress>,
+        format: ProgressFormat,
+        mut sink: Box<dyn Write + Send>,
+        interval: Duration,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel::<Report>();
+        let handle = thread::spawn(move || {
+            let mut drawn = 0;
+            loop {
+                let _ = match format {
+                    ProgressFormat::Text => {
+                        let line = progress.render();
+                        // Pads over the end of a longer previous line.
+                        let written = write!(sink, "\r{:width$}", line, width = drawn);
+                        drawn = line.chars().count();
+                        written
+                    }
+                    ProgressFormat::Json => writeln!(sink, "{}", progress.render_json()),
+                };
+                let _ = sink.flush();
+                match stopped.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Ok(report) => {
+                        let _ = match format {
+                            // The report takes the place of the bar.
+                            ProgressFormat::Text => {
+                                writeln!(sink, "\r{:width$}\r{}", "", report.to_text(), width = drawn)
+                            }
+                            ProgressFormat::Json => {
+                                writeln!(sink, "{}\n{}", progress.render_json(), report.to_json())
+                            }
+                        };
+                        let _ = sink.flush();
+                        return;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Reporter { stop, handle }
+    }
+
+    /// Stops the reporter after emitting the final state and `report`.
+    pub fn finish(self, report: Report) {
+        let _ = self.stop.send(report);
+        let _ = self.handle.join();
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn format_count(count: f64) -> String {
+    if count >= 1e9 {
+        format!("{:.2}G", count / 1e9)
+    } else if count >= 1e6 {
+        format!("{:.2}M", count / 1e6)
+    } else if count >= 1e3 {
+        format!("{:.2}K", count / 1e3)
+    } else {
+        format!("{:.0}", count)
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes / GB)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes / MB)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes / KB)
+    } else {
+        format!("{:.0} B", bytes)
+    }
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
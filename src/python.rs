@@ -0,0 +1,215 @@
+//! Python bindings, built with `--features python` (see `pyproject.toml`).
+//!
+//! ```python
+//! import crunch_rs
+//! crunch = crunch_rs.Crunch(4, 4, "abc123", contains=["1"])
+//! print(crunch.size())
+//! for word in crunch:
+//!     ...  # bytes, as charsets need not be text
+//! crunch_rs.mutate(["pass"], case="simple", leet=True)
+//! ```
+
+// The code pyo3's macros generate for `PyResult` returns trips this lint.
+#![allow(clippy::useless_conversion)]
+
+use crate::classes::parse_requirements;
+use crate::config::Config;
+use crate::frequency::Frequencies;
+use crate::generator::Window;
+use crate::mutate::{CaseMode, Mutator, Rule};
+use crate::rank::{rank, unrank};
+use crate::size::calculate_size;
+use crate::stream::Words;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+fn to_py(err: io::Error) -> PyErr {
+    match err.kind() {
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => PyValueError::new_err(err.to_string()),
+        _ => PyOSError::new_err(err.to_string()),
+    }
+}
+
+/// A keyspace: the same settings as the command line's, as keyword arguments.
+#[pyclass(name = "Crunch", module = "crunch_rs")]
+#[derive(Clone)]
+struct PyCrunch {
+    config: Config,
+}
+
+#[pymethods]
+impl PyCrunch {
+    #[new]
+    #[pyo3(signature = (
+        min_len,
+        max_len,
+        charset = "abcdefghijklmnopqrstuvwxyz",
+        *,
+        template = None,
+        placeholders = BTreeMap::new(),
+        no_duplicates = false,
+        exclude_chars = String::new(),
+        exclude_substrings = Vec::new(),
+        starts_with = None,
+        ends_with = None,
+        contains = Vec::new(),
+        require = None,
+        max_char_count = None,
+        min_entropy = None,
+        max_entropy = None,
+        order = "charset",
+        train = None,
+        seed = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        min_len: usize,
+        max_len: usize,
+        charset: &str,
+        template: Option<String>,
+        placeholders: BTreeMap<char, String>,
+        no_duplicates: bool,
+        exclude_chars: String,
+        exclude_substrings: Vec<String>,
+        starts_with: Option<String>,
+        ends_with: Option<String>,
+        contains: Vec<String>,
+        require: Option<&str>,
+        max_char_count: Option<usize>,
+        min_entropy: Option<f64>,
+        max_entropy: Option<f64>,
+        order: &str,
+        train: Option<&str>,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let frequencies = match (order, train) {
+            ("freq", Some(path)) => Some(Frequencies::learn(path).map_err(to_py)?),
+            ("freq", None) => Some(Frequencies::builtin()),
+            ("charset", None) => None,
+            ("charset", Some(_)) => return Err(PyValueError::new_err("train only applies to order='freq'")),
+            (order, _) => return Err(PyValueError::new_err(format!("unknown order '{}'", order))),
+        };
+        let crunch = PyCrunch {
+            config: Config {
+                min_len,
+                max_len,
+                charset: charset.to_string(),
+                template,
+                placeholders,
+                no_duplicates,
+                exclude_chars,
+                exclude_substrings,
+                starts_with,
+                ends_with,
+                contains,
+                require: require
+                    .map(parse_requirements)
+                    .transpose()
+                    .map_err(PyValueError::new_err)?
+                    .unwrap_or_default(),
+                max_char_count,
+                min_entropy,
+                max_entropy,
+                frequencies: frequencies.map(Arc::new),
+                shuffle_seed: seed,
+                ..Config::default()
+            },
+        };
+        crunch.config.validate().map_err(|err| to_py(err.into()))?;
+        Ok(crunch)
+    }
+
+    /// Number of words in the keyspace.
+    fn size(&self) -> PyResult<u64> {
+        calculate_size(&self.config).map_err(to_py)
+    }
+
+    /// The words from the `skip`th on, at most `limit` of them.
+    #[pyo3(signature = (skip = 0, limit = None))]
+    fn words(&self, skip: u64, limit: Option<u64>) -> PyWords {
+        PyWords {
+            words: Words::window(self.config.clone(), Window { skip, limit }),
+        }
+    }
+
+    fn __iter__(&self) -> PyWords {
+        self.words(0, None)
+    }
+
+    /// Index of the bytes `word` in the keyspace, or None if it is not in it.
+    fn rank(&self, word: &[u8]) -> PyResult<Option<u64>> {
+        rank(&self.config, word).map_err(to_py)
+    }
+
+    /// The word at `index` as bytes, or None past the end.
+    fn unrank(&self, py: Python<'_>, index: u64) -> PyResult<Option<Py<PyBytes>>> {
+        let word = unrank(&self.config, index).map_err(to_py)?;
+        Ok(word.map(|word| PyBytes::new_bound(py, &word).unbind()))
+    }
+}
+
+/// Iterator over the words of a [`PyCrunch`], as bytes.
+#[pyclass(name = "Words", module = "crunch_rs")]
+struct PyWords {
+    words: Words,
+}
+
+#[pymethods]
+impl PyWords {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<PyBytes>>> {
+        // Waiting for the generator thread need not hold the GIL.
+        let word = py.allow_threads(|| self.words.next());
+        match word {
+            Some(Ok(word)) => Ok(Some(PyBytes::new_bound(py, &word).unbind())),
+            Some(Err(err)) => Err(to_py(err)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Applies hashcat-style `rules`, then `case` ("simple" or "all") and
+/// `leet` variants, then `suffixes` to every word, as the `mutate`
+/// subcommand does.
+#[pyfunction]
+#[pyo3(signature = (words, rules = Vec::new(), case = None, leet = false, suffixes = Vec::new()))]
+fn mutate(
+    words: Vec<String>,
+    rules: Vec<String>,
+    case: Option<&str>,
+    leet: bool,
+    suffixes: Vec<String>,
+) -> PyResult<Vec<String>> {
+    let mutator = Mutator {
+        rules: rules
+            .iter()
+            .map(|rule| Rule::parse(rule))
+            .collect::<io::Result<_>>()
+            .map_err(to_py)?,
+        case: match case {
+            None => None,
+            Some("simple") => Some(CaseMode::Simple),
+            Some("all") => Some(CaseMode::All),
+            Some(mode) => return Err(PyValueError::new_err(format!("unknown case mode '{}'", mode))),
+        },
+        leet,
+        suffixes,
+        length: None,
+    };
+    Ok(words.iter().flat_map(|word| mutator.mutate(word)).collect())
+}
+
+#[pymodule]
+fn crunch_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCrunch>()?;
+    m.add_class::<PyWords>()?;
+    m.add_function(wrap_pyfunction!(mutate, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::charset::Charset;
+use crate::config::Config;
+use crate::filter::Filters;
+use crate::interleave::Interleaving;
+use crate::layout::{layouts, Layout};
+use crate::shuffle::keyspace_permutation;
+use std::io;
+
+/// Number of words in `layout`, or `None` if it does not fit in a `u64`.
+pub fn layout_len(layout: &[&Charset]) -> Option<u64> {
+    layout
+        .iter()
+        .try_fold(1u64, |total, charset| total.checked_mul(charset.len() as u64))
+}
+
+/// The symbol index at each position of word `index` of `layout`.
+///
+/// Words are numbered in mixed radix, the last position varying fastest,
+/// which is the order the generator writes them in.
+pub fn symbol_indices(layout: &[&Charset], index: u64) -> Option<Vec<usize>> {
+    let mut rest = index;
+    let mut indices = vec![0; layout.len()];
+    for (pos, charset) in layout.iter().enumerate().rev() {
+        let len = charset.len() as u64;
+        if len == 0 {
+            return None;
+        }
+        indices[pos] = (rest % len) as usize;
+        rest /= len;
+    }
+    // Anything left over means `index` is past the last word.
+    (rest == 0).then_some(indices)
+}
+
+/// Word `index` of `layout`, or `None` past its end.
+pub fn layout_unrank(layout: &[&Charset], index: u64) -> Option<Vec<u8>> {
+    let indices = symbol_indices(layout, index)?;
+    Some(
+        layout
+            .iter()
+            .zip(indices)
+            .flat_map(|(charset, idx)| charset.symbols()[idx].iter().copied())
+            .collect(),
+    )
+}
+
+/// Index of `word` within `layout`, or `None` if the layout cannot produce it.
+pub fn layout_rank(layout: &[&Charset], word: &[u8]) -> Option<u64> {
+    let (charset, rest) = match layout.split_first() {
+        Some(split) => split,
+        None => return word.is_empty().then_some(0),
+    };
+    let rest_len = layout_len(rest)?;
+    // Symbols may share a prefix, so each candidate for this position is tried in turn.
+    charset.symbols().iter().enumerate().find_map(|(idx, symbol)| {
+        let tail = word.strip_prefix(symbol.as_slice())?;
+        let tail_rank = layout_rank(rest, tail)?;
+        (idx as u64).checked_mul(rest_len)?.checked_add(tail_rank)
+    })
+}
+
+/// The layouts of `config`, provided every word of them is a candidate.
+///
+/// Filters drop words from the middle of a layout, so positions in a filtered
+/// keyspace can only be found by generating it.
+fn unfiltered_layouts(config: &Config) -> io::Result<Vec<Layout>> {
+    if Filters::from_config(config).is_active() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "word indices are only defined for unfiltered keyspaces (no --no-duplicates, --exclude-substr, \
+             --contains, --require, --max-char-count, entropy bounds or --not-in)",
+        ));
+    }
+    layouts(config)
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "keyspace has more than 2^64 words")
+}
+
+/// Position of `word` in the keyspace of `config`, counting from zero, or
+/// `None` if it is not a candidate. Shuffled and interleaved keyspaces count
+/// in the order they are written in.
+pub fn rank(config: &Config, word: &[u8]) -> io::Result<Option<u64>> {
+    let Some(index) = unshuffled_rank(config, word)? else {
+        return Ok(None);
+    };
+    match config.shuffle_seed {
+        Some(seed) => Ok(Some(keyspace_permutation(config, seed)?.1.invert(index))),
+        None => Ok(Some(index)),
+    }
+}
+
+/// Word `index` of the keyspace of `config`, or `None` past its end.
+pub fn unrank(config: &Config, index: u64) -> io::Result<Option<Vec<u8>>> {
+    match config.shuffle_seed {
+        Some(seed) => {
+            let (_, permutation) = keyspace_permutation(config, seed)?;
+            if index >= permutation.len() {
+                return Ok(None);
+            }
+            unshuffled_unrank(config, permutation.apply(index))
+        }
+        None => unshuffled_unrank(config, index),
+    }
+}
+
+fn unshuffled_rank(config: &Config, word: &[u8]) -> io::Result<Option<u64>> {
+    if let Some(batch) = config.interleave {
+        let layouts = unfiltered_layouts(config)?;
+        let interleaving = Interleaving::new(&layouts, batch);
+        for (which, layout) in layouts.iter().enumerate() {
+            let layout: Vec<&Charset> = layout.iter().collect();
+            if let Some(index) = layout_rank(&layout, word) {
+                return Ok(interleaving.position(which, index));
+            }
+        }
+        return Ok(None);
+    }
+    let mut offset = 0u64;
+    for layout in unfiltered_layouts(config)? {
+        let layout: Vec<&Charset> = layout.iter().collect();
+        if let Some(index) = layout_rank(&layout, word) {
+            return offset.checked_add(index).map(Some).ok_or_else(too_large);
+        }
+        let len = layout_len(&layout).ok_or_else(too_large)?;
+        offset = offset.checked_add(len).ok_or_else(too_large)?;
+    }
+    Ok(None)
+}
+
+fn unshuffled_unrank(config: &Config, index: u64) -> io::Result<Option<Vec<u8>>> {
+    if let Some(batch) = config.interleave {
+        let layouts = unfiltered_layouts(config)?;
+        let Some((which, index)) = Interleaving::new(&layouts, batch).locate(index) else {
+            return Ok(None);
+        };
+        let layout: Vec<&Charset> = layouts[which].iter().collect();
+        return Ok(layout_unrank(&layout, index));
+    }
+    let mut rest = index;
+    for layout in unfiltered_layouts(config)? {
+        let layout: Vec<&Charset> = layout.iter().collect();
+        let len = layout_len(&layout).ok_or_else(too_large)?;
+        if rest < len {
+            return Ok(layout_unrank(&layout, rest));
+        }
+        rest -= len;
+    }
+    Ok(None)
+}
@@ -0,0 +1,162 @@
+use crate::config::Config;
+use crate::generator::{generate_words_window, Window};
+use crate::layout::layouts;
+use crate::output::OutputBuffer;
+use crate::progress::Progress;
+use crate::size::{estimate, Estimate};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const RESPONSE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Most bytes read of a request line and its headers.
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+
+/// How long a read or write on a connection may block.
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections handled at once; more are turned away.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// Counts a connection as open until it is dropped.
+struct Open(Arc<AtomicUsize>);
+
+impl Drop for Open {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Serves the keyspace of `config` over HTTP, one thread per connection:
+///
+/// - `GET /words?skip=N&limit=M` streams words `N..N+M` (both optional);
+/// - `GET /info` returns the keyspace size as JSON.
+///
+/// Workers can split the keyspace between them by asking for disjoint ranges.
+/// Up to [`MAX_CONNECTIONS`] are served at once, and a client that sends an
+/// oversized request or stalls for half a minute is dropped.
+pub fn serve(listener: TcpListener, config: Config) -> io::Result<()> {
+    let total = estimate(&config)?;
+    // Words made of text are text; any other byte makes them binary.
+    let content_type = if layouts(&config)?
+        .iter()
+        .flatten()
+        .all(|charset| charset.symbols().iter().all(|symbol| std::str::from_utf8(symbol).is_ok()))
+    {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    };
+    let config = Arc::new(config);
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let timeouts = stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(IO_TIMEOUT)));
+        if timeouts.is_err() {
+            continue;
+        }
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            let _ = respond(&mut stream, "503 Service Unavailable", "text/plain", "too many connections\n");
+            continue;
+        }
+        let guard = Open(Arc::clone(&open));
+        let config = Arc::clone(&config);
+        thread::spawn(move || {
+            let _guard = guard;
+            // A worker hanging up mid-stream is its own business.
+            let _ = handle(stream, &config, total, content_type);
+        });
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, config: &Config, total: Estimate, content_type: &str) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_HEAD));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers carry nothing we use, but must be read before replying.
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            if reader.get_ref().limit() == 0 {
+                let body = format!("the request line and headers exceed {} bytes\n", MAX_REQUEST_HEAD);
+                respond(&mut stream, "431 Request Header Fields Too Large", "text/plain", &body)?;
+                // Closing with the rest unread would reset the connection
+                // before the client saw the reply, so a little more is read.
+                stream.shutdown(Shutdown::Write)?;
+                io::copy(&mut (&stream).take(MAX_REQUEST_HEAD * 8), &mut io::sink())?;
+                return Ok(());
+            }
+            break;
+        }
+        if header.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", "only GET is supported\n");
+    }
+    match path {
+        "/info" => {
+            let body = format!("{{\"words\":{},\"bytes\":{}}}\n", total.words, total.bytes);
+            respond(&mut stream, "200 OK", "application/json", &body)
+        }
+        "/words" => match parse_window(query) {
+            Ok(mut window) => {
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nConnection: close\r\n\r\n",
+                    content_type
+                )?;
+                let mut body = OutputBuffer::with_capacity(RESPONSE_BUFFER_SIZE, &mut stream);
+                generate_words_window(config, &mut body, &Progress::new(0), &mut window)?;
+                body.flush()
+            }
+            Err(err) => respond(&mut stream, "400 Bad Request", "text/plain", &format!("{}\n", err)),
+        },
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "try /words or /info\n"),
+    }
+}
+
+/// Reads `skip` and `limit` from a query string such as `skip=100&limit=50`.
+fn parse_window(query: &str) -> Result<Window, String> {
+    let mut window = Window::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a non-negative integer, got '{}'", key, value))
+        };
+        match key {
+            "skip" => window.skip = number()?,
+            "limit" => window.limit = Some(number()?),
+            _ => return Err(format!("unknown parameter '{}'", key)),
+        }
+    }
+    Ok(window)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
@@ -0,0 +1,182 @@
+use crate::charset::Charset;
+use crate::config::Config;
+use crate::filter::Filters;
+use crate::generator::{write_word, Window};
+use crate::layout::{layouts, room_after};
+use crate::progress::Progress;
+use crate::rank::{layout_len, symbol_indices};
+use std::io::{self, Write};
+
+const ROUNDS: usize = 4;
+
+/// A pseudo-random permutation of `0..len` chosen by `seed`, computed one
+/// index at a time so a shuffled keyspace never has to be held in memory.
+///
+/// A balanced Feistel network permutes the smallest even-width power of two
+/// covering `len`; indices it maps past the end are fed through again
+/// ("cycle walking") until they land inside the range.
+#[derive(Debug, Clone)]
+pub struct Permutation {
+    len: u64,
+    half_bits: u32,
+    keys: [u64; ROUNDS],
+}
+
+impl Permutation {
+    pub fn new(len: u64, seed: u64) -> Self {
+        let bits = (64 - len.saturating_sub(1).leading_zeros()).max(2);
+        let mut state = seed;
+        Permutation {
+            len,
+            half_bits: bits.div_ceil(2),
+            keys: std::array::from_fn(|_| {
+                state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                mix(state)
+            }),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Where `index` moves to.
+    pub fn apply(&self, index: u64) -> u64 {
+        let mut x = index;
+        loop {
+            x = self.encrypt(x);
+            if x < self.len {
+                return x;
+            }
+        }
+    }
+
+    /// The index that [`apply`](Self::apply) moves to `position`.
+    pub fn invert(&self, position: u64) -> u64 {
+        let mut x = position;
+        loop {
+            x = self.decrypt(x);
+            if x < self.len {
+                return x;
+            }
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        (1u64 << self.half_bits) - 1
+    }
+
+    fn round(&self, half: u64, key: u64) -> u64 {
+        mix(half ^ key) & self.mask()
+    }
+
+    fn encrypt(&self, x: u64) -> u64 {
+        let (mut left, mut right) = (x >> self.half_bits, x & self.mask());
+        for &key in &self.keys {
+            (left, right) = (right, left ^ self.round(right, key));
+        }
+        (left << self.half_bits) | right
+    }
+
+    fn decrypt(&self, x: u64) -> u64 {
+        let (mut left, mut right) = (x >> self.half_bits, x & self.mask());
+        for &key in self.keys.iter().rev() {
+            (left, right) = (right ^ self.round(left, key), left);
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+/// The splitmix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The permutation a shuffled run of `config` follows over its unfiltered
+/// keyspace, along with that keyspace's layouts.
+pub fn keyspace_permutation(config: &Config, seed: u64) -> io::Result<(Vec<Vec<Charset>>, Permutation)> {
+    let layouts = layouts(config)?;
+    let len = layouts.iter().try_fold(0u64, |total, layout| {
+        let layout: Vec<&Charset> = layout.iter().collect();
+        total.checked_add(layout_len(&layout)?)
+    });
+    let len = len.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "keyspace has more than 2^64 words"))?;
+    Ok((layouts, Permutation::new(len, seed)))
+}
+
+/// Writes the words of the keyspace inside `window` in the order given by
+/// `seed`: every candidate exactly once, visited through a [`Permutation`]
+/// of their indices.
+///
+/// Filters are checked on each word as it is drawn, so a filtered keyspace
+/// costs as much as the unfiltered one.
+pub fn generate_shuffled<W: Write + ?Sized>(
+    config: &Config,
+    seed: u64,
+    writer: &mut W,
+    progress: &Progress,
+    window: &mut Window,
+) -> io::Result<()> {
+    let filters = Filters::from_config(config);
+    let (layouts, permutation) = keyspace_permutation(config, seed)?;
+    let layouts: Vec<Vec<&Charset>> = layouts.iter().map(|layout| layout.iter().collect()).collect();
+    let rooms: Vec<Vec<usize>> = layouts.iter().map(|layout| room_after(layout)).collect();
+    // First index of each layout in the unshuffled order.
+    let mut starts = Vec::with_capacity(layouts.len());
+    let mut start = 0;
+    for layout in &layouts {
+        starts.push(start);
+        start += layout_len(layout).unwrap_or(0);
+    }
+
+    // Without filters every index is a word, so the window can start directly.
+    let mut from = 0;
+    if !filters.is_active() {
+        from = window.skip.min(permutation.len());
+        window.skip -= from;
+    }
+    let mut word = Vec::new();
+    for index in from..permutation.len() {
+        if window.is_exhausted() {
+            break;
+        }
+        let position = permutation.apply(index);
+        let which = starts.partition_point(|&start| start <= position) - 1;
+        let layout = &layouts[which];
+        let room = &rooms[which];
+        let indices = symbol_indices(layout, position - starts[which]).expect("position is within its layout");
+
+        word.clear();
+        let mut previous: Option<&[u8]> = None;
+        let mut allowed = true;
+        for (pos, (charset, idx)) in layout.iter().zip(indices).enumerate() {
+            let symbol = &charset.symbols()[idx];
+            if !filters.allows(&word, previous, symbol) || !filters.reachable(&word, symbol, room[pos]) {
+                allowed = false;
+                break;
+            }
+            word.extend_from_slice(symbol);
+            previous = Some(symbol);
+        }
+        if !allowed {
+            continue;
+        }
+        if !filters.accepts(&word) {
+            progress.reject(1);
+            continue;
+        }
+        if window.skip > 0 {
+            window.skip -= 1;
+            continue;
+        }
+        write_word(writer, &word, progress, config.line_ending)?;
+        window.limit = window.limit.map(|limit| limit - 1);
+    }
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use crate::charset::Charset;
+use crate::classes::{class_counts, CLASS_COUNT};
+use crate::config::{Config, LineEnding};
+use crate::filter::Filters;
+use crate::layout::{layouts, room_after};
+use std::collections::HashMap;
+use std::io;
+
+/// Number of words a layout yields once `filters` are applied.
+///
+/// Exact, except for `max_char_count` when [`counts_exactly`] says otherwise:
+/// combined with other filters its pass rate is then assumed independent of
+/// theirs, and with positions whose charsets partially overlap the cap is
+/// ignored, giving an upper bound.
+pub fn layout_size(layout: &[&Charset], filters: &Filters) -> u64 {
+    let Some(max) = filters.max_char_count else {
+        return prefix_filtered_size(layout, filters, None);
+    };
+
+    let others = Filters {
+        max_char_count: None,
+        ..filters.clone()
+    };
+    let groups = charset_groups(layout);
+    let disjoint = disjoint(&groups);
+    if disjoint && !others.is_active() {
+        return capped_product(&groups, max);
+    }
+    if let Some(cap) = Cap::fits(layout, max) {
+        return prefix_filtered_size(layout, &others, Some(&cap));
+    }
+
+    let base = prefix_filtered_size(layout, &others, None);
+    if !disjoint {
+        return base;
+    }
+    let raw = prefix_filtered_size(layout, &Filters::default(), None);
+    if raw == 0 {
+        return 0;
+    }
+    (base as f64 * capped_product(&groups, max) as f64 / raw as f64).round() as u64
+}
+
+/// Whether [`layout_size`] counts `layout` exactly under `filters`.
+pub fn counts_exactly(layout: &[&Charset], filters: &Filters) -> bool {
+    let Some(max) = filters.max_char_count else {
+        return true;
+    };
+    let others_active = Filters {
+        max_char_count: None,
+        ..filters.clone()
+    }
+    .is_active();
+    (disjoint(&charset_groups(layout)) && !others_active) || Cap::fits(layout, max).is_some()
+}
+
+/// The distinct charsets of a layout with the number of positions of each.
+fn charset_groups<'a>(layout: &[&'a Charset]) -> Vec<(&'a Charset, usize)> {
+    let mut groups: Vec<(&Charset, usize)> = Vec::new();
+    for &charset in layout {
+        match groups.iter_mut().find(|(group, _)| *group == charset) {
+            Some((_, positions)) => *positions += 1,
+            None => groups.push((charset, 1)),
+        }
+    }
+    groups
+}
+
+/// Whether no two distinct charsets share a symbol, so that each group of
+/// positions can be capped on its own.
+fn disjoint(groups: &[(&Charset, usize)]) -> bool {
+    groups.iter().enumerate().all(|(i, (a, _))| {
+        groups[i + 1..]
+            .iter()
+            .all(|(b, _)| a.symbols().iter().all(|symbol| !b.symbols().contains(symbol)))
+    })
+}
+
+fn capped_product(groups: &[(&Charset, usize)], max: usize) -> u64 {
+    groups.iter().fold(1u64, |total, (charset, positions)| {
+        total.saturating_mul(capped_count(charset.len(), *positions, max))
+    })
+}
+
+/// Most distinct symbol counts [`prefix_filtered_size`] tracks for the cap.
+const MAX_CAP_STATES: u128 = 20_000;
+
+/// `max_char_count` as [`prefix_filtered_size`] counts it: how often each
+/// symbol of the layout was placed.
+struct Cap {
+    max: usize,
+    /// Index of every symbol in the counts.
+    symbols: HashMap<Vec<u8>, usize>,
+}
+
+impl Cap {
+    /// The cap, if every symbol of `layout` is a single byte or every one a
+    /// single UTF-8 character, so that occurrences are placements, and the
+    /// counts a word can reach are few.
+    fn fits(layout: &[&Charset], max: usize) -> Option<Cap> {
+        let mut symbols = HashMap::new();
+        for charset in layout {
+            for symbol in charset.symbols() {
+                let next = symbols.len();
+                symbols.entry(symbol.clone()).or_insert(next);
+            }
+        }
+        let single_char = |symbol: &Vec<u8>| std::str::from_utf8(symbol).is_ok_and(|s| s.chars().count() == 1);
+        if !symbols.keys().all(|symbol| symbol.len() == 1) && !symbols.keys().all(single_char) {
+            return None;
+        }
+        // Count vectors with every entry at most `max` and at most
+        // `layout.len()` in total, one symbol at a time.
+        let mut vectors = vec![0u128; layout.len() + 1];
+        vectors[0] = 1;
+        for _ in 0..symbols.len() {
+            let mut next = vec![0u128; layout.len() + 1];
+            for (total, slot) in next.iter_mut().enumerate() {
+                for used in 0..=max.min(total) {
+                    *slot = slot.saturating_add(vectors[total - used]);
+                }
+            }
+            vectors = next;
+        }
+        let states = vectors.iter().fold(0u128, |sum, &count| sum.saturating_add(count));
+        (states <= MAX_CAP_STATES).then_some(Cap { max, symbols })
+    }
+}
+
+/// Words of `length` symbols over an alphabet of `symbols` in which no symbol
+/// is used more than `max` times: `length! [x^length] (sum_{i<=max} x^i/i!)^symbols`,
+/// evaluated as a DP that adds one symbol of the alphabet at a time.
+fn capped_count(symbols: usize, length: usize, max: usize) -> u64 {
+    let mut binomial = vec![vec![0u128; length + 1]; length + 1];
+    for n in 0..=length {
+        binomial[n][0] = 1;
+        for k in 1..=n {
+            binomial[n][k] = binomial[n - 1][k - 1].saturating_add(binomial[n - 1][k]);
+        }
+    }
+
+    // ways[l]: words of length l over the symbols considered so far.
+    let mut ways = vec![0u128; length + 1];
+    ways[0] = 1;
+    for _ in 0..symbols {
+        let mut next = vec![0u128; length + 1];
+        for (l, slot) in next.iter_mut().enumerate() {
+            for used in 0..=max.min(l) {
+                let placed = binomial[l][used].saturating_mul(ways[l - used]);
+                *slot = slot.saturating_add(placed);
+            }
+        }
+        ways = next;
+    }
+    ways[length].min(u64::MAX as u128) as u64
+}
+
+/// Exact size under the filters that only look at a bounded tail of the
+/// word, and under `cap` by counting each symbol's placements.
+fn prefix_filtered_size(layout: &[&Charset], filters: &Filters, cap: Option<&Cap>) -> u64 {
+    if !filters.is_active() && cap.is_none() {
+        return layout
+            .iter()
+            .fold(1u64, |total, charset| total.saturating_mul(charset.len() as u64));
+    }
+
+    // Count surviving prefixes grouped by everything the filters still look
+    // at: the last symbol, the tail that could grow into an excluded or
+    // required substring, which required substrings have been seen, and how
+    // many characters of each required class and, under a cap, how many of
+    // each symbol were placed.
+    type State = (Vec<u8>, Vec<u8>, Vec<bool>, [usize; CLASS_COUNT], Vec<usize>);
+    let caps: [usize; CLASS_COUNT] = std::array::from_fn(|class| filters.class_cap(class));
+    let mut states: HashMap<State, u64> = HashMap::new();
+    let start = (
+        Vec::new(),
+        Vec::new(),
+        vec![false; filters.required_substrings.len()],
+        [0; CLASS_COUNT],
+        vec![0; cap.map_or(0, |cap| cap.symbols.len())],
+    );
+    states.insert(start, 1);
+    let room = room_after(layout);
+    for (pos, charset) in layout.iter().enumerate() {
+        let remaining = room[pos];
+        let mut next = HashMap::new();
+        for ((previous, pending, found, classes, placed), count) in &states {
+            let previous = (pos > 0).then_some(previous.as_slice());
+            for symbol in charset.symbols() {
+                if !filters.allows(pending, previous, symbol) {
+                    continue;
+                }
+                let mut placed = placed.clone();
+                if let Some(cap) = cap {
+                    let times = &mut placed[cap.symbols[symbol]];
+                    if *times >= cap.max {
+                        continue;
+                    }
+                    *times += 1;
+                }
+                let mut classes = *classes;
+                for (class, extra) in class_counts(symbol).into_iter().enumerate() {
+                    classes[class] = (classes[class] + extra).min(caps[class]);
+                }
+                if !filters.classes_feasible(&classes, remaining) {
+                    continue;
+                }
+                let found = filters.found_required(found, pending, symbol);
+                let mut tail = pending.clone();
+                tail.extend_from_slice(symbol);
+                let pending = filters.pending_suffix(&tail).to_vec();
+                let last = if filters.no_duplicates {
+                    symbol.clone()
+                } else {
+                    Vec::new()
+                };
+                let entry = next.entry((last, pending, found, classes, placed)).or_insert(0u64);
+                *entry = entry.saturating_add(*count);
+            }
+        }
+        states = next;
+    }
+    states
+        .iter()
+        .filter(|((_, _, found, _, _), _)| found.iter().all(|&found| found))
+        .fold(0u64, |sum, (_, count)| sum.saturating_add(*count))
+}
+
+pub fn calculate_size(config: &Config) -> io::Result<u64> {
+    Ok(estimate(config)?.words)
+}
+
+/// Words and bytes a run is expected to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    pub words: u64,
+    pub bytes: u64,
+}
+
+impl Estimate {
+    /// For generators that cannot cheaply size their words: assumes the
+    /// average word is 7 bytes long.
+    pub fn approximate(words: u64, line_ending: LineEnding) -> Self {
+        let line = 7 + line_ending.as_bytes().len() as u64;
+        Estimate {
+            words,
+            bytes: words.saturating_mul(line),
+        }
+    }
+
+    /// What is left after the first `skipped` words, assuming they were of
+    /// average length.
+    pub fn remaining(self, skipped: u64) -> Self {
+        let words = self.words.saturating_sub(skipped);
+        let bytes = match self.words {
+            0 => 0,
+            total => (self.bytes as f64 * words as f64 / total as f64).round() as u64,
+        };
+        Estimate { words, bytes }
+    }
+}
+
+/// Bytes written for `words` words of `layout`, terminators included.
+///
+/// Exact when no filter applies; otherwise the surviving words are assumed
+/// to have the layout's average length.
+pub fn layout_bytes(layout: &[&Charset], words: u64, line_ending: LineEnding) -> u64 {
+    let average_word: f64 = layout
+        .iter()
+        .filter(|charset| !charset.is_empty())
+        .map(|charset| {
+            let total: usize = charset.symbols().iter().map(Vec::len).sum();
+            total as f64 / charset.len() as f64
+        })
+        .sum();
+    let line = average_word + line_ending.as_bytes().len() as f64;
+    (words as f64 * line).round() as u64
+}
+
+pub fn estimate(config: &Config) -> io::Result<Estimate> {
+    let filters = Filters::from_config(config);
+    let mut total = Estimate { words: 0, bytes: 0 };
+    for layout in layouts(config)? {
+        let layout: Vec<&Charset> = layout.iter().collect();
+        let words = layout_size(&layout, &filters);
+        total.words = total.words.saturating_add(words);
+        total.bytes = total
+            .bytes
+            .saturating_add(layout_bytes(&layout, words, config.line_ending));
+    }
+    Ok(total)
+}
+
+/// Whether [`estimate`] counts the words of `config` exactly; filters that
+/// judge whole words aside.
+pub fn estimate_is_exact(config: &Config) -> io::Result<bool> {
+    let filters = Filters::from_config(config);
+    Ok(layouts(config)?.iter().all(|layout| {
+        let layout: Vec<&Charset> = layout.iter().collect();
+        counts_exactly(&layout, &filters)
+    }))
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,191 @@
+use crate::config::{Config, LineEnding};
+use crate::generator::{generate_words_window, Window};
+use crate::progress::Progress;
+use std::io::{self, Write};
+use std::mem;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+
+/// Bytes of words handed over at a time.
+const BATCH_BYTES: usize = 64 * 1024;
+
+/// Batches in flight between the generator and the reader.
+const BATCHES_AHEAD: usize = 4;
+
+/// Bytes of the length in front of every word of a batch.
+const LENGTH_BYTES: usize = 4;
+
+/// The words of a keyspace as an iterator, for callers that pull words
+/// rather than give the generator a writer.
+///
+/// The generator runs on its own thread and hands words over in batches, so
+/// memory stays bounded however large the keyspace. Dropping the iterator
+/// stops the generator at its next batch. Words are framed by their length,
+/// so they may hold any byte, NUL included.
+pub struct Words {
+    batches: Receiver<io::Result<Vec<u8>>>,
+    batch: Vec<u8>,
+    offset: usize,
+}
+
+impl Words {
+    pub fn new(config: Config) -> Self {
+        Words::window(config, Window::default())
+    }
+
+    /// The words of `config` that fall inside `window`.
+    pub fn window(mut config: Config, mut window: Window) -> Self {
+        // Without a line ending the generator writes each word on its own.
+        config.line_ending = LineEnding::None;
+        let (sender, batches) = sync_channel(BATCHES_AHEAD);
+        thread::spawn(move || {
+            let mut writer = BatchWriter {
+                sender: sender.clone(),
+                batch: Vec::with_capacity(BATCH_BYTES),
+            };
+            let result = generate_words_window(&config, &mut writer, &Progress::unbounded(), &mut window)
+                .and_then(|()| writer.flush());
+            if let Err(err) = result {
+                // Nobody is listening any more when the iterator was dropped.
+                if err.kind() != io::ErrorKind::BrokenPipe {
+                    let _ = sender.send(Err(err));
+                }
+            }
+        });
+        Words {
+            batches,
+            batch: Vec::new(),
+            offset: 0,
+        }
+    }
+}
+
+impl Iterator for Words {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset == self.batch.len() {
+            match self.batches.recv() {
+                Ok(Ok(batch)) => {
+                    self.batch = batch;
+                    self.offset = 0;
+                }
+                Ok(Err(err)) => return Some(Err(err)),
+                Err(_) => return None,
+            }
+        }
+        let (len, rest) = self.batch[self.offset..].split_at(LENGTH_BYTES);
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let word = rest[..len].to_vec();
+        self.offset += LENGTH_BYTES + len;
+        Some(Ok(word))
+    }
+}
+
+/// Collects the words, one per write, into batches of length-prefixed words
+/// and sends each one on.
+struct BatchWriter {
+    sender: SyncSender<io::Result<Vec<u8>>>,
+    batch: Vec<u8>,
+}
+
+impl BatchWriter {
+    fn send(&mut self, batch: Vec<u8>) -> io::Result<()> {
+        self.sender
+            .send(Ok(batch))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the word iterator was dropped"))
+    }
+}
+
+impl Write for BatchWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = u32::try_from(buf.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "word longer than 4 GiB"))?;
+        self.batch.extend_from_slice(&len.to_le_bytes());
+        self.batch.extend_from_slice(buf);
+        if self.batch.len() >= BATCH_BYTES {
+            let batch = mem::replace(&mut self.batch, Vec::with_capacity(BATCH_BYTES));
+            self.send(batch)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let batch = mem::take(&mut self.batch);
+        self.send(batch)
+    }
+}
+
+/// Hands the words of `config` inside `window` to `chunk`, up to
+/// `chunk_words` at a time, until they run out or `chunk` returns false.
+/// Returns how many words were handed over.
+///
+/// Unlike [`Words`] this runs the generator on the calling thread, for hosts
+/// without threads such as WebAssembly in a browser. Words may hold any byte
+/// as for [`Words`].
+pub fn for_each_chunk(
+    config: &Config,
+    mut window: Window,
+    chunk_words: usize,
+    chunk: impl FnMut(Vec<Vec<u8>>) -> bool,
+) -> io::Result<u64> {
+    let mut config = config.clone();
+    config.line_ending = LineEnding::None;
+    let mut writer = ChunkWriter {
+        chunk,
+        words: Vec::with_capacity(chunk_words.max(1)),
+        chunk_words: chunk_words.max(1),
+        handed_over: 0,
+        stopped: false,
+    };
+    match generate_words_window(&config, &mut writer, &Progress::unbounded(), &mut window) {
+        Err(_) if writer.stopped => {}
+        result => {
+            result?;
+            writer.hand_over();
+        }
+    }
+    Ok(writer.handed_over)
+}
+
+/// Takes the generator's words, one per write, and hands them over in chunks.
+struct ChunkWriter<F: FnMut(Vec<Vec<u8>>) -> bool> {
+    chunk: F,
+    words: Vec<Vec<u8>>,
+    chunk_words: usize,
+    handed_over: u64,
+    /// Set once `chunk` asked for no more words.
+    stopped: bool,
+}
+
+impl<F: FnMut(Vec<Vec<u8>>) -> bool> ChunkWriter<F> {
+    fn hand_over(&mut self) {
+        if self.words.is_empty() {
+            return;
+        }
+        let words = mem::replace(&mut self.words, Vec::with_capacity(self.chunk_words));
+        self.handed_over += words.len() as u64;
+        self.stopped = !(self.chunk)(words);
+    }
+}
+
+impl<F: FnMut(Vec<Vec<u8>>) -> bool> Write for ChunkWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.words.push(buf.to_vec());
+        if self.words.len() == self.chunk_words {
+            self.hand_over();
+            if self.stopped {
+                // Not `Interrupted`, which `write_all` would retry.
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "no more words wanted"));
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+/// Largest count of numbers one spec may expand to.
+const MAX_NUMBERS: u64 = 1_000_000;
+
+/// Parses a comma-separated list of numbers and ranges such as
+/// `1990-2025` or `0-99,123`. A range whose start has leading zeros, such as
+/// `00-99`, pads every number to that width.
+pub fn parse_numbers(spec: &str) -> Result<Vec<String>, String> {
+    let mut numbers = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let (start, end) = (start.trim(), end.trim());
+        let number = |value: &str| {
+            if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(format!("invalid number '{}' in '{}'", value, part));
+            }
+            value.parse::<u64>().map_err(|_| format!("number '{}' is too large", value))
+        };
+        let (first, last) = (number(start)?, number(end)?);
+        if last < first {
+            return Err(format!("empty range '{}'", part));
+        }
+        if last - first >= MAX_NUMBERS || numbers.len() as u64 + (last - first) >= MAX_NUMBERS {
+            return Err(format!("'{}' has more than {} numbers", spec, MAX_NUMBERS));
+        }
+        let width = if start.len() > 1 && start.starts_with('0') { start.len() } else { 0 };
+        numbers.extend((first..=last).map(|n| format!("{:0width$}", n, width = width)));
+    }
+    if numbers.is_empty() {
+        return Err("no numbers given".to_string());
+    }
+    Ok(numbers)
+}
+
+/// Every suffix made of one of `values`, directly or after one of the
+/// characters of `separators`, without repeats.
+pub fn suffixes(values: &[String], separators: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    std::iter::once(String::new())
+        .chain(separators.chars().map(String::from))
+        .flat_map(|separator| values.iter().map(move |value| format!("{}{}", separator, value)))
+        .filter(|suffix| seen.insert(suffix.clone()))
+        .collect()
+}
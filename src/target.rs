@@ -0,0 +1,235 @@
+use crate::error::Error;
+use crate::mutate::{case_variants, leet_variants, CaseMode};
+use crate::patterns::Date;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// What is known about the person a wordlist targets, CUPP style.
+///
+/// Keys are the same in an answers file and in the interactive questions;
+/// everything is optional. Birthdays are `YYYY-MM-DD`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Target {
+    pub first_name: Option<String>,
+    pub surname: Option<String>,
+    pub nickname: Option<String>,
+    pub birthday: Option<String>,
+    pub partner_name: Option<String>,
+    pub partner_nickname: Option<String>,
+    pub partner_birthday: Option<String>,
+    pub child_name: Option<String>,
+    pub child_nickname: Option<String>,
+    pub child_birthday: Option<String>,
+    pub pet: Option<String>,
+    pub company: Option<String>,
+    /// Anything else worth trying: hobbies, teams, places, ...
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// The questions [`Target::ask`] asks, in order.
+const QUESTIONS: &[(&str, &str)] = &[
+    ("first-name", "First name"),
+    ("surname", "Surname"),
+    ("nickname", "Nickname"),
+    ("birthday", "Birthday (YYYY-MM-DD)"),
+    ("partner-name", "Partner's name"),
+    ("partner-nickname", "Partner's nickname"),
+    ("partner-birthday", "Partner's birthday (YYYY-MM-DD)"),
+    ("child-name", "Child's name"),
+    ("child-nickname", "Child's nickname"),
+    ("child-birthday", "Child's birthday (YYYY-MM-DD)"),
+    ("pet", "Pet's name"),
+    ("company", "Company"),
+    ("keywords", "Other keywords, comma separated"),
+];
+
+impl Target {
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let target: Target =
+            toml::from_str(contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        target.dates()?;
+        Ok(target)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| Error::input(path.display().to_string(), err))?;
+        Self::parse(&contents)
+            .map_err(|err| Error::config(format!("invalid answers file {}: {}", path.display(), err)).into())
+    }
+
+    /// Asks each question on `prompts` and reads the answer from `answers`;
+    /// a blank answer skips the question and a malformed birthday is asked again.
+    pub fn ask<R: BufRead, W: Write>(mut answers: R, prompts: &mut W) -> io::Result<Self> {
+        let mut target = Target::default();
+        for &(key, question) in QUESTIONS {
+            loop {
+                write!(prompts, "> {}: ", question)?;
+                prompts.flush()?;
+                let mut line = String::new();
+                if answers.read_line(&mut line)? == 0 {
+                    return Ok(target);
+                }
+                let answer = line.trim();
+                if key.ends_with("birthday") && !answer.is_empty() {
+                    if let Err(err) = Date::parse(answer) {
+                        writeln!(prompts, "  {}", err)?;
+                        continue;
+                    }
+                }
+                target.answer(key, answer);
+                break;
+            }
+        }
+        Ok(target)
+    }
+
+    fn answer(&mut self, key: &str, answer: &str) {
+        if key == "keywords" {
+            self.keywords = answer
+                .split(',')
+                .map(str::trim)
+                .filter(|keyword| !keyword.is_empty())
+                .map(str::to_string)
+                .collect();
+            return;
+        }
+        let field = match key {
+            "first-name" => &mut self.first_name,
+            "surname" => &mut self.surname,
+            "nickname" => &mut self.nickname,
+            "birthday" => &mut self.birthday,
+            "partner-name" => &mut self.partner_name,
+            "partner-nickname" => &mut self.partner_nickname,
+            "partner-birthday" => &mut self.partner_birthday,
+            "child-name" => &mut self.child_name,
+            "child-nickname" => &mut self.child_nickname,
+            "child-birthday" => &mut self.child_birthday,
+            "pet" => &mut self.pet,
+            "company" => &mut self.company,
+            _ => unreachable!("unknown question {}", key),
+        };
+        *field = Some(answer.to_string()).filter(|answer| !answer.is_empty());
+    }
+
+    /// The names and keywords, lowercased with spaces removed.
+    fn bases(&self) -> Vec<String> {
+        let names = [
+            &self.first_name,
+            &self.surname,
+            &self.nickname,
+            &self.partner_name,
+            &self.partner_nickname,
+            &self.child_name,
+            &self.child_nickname,
+            &self.pet,
+            &self.company,
+        ];
+        let mut bases = Vec::new();
+        for name in names.into_iter().flatten().chain(&self.keywords) {
+            let base: String = name.split_whitespace().collect::<String>().to_lowercase();
+            if !base.is_empty() && !bases.contains(&base) {
+                bases.push(base);
+            }
+        }
+        bases
+    }
+
+    fn dates(&self) -> io::Result<Vec<Date>> {
+        [&self.birthday, &self.partner_birthday, &self.child_birthday]
+            .into_iter()
+            .flatten()
+            .map(|date| Date::parse(date))
+            .collect()
+    }
+}
+
+/// Renderings of a birthday appended to words and tried on their own.
+const DATE_FORMATS: &[&str] = &[
+    "YYYY", "YY", "DD", "MM", "D", "M", "DDMM", "MMDD", "DM", "MD", "DDMMYY", "MMDDYY", "DDMMYYYY",
+    "MMDDYYYY", "YYYYMMDD",
+];
+
+/// How [`wordlist`] combines the answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetRules {
+    /// Years appended to every word.
+    pub years: RangeInclusive<u32>,
+    /// Numbers from 0 up to this are appended to every word.
+    pub numbers: u32,
+    /// Characters appended to every word, and after appended dates and years.
+    pub specials: String,
+    /// Also use every leetspeak spelling of the single names and keywords.
+    pub leet: bool,
+    /// Length bounds, in characters, of the words kept.
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+/// The targeted wordlist for `target`, without duplicates.
+///
+/// Names and keywords, alone and joined in pairs, are tried lowercase,
+/// uppercase and capitalized, then followed by nothing, by each birthday
+/// rendering, year and number, and by each special character on its own or
+/// after a birthday or year. Birthday renderings are also tried alone.
+pub fn wordlist(target: &Target, rules: &TargetRules) -> io::Result<Vec<String>> {
+    let bases = target.bases();
+    let mut dates: Vec<String> = Vec::new();
+    for date in target.dates()? {
+        for format in DATE_FORMATS {
+            let rendered = date.format(format);
+            if !dates.contains(&rendered) {
+                dates.push(rendered);
+            }
+        }
+    }
+
+    let mut stems: Vec<String> = Vec::new();
+    for base in &bases {
+        stems.extend(case_variants(base, CaseMode::Simple));
+    }
+    for first in &bases {
+        for second in bases.iter().filter(|second| *second != first) {
+            // Both the joined word's case variants (Smithjohn) and the parts' (SmithJohn).
+            stems.extend(case_variants(&format!("{}{}", first, second), CaseMode::Simple));
+            let parts = case_variants(first, CaseMode::Simple)
+                .into_iter()
+                .zip(case_variants(second, CaseMode::Simple));
+            stems.extend(parts.map(|(first, second)| first + &second));
+        }
+    }
+    if rules.leet {
+        stems.extend(bases.iter().flat_map(|base| leet_variants(base)).skip(1));
+    }
+
+    let specials: Vec<String> = rules.specials.chars().map(String::from).collect();
+    let mut suffixes = vec![String::new()];
+    let dated: Vec<String> = dates.iter().cloned().chain(rules.years.clone().map(|year| year.to_string())).collect();
+    suffixes.extend(dated.iter().cloned());
+    suffixes.extend((0..=rules.numbers).map(|number| number.to_string()));
+    suffixes.extend(specials.iter().cloned());
+    suffixes.extend(dated.iter().flat_map(|date| specials.iter().map(move |special| format!("{}{}", date, special))));
+
+    let mut seen = HashSet::new();
+    let mut words = Vec::new();
+    let mut keep = |word: String| {
+        let len = word.chars().count();
+        if (rules.min_len..=rules.max_len).contains(&len) && seen.insert(word.clone()) {
+            words.push(word);
+        }
+    };
+    for stem in &stems {
+        for suffix in &suffixes {
+            keep(format!("{}{}", stem, suffix));
+        }
+    }
+    for date in dates {
+        keep(date);
+    }
+    Ok(words)
+}
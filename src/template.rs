@@ -0,0 +1,102 @@
+use crate::charset::{decode_escape, Charset};
+use std::collections::BTreeMap;
+use std::io;
+
+/// Most optional groups a template may have; each one doubles the layouts.
+pub const MAX_OPTIONAL_GROUPS: usize = 12;
+
+/// Expands a template into the layouts it stands for, one charset per
+/// output position.
+///
+/// `@` draws from `charset`, `%` from the digits, `?x` from the user-defined
+/// placeholder `x`, `*` from the lines of `words` as whole symbols, `??`,
+/// `?[`, `?]` and `?*` are literal `?`, `[`, `]` and `*`, escapes
+/// such as `\t` and `\xff` are literal bytes (see
+/// [`unescape`](crate::charset::unescape)), and any other character is
+/// emitted literally. Brackets make their contents
+/// optional: `pass[%][%]` yields a layout for `pass`, one for `pass%` and one
+/// for `pass%%`, shortest first. Identical layouts are only kept once.
+pub fn parse_template(
+    template: &str,
+    charset: &Charset,
+    placeholders: &BTreeMap<char, String>,
+    words: &[Vec<u8>],
+) -> io::Result<Vec<Vec<Charset>>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    // Each position with the optional group it belongs to, if any.
+    let mut positions: Vec<(Charset, Option<usize>)> = Vec::new();
+    let mut groups = 0;
+    let mut open = None;
+    let mut wordlist = None;
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        let charset = match c {
+            '[' if open.is_some() => return Err(invalid("optional groups cannot be nested".to_string())),
+            '[' => {
+                open = Some(positions.len());
+                continue;
+            }
+            ']' => match open.take() {
+                Some(start) if start == positions.len() => {
+                    return Err(invalid("template has an empty optional group []".to_string()))
+                }
+                Some(_) => {
+                    groups += 1;
+                    continue;
+                }
+                None => return Err(invalid("template has an unmatched ']' (use ?] for a literal ']')".to_string())),
+            },
+            '@' => charset.clone(),
+            '%' => Charset::digits(),
+            '*' if words.is_empty() => {
+                return Err(invalid(
+                    "template uses * but there are no --wordlist words (use ?* for a literal '*')".to_string(),
+                ))
+            }
+            '*' => wordlist
+                .get_or_insert_with(|| Charset::from_symbols(words.to_vec()))
+                .clone(),
+            '?' => match chars.next() {
+                Some(literal @ ('?' | '[' | ']' | '*')) => Charset::literal(literal.to_string().as_bytes()),
+                Some(key) => match placeholders.get(&key) {
+                    Some(custom) => Charset::parse(custom)?,
+                    None => {
+                        return Err(invalid(format!(
+                            "template uses undefined placeholder ?{} (define it with --set '{}=...')",
+                            key, key
+                        )))
+                    }
+                },
+                None => return Err(invalid("template ends with a dangling '?' (use ?? for a literal '?')".to_string())),
+            },
+            '\\' => Charset::literal(&decode_escape(&mut chars)?),
+            c => Charset::literal(c.to_string().as_bytes()),
+        };
+        positions.push((charset, open.map(|_| groups)));
+    }
+    if open.is_some() {
+        return Err(invalid("template has an unclosed '[' (use ?[ for a literal '[')".to_string()));
+    }
+    if groups > MAX_OPTIONAL_GROUPS {
+        return Err(invalid(format!(
+            "template has {} optional groups; at most {} are supported",
+            groups, MAX_OPTIONAL_GROUPS
+        )));
+    }
+
+    // Every choice of present groups, fewest first.
+    let mut choices: Vec<u32> = (0..1u32 << groups).collect();
+    choices.sort_by_key(|present| present.count_ones());
+    let mut layouts: Vec<Vec<Charset>> = Vec::new();
+    for present in choices {
+        let layout: Vec<Charset> = positions
+            .iter()
+            .filter(|(_, group)| group.is_none_or(|group| present & (1 << group) != 0))
+            .map(|(charset, _)| charset.clone())
+            .collect();
+        if !layouts.contains(&layout) {
+            layouts.push(layout);
+        }
+    }
+    Ok(layouts)
+}
@@ -0,0 +1,211 @@
+use crate::config::LineEnding;
+use crate::error::Error;
+use crate::output::{shell, Framing};
+use std::io::{self, Read, Write};
+use std::process::Stdio;
+use std::thread;
+
+/// Words `--transform-cmd` hands its program at a time.
+pub const DEFAULT_TRANSFORM_BATCH: usize = 10_000;
+
+/// What becomes of a word after a [`Transform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// Write the word as it now is.
+    Keep,
+    /// Leave it out.
+    Skip,
+}
+
+/// Custom mangling of every candidate on its way to the output.
+pub trait Transform {
+    /// Rewrites `word`, which has no terminator, in place and says whether it
+    /// is written.
+    fn transform(&mut self, word: &mut Vec<u8>) -> Emit;
+}
+
+impl<F: FnMut(&mut Vec<u8>) -> Emit> Transform for F {
+    fn transform(&mut self, word: &mut Vec<u8>) -> Emit {
+        self(word)
+    }
+}
+
+/// Words and bytes, terminators included, taken in and passed on by a
+/// transforming writer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rewritten {
+    pub words_in: u64,
+    pub bytes_in: u64,
+    pub words_out: u64,
+    pub bytes_out: u64,
+}
+
+/// Passes every word written through it through a [`Transform`].
+///
+/// Words are told apart by the line ending, so one is required. A word is
+/// held back until its terminator arrives.
+pub struct Transformed<W: Write, T: Transform> {
+    inner: W,
+    transform: T,
+    framing: Framing,
+    word: Vec<u8>,
+    /// The words of the current write that are passed on.
+    kept: Vec<u8>,
+    counts: Rewritten,
+}
+
+impl<W: Write, T: Transform> Transformed<W, T> {
+    pub fn new(inner: W, transform: T, line_ending: LineEnding) -> io::Result<Self> {
+        Ok(Transformed {
+            inner,
+            transform,
+            framing: Framing::required(line_ending, "a transform")?,
+            word: Vec::new(),
+            kept: Vec::new(),
+            counts: Rewritten::default(),
+        })
+    }
+
+    pub fn counts(&self) -> Rewritten {
+        self.counts
+    }
+}
+
+impl<W: Write, T: Transform> Write for Transformed<W, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let terminator = self.framing.terminator();
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.counts.words_in += 1;
+            self.counts.bytes_in += line.len() as u64;
+            self.word.clear();
+            self.word.extend_from_slice(self.framing.word(line));
+            if self.transform.transform(&mut self.word) == Emit::Keep {
+                self.kept.extend_from_slice(&self.word);
+                self.kept.extend_from_slice(terminator);
+                self.counts.words_out += 1;
+                self.counts.bytes_out += (self.word.len() + terminator.len()) as u64;
+            }
+        }
+        if !self.kept.is_empty() {
+            self.inner.write_all(&self.kept)?;
+            self.kept.clear();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Pipes the words written through it through an external program, for
+/// `--transform-cmd`.
+///
+/// Every `batch` words the program is started through the platform shell
+/// and given them on stdin, one per line in the run's line ending; the lines
+/// it prints replace them, however many there are. A word is held back
+/// until its terminator arrives, and a short last batch until a flush.
+pub struct TransformCommand<W: Write> {
+    inner: W,
+    command: String,
+    batch: usize,
+    framing: Framing,
+    words: Vec<u8>,
+    queued: usize,
+    output: Vec<u8>,
+    counts: Rewritten,
+}
+
+impl<W: Write> TransformCommand<W> {
+    pub fn new(inner: W, command: &str, batch: usize, line_ending: LineEnding) -> io::Result<Self> {
+        Ok(TransformCommand {
+            inner,
+            command: command.to_string(),
+            batch: batch.max(1),
+            framing: Framing::required(line_ending, "--transform-cmd")?,
+            words: Vec::new(),
+            queued: 0,
+            output: Vec::new(),
+            counts: Rewritten::default(),
+        })
+    }
+
+    pub fn counts(&self) -> Rewritten {
+        self.counts
+    }
+
+    /// Runs the program over the queued words and writes what it prints.
+    fn run(&mut self) -> io::Result<()> {
+        if self.queued == 0 {
+            return Ok(());
+        }
+        let fail = |err: io::Error| -> io::Error { Error::output(format!("to '{}'", self.command), err).into() };
+        let mut child = shell(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(fail)?;
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let words = &self.words;
+        self.output.clear();
+        // Fed from another thread, so a program that prints as it reads
+        // cannot fill its stdout pipe while we are still writing.
+        let (fed, read) = thread::scope(|scope| {
+            let feeder = scope.spawn(move || stdin.write_all(words));
+            let read = stdout.read_to_end(&mut self.output);
+            (feeder.join().unwrap(), read)
+        });
+        let status = child.wait().map_err(fail)?;
+        read.map_err(fail)?;
+        // A program may stop reading once it has printed all it wants to.
+        match fed {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(fail(err)),
+            _ => {}
+        }
+        if !status.success() {
+            return Err(io::Error::other(format!("'{}' exited with {}", self.command, status)));
+        }
+        self.words.clear();
+        self.queued = 0;
+
+        let terminator = self.framing.terminator();
+        let (delimiter, before) = terminator.split_last().unwrap();
+        let mut rewritten = Vec::with_capacity(self.output.len());
+        let mut lines = self.output.split(|byte| byte == delimiter).peekable();
+        while let Some(line) = lines.next() {
+            // Whatever follows the last terminator is a word only if it is not empty.
+            if lines.peek().is_none() && line.is_empty() {
+                break;
+            }
+            let word = line.strip_suffix(before).unwrap_or(line);
+            rewritten.extend_from_slice(word);
+            rewritten.extend_from_slice(terminator);
+            self.counts.words_out += 1;
+            self.counts.bytes_out += (word.len() + terminator.len()) as u64;
+        }
+        self.inner.write_all(&rewritten)
+    }
+}
+
+impl<W: Write> Write for TransformCommand<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let words = self.framing.complete(buf);
+        for line in self.framing.lines(&words) {
+            self.counts.words_in += 1;
+            self.counts.bytes_in += line.len() as u64;
+            self.words.extend_from_slice(line);
+            self.queued += 1;
+            if self.queued >= self.batch {
+                self.run()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.run()?;
+        self.inner.flush()
+    }
+}
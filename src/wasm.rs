@@ -0,0 +1,154 @@
+//! WebAssembly bindings, built with `--features wasm`, e.g. through
+//! `wasm-pack build --target web -- --features wasm`.
+//!
+//! ```js
+//! import init, { Crunch } from "./pkg/crunch_rs.js";
+//! await init();
+//! const crunch = new Crunch(1, 4, "abc123", { contains: ["1"] });
+//! console.log(crunch.size());
+//! // Every word, 1000 at a time; returning false stops early.
+//! // Words are Uint8Arrays, as charsets need not be text.
+//! crunch.generate((words) => { show(words); return true; }, 1000);
+//! // Or one chunk per call, leaving the page responsive in between.
+//! const next = crunch.chunk(5000n, 1000);
+//! ```
+
+use crate::config::Config;
+use crate::generator::Window;
+use crate::rank::{rank, unrank};
+use crate::size::calculate_size;
+use crate::stream::for_each_chunk;
+use js_sys::{Array, Function, Reflect, Uint8Array};
+use std::io;
+use wasm_bindgen::prelude::*;
+
+fn to_js(err: io::Error) -> JsValue {
+    JsError::new(&err.to_string()).into()
+}
+
+fn invalid(message: String) -> JsValue {
+    JsError::new(&message).into()
+}
+
+/// The value of `key` in the options object, if it is set.
+fn option(options: &JsValue, key: &str) -> Result<Option<JsValue>, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(None);
+    }
+    let value = Reflect::get(options, &JsValue::from_str(key))?;
+    Ok((!value.is_undefined() && !value.is_null()).then_some(value))
+}
+
+fn string_option(options: &JsValue, key: &str) -> Result<Option<String>, JsValue> {
+    option(options, key)?
+        .map(|value| value.as_string().ok_or_else(|| invalid(format!("{} must be a string", key))))
+        .transpose()
+}
+
+fn strings_option(options: &JsValue, key: &str) -> Result<Vec<String>, JsValue> {
+    let Some(value) = option(options, key)? else {
+        return Ok(Vec::new());
+    };
+    if !Array::is_array(&value) {
+        return Err(invalid(format!("{} must be an array of strings", key)));
+    }
+    Array::from(&value)
+        .iter()
+        .map(|item| item.as_string().ok_or_else(|| invalid(format!("{} must be an array of strings", key))))
+        .collect()
+}
+
+fn words_to_js(words: Vec<Vec<u8>>) -> Array {
+    words.iter().map(|word| Uint8Array::from(&word[..])).collect()
+}
+
+/// A keyspace. `options` may set `template`, `excludeChars`,
+/// `excludeSubstrings`, `startsWith`, `endsWith`, `contains` and
+/// `noDuplicates`, as the command line's options do.
+#[wasm_bindgen]
+pub struct Crunch {
+    config: Config,
+}
+
+#[wasm_bindgen]
+impl Crunch {
+    #[wasm_bindgen(constructor)]
+    pub fn new(min_len: usize, max_len: usize, charset: &str, options: JsValue) -> Result<Crunch, JsValue> {
+        let no_duplicates = match option(&options, "noDuplicates")? {
+            Some(value) => value
+                .as_bool()
+                .ok_or_else(|| invalid("noDuplicates must be a boolean".to_string()))?,
+            None => false,
+        };
+        let config = Config {
+            min_len,
+            max_len,
+            charset: charset.to_string(),
+            template: string_option(&options, "template")?,
+            no_duplicates,
+            exclude_chars: string_option(&options, "excludeChars")?.unwrap_or_default(),
+            exclude_substrings: strings_option(&options, "excludeSubstrings")?,
+            starts_with: string_option(&options, "startsWith")?,
+            ends_with: string_option(&options, "endsWith")?,
+            contains: strings_option(&options, "contains")?,
+            ..Config::default()
+        };
+        config.validate().map_err(|err| to_js(err.into()))?;
+        Ok(Crunch { config })
+    }
+
+    /// Number of words in the keyspace, as a BigInt.
+    pub fn size(&self) -> Result<u64, JsValue> {
+        calculate_size(&self.config).map_err(to_js)
+    }
+
+    /// Calls `callback` with arrays of up to `chunk_size` words until the
+    /// keyspace is done or `callback` returns `false`. Returns the number of
+    /// words handed over; an exception thrown by `callback` is passed on.
+    pub fn generate(&self, callback: &Function, chunk_size: usize) -> Result<u64, JsValue> {
+        let mut thrown = None;
+        let words = for_each_chunk(&self.config, Window::default(), chunk_size, |words| {
+            match callback.call1(&JsValue::NULL, &words_to_js(words)) {
+                Ok(wanted) => wanted.as_bool() != Some(false),
+                Err(err) => {
+                    thrown = Some(err);
+                    false
+                }
+            }
+        })
+        .map_err(to_js)?;
+        match thrown {
+            Some(err) => Err(err),
+            None => Ok(words),
+        }
+    }
+
+    /// Up to `count` words starting at the `skip`th, for callers that pull
+    /// a chunk at a time. Skipping is immediate without whole-word filters.
+    pub fn chunk(&self, skip: u64, count: usize) -> Result<Array, JsValue> {
+        let window = Window {
+            skip,
+            limit: Some(count as u64),
+        };
+        let chunk = Array::new();
+        for_each_chunk(&self.config, window, count, |words| {
+            for word in words_to_js(words) {
+                chunk.push(&word);
+            }
+            true
+        })
+        .map_err(to_js)?;
+        Ok(chunk)
+    }
+
+    /// Index of the `Uint8Array` `word` in the keyspace, or `undefined` if
+    /// it is not in it.
+    pub fn rank(&self, word: &[u8]) -> Result<Option<u64>, JsValue> {
+        rank(&self.config, word).map_err(to_js)
+    }
+
+    /// The word at `index` as a `Uint8Array`, or `undefined` past the end.
+    pub fn unrank(&self, index: u64) -> Result<Option<Vec<u8>>, JsValue> {
+        unrank(&self.config, index).map_err(to_js)
+    }
+}
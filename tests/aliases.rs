@@ -0,0 +1,32 @@
+use crunch_rs::charset::{aliases, resolve_alias};
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+use std::collections::BTreeSet;
+
+#[test]
+fn crunch_charset_names_resolve() {
+    assert_eq!(resolve_alias("numeric"), "0123456789");
+    assert_eq!(resolve_alias("symbols14"), "!@#$%^&*()-_+=");
+    assert_eq!(resolve_alias("lalpha-space"), "abcdefghijklmnopqrstuvwxyz ");
+    assert_eq!(resolve_alias("mixalpha-numeric-all").chars().count(), 26 + 26 + 10 + 32);
+    assert_eq!(resolve_alias("ualpha-sv"), "ABCDEFGHIJKLMNOPQRSTUVWXYZÅÄÖ");
+    // Anything else is still a literal list of characters.
+    assert_eq!(resolve_alias("abc"), "abc");
+}
+
+#[test]
+fn alias_names_are_unique() {
+    let names: BTreeSet<String> = aliases().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names.len(), aliases().len());
+}
+
+#[test]
+fn keyspace_accepts_a_named_charset() {
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "hex-lower".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(calculate_size(&config).unwrap(), 256);
+}
@@ -0,0 +1,35 @@
+use crunch_rs::analyze::{composition, mask, Analysis};
+
+#[test]
+fn masks_use_hashcat_classes() {
+    assert_eq!(mask("Pass12!"), "?u?l?l?l?d?d?s");
+    assert_eq!(mask("a b"), "?l?s?l");
+    assert_eq!(mask("çé1"), "çé?d");
+    assert_eq!(mask(""), "");
+}
+
+#[test]
+fn compositions_follow_pack_names() {
+    assert_eq!(composition("password"), "loweralpha");
+    assert_eq!(composition("123456"), "numeric");
+    assert_eq!(composition("Password1"), "mixedalphanum");
+    assert_eq!(composition("pass!1"), "loweralphaspecialnum");
+    assert_eq!(composition("!!"), "special");
+    assert_eq!(composition(""), "empty");
+}
+
+#[test]
+fn top_masks_stop_once_coverage_is_reached() {
+    let mut analysis = Analysis::default();
+    for word in ["aaa", "bbb", "ccc", "ddd", "11", "22", "33", "A1", "B2", "x!"] {
+        analysis.add(word);
+    }
+    assert_eq!(analysis.words, 10);
+    assert_eq!(analysis.lengths.get(&2), Some(&6));
+
+    assert_eq!(analysis.top_masks(40.0, None), vec![("?l?l?l", 4)]);
+    assert_eq!(analysis.top_masks(50.0, None), vec![("?l?l?l", 4), ("?d?d", 3)]);
+    assert_eq!(analysis.top_masks(100.0, None).len(), 4);
+    assert_eq!(analysis.top_masks(100.0, Some(2)).len(), 2);
+    assert_eq!(analysis.top_compositions()[0], ("loweralpha", 4));
+}
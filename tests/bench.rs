@@ -0,0 +1,32 @@
+use crunch_rs::bench::{run_bench, BenchOutput};
+use crunch_rs::charset::Charset;
+use crunch_rs::compress::Compression;
+use std::time::Duration;
+
+#[test]
+fn bench_restarts_small_keyspaces_until_the_deadline() {
+    let charset = Charset::from_chars("ab");
+    let output = BenchOutput::Buffered { buffer_size: 64 };
+    let result = run_bench(&[&charset, &charset], output, Duration::from_millis(50)).unwrap();
+    assert!(result.words > 4);
+    assert_eq!(result.bytes, result.words * 3);
+    assert!(result.elapsed >= Duration::from_millis(50));
+}
+
+#[test]
+fn compressed_benches_report_the_threads_that_ran() {
+    let charset = Charset::from_chars("abc");
+    let output = BenchOutput::Compressed {
+        compression: Compression::Gzip,
+        threads: 0,
+    };
+    let result = run_bench(&[&charset; 3], output, Duration::from_millis(50)).unwrap();
+    assert!(result.words > 27);
+    assert_eq!(
+        result.output,
+        BenchOutput::Compressed {
+            compression: Compression::Gzip,
+            threads: 1
+        }
+    );
+}
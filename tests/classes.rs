@@ -0,0 +1,83 @@
+use crunch_rs::charset::Charset;
+use crunch_rs::classes::{parse_requirements, CharClass, ClassRequirement};
+use crunch_rs::filter::Filters;
+use crunch_rs::generator::generate_all_combinations;
+use crunch_rs::progress::Progress;
+use crunch_rs::size::calculate_size;
+use crunch_rs::{Config, LineEnding};
+
+mod common;
+
+use common::words;
+
+#[test]
+fn requirement_specs_parse() {
+    let parsed = parse_requirements("digit:2, symbol:1+,upper:1-3").unwrap();
+    assert_eq!(
+        parsed,
+        [
+            ClassRequirement { class: CharClass::Digit, min: 2, max: Some(2) },
+            ClassRequirement { class: CharClass::Symbol, min: 1, max: None },
+            ClassRequirement { class: CharClass::Upper, min: 1, max: Some(3) },
+        ]
+    );
+    assert!(parse_requirements("digits:2").is_err());
+    assert!(parse_requirements("digit").is_err());
+    assert!(parse_requirements("digit:3-1").is_err());
+}
+
+#[test]
+fn class_requirements_are_pruned_and_counted_exactly() {
+    let config = Config {
+        min_len: 1,
+        max_len: 5,
+        charset: "aB1!".to_string(),
+        require: parse_requirements("digit:2,symbol:1+,upper:0-1").unwrap(),
+        ..Default::default()
+    };
+    let required = words(&config);
+    let count = |word: &str, f: fn(&char) -> bool| word.chars().filter(f).count();
+    assert!(!required.is_empty());
+    for word in &required {
+        assert_eq!(count(word, char::is_ascii_digit), 2);
+        assert!(count(word, |c| *c == '!') >= 1);
+        assert!(count(word, char::is_ascii_uppercase) <= 1);
+    }
+    let expected = words(&Config { require: Vec::new(), ..config.clone() })
+        .iter()
+        .filter(|word| {
+            count(word, char::is_ascii_digit) == 2
+                && count(word, |c| *c == '!') >= 1
+                && count(word, char::is_ascii_uppercase) <= 1
+        })
+        .count();
+    assert_eq!(required.len(), expected);
+    assert_eq!(calculate_size(&config).unwrap(), expected as u64);
+}
+
+#[test]
+fn class_requirements_are_counted_with_max_char_count() {
+    let config = Config {
+        min_len: 2,
+        max_len: 4,
+        charset: "aB1!".to_string(),
+        require: parse_requirements("upper:1+,digit:1").unwrap(),
+        max_char_count: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(words(&config).len(), 38);
+    assert_eq!(calculate_size(&config).unwrap(), 38);
+}
+
+#[test]
+fn impossible_requirements_prune_everything() {
+    let filters = Filters {
+        classes: parse_requirements("digit:3").unwrap(),
+        ..Default::default()
+    };
+    let progress = Progress::new(0);
+    let mut out = Vec::new();
+    generate_all_combinations(2, &Charset::from_chars("0123456789"), &mut out, &progress, &filters, LineEnding::Lf)
+        .unwrap();
+    assert!(out.is_empty());
+}
@@ -0,0 +1,29 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use crunch_rs::generator::generate_words;
+use crunch_rs::progress::Progress;
+use crunch_rs::Config;
+
+/// Everything the generator writes for `config`.
+pub fn output(config: &Config) -> Vec<u8> {
+    let mut out = Vec::new();
+    generate_words(config, &mut out, &Progress::new(0)).unwrap();
+    out
+}
+
+/// The lines of the output, which must be UTF-8.
+pub fn words(config: &Config) -> Vec<String> {
+    String::from_utf8(output(config)).unwrap().lines().map(str::to_string).collect()
+}
+
+/// The words of the output as bytes, leaving out empty ones.
+pub fn byte_words(config: &Config) -> Vec<Vec<u8>> {
+    output(config)
+        .split(|&byte| byte == b'\n')
+        .filter(|word| !word.is_empty())
+        .map(<[u8]>::to_vec)
+        .collect()
+}
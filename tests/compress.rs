@@ -0,0 +1,117 @@
+use crunch_rs::compress::{extract, Compression, ParallelEncoder, SeekIndex};
+use crunch_rs::{Config, LineEnding};
+use flate2::read::MultiGzDecoder;
+use std::cell::RefCell;
+use std::io::{self, Cursor, Read, Write};
+use std::rc::Rc;
+
+mod common;
+
+fn words() -> Vec<u8> {
+    let config = Config {
+        min_len: 1,
+        max_len: 4,
+        charset: "abcdef".to_string(),
+        ..Config::default()
+    };
+    common::output(&config)
+}
+
+fn decompress(compression: Compression, data: &[u8]) -> Vec<u8> {
+    match compression {
+        Compression::Gzip => {
+            let mut out = Vec::new();
+            MultiGzDecoder::new(data).read_to_end(&mut out).unwrap();
+            out
+        }
+        Compression::Zstd => zstd::decode_all(data).unwrap(),
+    }
+}
+
+#[test]
+fn blocks_come_back_in_order() {
+    let words = words();
+    for compression in [Compression::Gzip, Compression::Zstd] {
+        let mut out = Vec::new();
+        let mut encoder = ParallelEncoder::new(&mut out, compression, 4, 97);
+        for chunk in words.chunks(13) {
+            encoder.write_all(chunk).unwrap();
+        }
+        encoder.flush().unwrap();
+        drop(encoder);
+        assert!(out.len() < words.len());
+        assert_eq!(decompress(compression, &out), words, "{:?}", compression);
+    }
+}
+
+#[test]
+fn flushing_and_dropping_keep_the_output_valid() {
+    let mut out = Vec::new();
+    {
+        let mut encoder = ParallelEncoder::new(&mut out, Compression::Gzip, 2, 1 << 20);
+        encoder.write_all(b"first\n").unwrap();
+        encoder.flush().unwrap();
+        encoder.flush().unwrap();
+        encoder.write_all(b"second\n").unwrap();
+    }
+    assert_eq!(decompress(Compression::Gzip, &out), b"first\nsecond\n");
+
+    let mut empty = Vec::new();
+    ParallelEncoder::new(&mut empty, Compression::Zstd, 1, 64).flush().unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn names_parse() {
+    assert_eq!(Compression::parse("GZIP"), Some(Compression::Gzip));
+    assert_eq!(Compression::parse("zst"), Some(Compression::Zstd));
+    assert_eq!(Compression::parse("bzip2"), None);
+    assert_eq!(Compression::Zstd.extension(), ".zst");
+}
+
+/// A writer whose contents can still be read after it is given away.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn indexed_frames_can_be_extracted_alone() {
+    let words = words();
+    let (mut out, index) = (Vec::new(), Shared::default());
+    {
+        let mut encoder =
+            ParallelEncoder::indexed(&mut out, Compression::Zstd, 3, index.clone(), 100, LineEnding::Lf).unwrap();
+        for chunk in words.chunks(7) {
+            encoder.write_all(chunk).unwrap();
+            // Flushing mid-word must not start a frame there.
+            encoder.flush().unwrap();
+        }
+    }
+    assert_eq!(decompress(Compression::Zstd, &out), words);
+
+    let index = SeekIndex::read(&index.0.borrow()[..]).unwrap();
+    assert_eq!(index.words(), 1554);
+    assert!(index.frames.iter().all(|frame| frame.words <= 100));
+    assert!(index.frames.len() >= 16);
+
+    let lines: Vec<&[u8]> = words.split_inclusive(|&byte| byte == b'\n').collect();
+    for (skip, limit) in [(0, Some(3)), (99, Some(2)), (250, Some(300)), (1500, None), (1554, None)] {
+        let mut range = Vec::new();
+        let written = extract(&mut Cursor::new(&out), &index, skip, limit, &mut range).unwrap();
+        let end = limit.map_or(lines.len(), |limit| skip as usize + limit as usize);
+        assert_eq!(range, lines[skip as usize..end].concat(), "{} {:?}", skip, limit);
+        assert_eq!(written, (end - skip as usize) as u64);
+    }
+
+    assert!(SeekIndex::read(&b"crunch-rs seek index 1\nline-ending lf\n0 x\n"[..]).is_err());
+    assert!(SeekIndex::read(&b"something else\n"[..]).is_err());
+}
@@ -0,0 +1,32 @@
+use crunch_rs::dedup::{SeenWords, Unique};
+use crunch_rs::LineEnding;
+use std::io::Write;
+
+#[test]
+fn repeats_are_dropped_across_writes() {
+    let mut out = Vec::new();
+    let mut unique = Unique::new(&mut out, SeenWords::new(1 << 20, std::env::temp_dir()), LineEnding::Crlf).unwrap();
+    unique.write_all(b"pass\r\np4ss\r\npa").unwrap();
+    unique.write_all(b"ss\r\nP4SS\r\np4ss\r\n").unwrap();
+    assert_eq!((unique.duplicates(), unique.duplicate_bytes()), (2, 12));
+    drop(unique);
+    assert_eq!(out, b"pass\r\np4ss\r\nP4SS\r\n");
+    assert!(Unique::new(Vec::new(), SeenWords::new(1 << 20, std::env::temp_dir()), LineEnding::None).is_err());
+}
+
+#[test]
+fn spilled_words_are_still_remembered() {
+    // A tiny budget spills every few words to disk.
+    let mut seen = SeenWords::new(1024, std::env::temp_dir());
+    let words: Vec<String> = (0..5000).map(|n| format!("word{}", n * 7 % 5000)).collect();
+    for word in &words {
+        assert!(seen.insert(word.as_bytes()).unwrap(), "{}", word);
+    }
+    assert!(seen.runs() > 10);
+    for word in words.iter().rev() {
+        assert!(!seen.insert(word.as_bytes()).unwrap(), "{}", word);
+    }
+    assert!(seen.insert(b"word5000").unwrap());
+    assert!(seen.insert(b"").unwrap());
+    assert!(!seen.insert(b"").unwrap());
+}
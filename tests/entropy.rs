@@ -0,0 +1,30 @@
+use crunch_rs::entropy::word_entropy;
+use crunch_rs::generator::generate_words;
+use crunch_rs::progress::Progress;
+use crunch_rs::Config;
+
+#[test]
+fn entropy_rewards_varied_characters() {
+    assert_eq!(word_entropy(b""), 0.0);
+    assert_eq!(word_entropy(b"aaaa"), 0.0);
+    assert_eq!(word_entropy(b"abab"), 4.0);
+    assert_eq!(word_entropy(b"abcd"), 8.0);
+    assert_eq!(word_entropy("äöäö".as_bytes()), 4.0);
+}
+
+#[test]
+fn entropy_bounds_filter_words_and_count_rejects() {
+    let config = Config {
+        min_len: 3,
+        max_len: 3,
+        charset: "ab".to_string(),
+        min_entropy: Some(1.0),
+        max_entropy: Some(2.9),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    let progress = Progress::new(8);
+    generate_words(&config, &mut out, &progress).unwrap();
+    assert_eq!(out, b"aab\naba\nabb\nbaa\nbab\nbba\n");
+    assert_eq!((progress.words(), progress.enumerated()), (6, 8));
+}
@@ -0,0 +1,68 @@
+use crunch_rs::charset::{escape, unescape, Charset};
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::calculate_size;
+use crunch_rs::{Config, LineEnding};
+
+mod common;
+
+use common::output;
+
+#[test]
+fn escapes_decode_to_bytes() {
+    assert_eq!(
+        unescape(r"a\t\n\r\0\\\x00\xffé").unwrap(),
+        [&b"a"[..], b"\t", b"\n", b"\r", b"\0", b"\\", b"\0", b"\xff", "é".as_bytes()]
+    );
+    assert_eq!(Charset::parse(r"\x00\0").unwrap().len(), 1);
+    for bad in [r"\q", r"\x4", r"\xzz", "ab\\"] {
+        assert!(unescape(bad).is_err(), "{}", bad);
+    }
+    assert_eq!(escape(b"a\\\t\0\x01\xff\xc3\xa9"), r"a\\\t\0\x01\xffé");
+}
+
+#[test]
+fn named_charsets_are_not_unescaped() {
+    // symbols-all lists a backslash, which must not start an escape.
+    assert!(Charset::resolve("symbols-all").unwrap().symbols().contains(&b"\\".to_vec()));
+}
+
+#[test]
+fn raw_bytes_reach_the_output_unchanged() {
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: r"\x00\xff".to_string(),
+        line_ending: LineEnding::None,
+        ..Config::default()
+    };
+    assert_eq!(output(&config), b"\0\0\0\xff\xff\0\xff\xff");
+    assert_eq!(rank(&config, b"\xff\0").unwrap(), Some(2));
+    assert_eq!(unrank(&config, 3).unwrap().unwrap(), b"\xff\xff");
+}
+
+#[test]
+fn charset_files_use_every_byte() {
+    let config = Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "ignored".to_string(),
+        charset_bytes: Some(b"\x01\n\x01".to_vec()),
+        exclude_chars: r"\n".to_string(),
+        line_ending: LineEnding::Nul,
+        ..Config::default()
+    };
+    assert_eq!(calculate_size(&config).unwrap(), 2);
+    assert_eq!(output(&config), b"\x01\0\x01\x01\0");
+}
+
+#[test]
+fn templates_and_placeholders_take_escapes() {
+    let config = Config {
+        min_len: 3,
+        max_len: 3,
+        template: Some(r"\x7f?1\t".to_string()),
+        placeholders: [('1', r"\x00\x01".to_string())].into(),
+        ..Config::default()
+    };
+    assert_eq!(output(&config), b"\x7f\0\t\n\x7f\x01\t\n");
+}
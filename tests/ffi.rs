@@ -0,0 +1,92 @@
+#![cfg(feature = "ffi")]
+
+use crunch_rs::ffi::*;
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+fn config() -> CrunchConfig {
+    CrunchConfig {
+        min_len: 1,
+        max_len: 3,
+        charset: c"ab".as_ptr(),
+        pattern: ptr::null(),
+        exclude_chars: ptr::null(),
+        starts_with: ptr::null(),
+        ends_with: ptr::null(),
+        contains: ptr::null(),
+        contains_len: 0,
+        no_duplicates: false,
+        skip: 0,
+        limit: 0,
+    }
+}
+
+fn collect(config: &CrunchConfig, capacity: usize) -> Vec<String> {
+    let mut words = Vec::new();
+    unsafe {
+        let generator = crunch_generator_new(config);
+        assert!(!generator.is_null());
+        let mut buf = vec![0 as c_char; capacity];
+        let mut len = 0;
+        loop {
+            match crunch_generator_next(generator, buf.as_mut_ptr(), buf.len(), &mut len) {
+                CRUNCH_OK => words.push(CStr::from_ptr(buf.as_ptr()).to_str().unwrap().to_string()),
+                CRUNCH_BUFFER_TOO_SMALL => buf.resize(len + 1, 0),
+                CRUNCH_DONE => break,
+                status => panic!("unexpected status {}", status),
+            }
+        }
+        crunch_generator_free(generator);
+    }
+    words
+}
+
+#[test]
+fn generates_the_keyspace() {
+    let words = collect(&config(), 16);
+    assert_eq!(words.len(), 14);
+    assert_eq!(words[..3], ["a", "b", "aa"]);
+    let mut size = 0;
+    assert_eq!(unsafe { crunch_keyspace_size(&config(), &mut size) }, CRUNCH_OK);
+    assert_eq!(size, 14);
+}
+
+#[test]
+fn short_buffers_keep_the_word() {
+    assert_eq!(collect(&config(), 2), collect(&config(), 16));
+}
+
+#[test]
+fn filters_and_windows_apply() {
+    let contains = [c"b".as_ptr()];
+    let config = CrunchConfig {
+        pattern: c"@@".as_ptr(),
+        contains: contains.as_ptr(),
+        contains_len: 1,
+        skip: 1,
+        limit: 1,
+        ..config()
+    };
+    assert_eq!(collect(&config, 16), ["ba"]);
+}
+
+#[test]
+fn errors_are_reported() {
+    let config = CrunchConfig {
+        min_len: 4,
+        ..config()
+    };
+    unsafe {
+        assert!(crunch_generator_new(&config).is_null());
+        let message = CStr::from_ptr(crunch_last_error()).to_str().unwrap();
+        assert!(message.contains("min_len"), "{}", message);
+        crunch_generator_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn checked_in_header_is_current() {
+    // Refresh it from the build's copy when the interface changes.
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/crunch_rs.h"));
+    assert_eq!(include_str!("../include/crunch_rs.h"), generated, "include/crunch_rs.h is stale");
+}
@@ -0,0 +1,115 @@
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+
+mod common;
+
+use common::words;
+
+#[test]
+fn excluded_chars_are_removed_from_charset_and_literals() {
+    let config = Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "abc".to_string(),
+        exclude_chars: "b".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(words(&config), ["a", "c", "aa", "ac", "ca", "cc"]);
+
+    let config = Config {
+        charset: "abc".to_string(),
+        template: Some("b@".to_string()),
+        exclude_chars: "b".to_string(),
+        ..Default::default()
+    };
+    assert!(words(&config).is_empty());
+    assert_eq!(calculate_size(&config).unwrap(), 0);
+}
+
+#[test]
+fn excluded_substrings_are_pruned_and_counted_exactly() {
+    let config = Config {
+        min_len: 1,
+        max_len: 5,
+        charset: "ab1".to_string(),
+        no_duplicates: true,
+        exclude_substrings: vec!["ab".to_string(), "11".to_string()],
+        ..Default::default()
+    };
+    let words = words(&config);
+    assert!(words.iter().all(|w| !w.contains("ab") && !w.contains("11")));
+    assert_eq!(calculate_size(&config).unwrap(), words.len() as u64);
+}
+
+#[test]
+fn max_char_count_caps_occurrences_and_is_counted_exactly() {
+    let config = Config {
+        min_len: 1,
+        max_len: 6,
+        charset: "abc".to_string(),
+        max_char_count: Some(2),
+        ..Default::default()
+    };
+    let words = words(&config);
+    assert!(words
+        .iter()
+        .all(|w| "abc".chars().all(|c| w.matches(c).count() <= 2)));
+    assert_eq!(words.len(), 270);
+    assert_eq!(calculate_size(&config).unwrap(), 270);
+}
+
+#[test]
+fn max_char_count_is_counted_with_the_other_filters() {
+    let config = Config {
+        min_len: 1,
+        max_len: 4,
+        charset: "abc1".to_string(),
+        no_duplicates: true,
+        max_char_count: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(words(&config).len(), 188);
+    assert_eq!(calculate_size(&config).unwrap(), 188);
+}
+
+#[test]
+fn affixes_pin_positions_and_shrink_the_keyspace() {
+    let config = Config {
+        min_len: 2,
+        max_len: 4,
+        charset: "abc".to_string(),
+        starts_with: Some("ab".to_string()),
+        ends_with: Some("c".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(words(&config), ["abc", "abac", "abbc", "abcc"]);
+    assert_eq!(calculate_size(&config).unwrap(), 4);
+
+    let config = Config {
+        starts_with: Some("x".to_string()),
+        ..config
+    };
+    assert!(words(&config).is_empty());
+}
+
+#[test]
+fn required_substrings_are_pruned_and_counted_exactly() {
+    let config = Config {
+        min_len: 1,
+        max_len: 5,
+        charset: "abc".to_string(),
+        contains: vec!["ab".to_string(), "ca".to_string()],
+        no_duplicates: true,
+        ..Default::default()
+    };
+    let required = words(&config);
+    let expected: Vec<String> = words(&Config {
+        contains: Vec::new(),
+        ..config.clone()
+    })
+    .into_iter()
+    .filter(|word| word.contains("ab") && word.contains("ca"))
+    .collect();
+    assert_eq!(required, expected);
+    assert_eq!(calculate_size(&config).unwrap(), required.len() as u64);
+}
@@ -0,0 +1,69 @@
+use crunch_rs::format::{RecordFormat, Records};
+use crunch_rs::LineEnding;
+use std::io::Write;
+
+fn encode(format: RecordFormat, line_ending: LineEnding, writes: &[&[u8]]) -> (Vec<u8>, u64, u64) {
+    let mut out = Vec::new();
+    let mut records = Records::new(&mut out, format, line_ending).unwrap();
+    for buf in writes {
+        records.write_all(buf).unwrap();
+    }
+    let bytes = (records.bytes_in(), records.bytes_out());
+    drop(records);
+    (out, bytes.0, bytes.1)
+}
+
+#[test]
+fn jsonl_records_carry_the_metadata() {
+    let (out, bytes_in, bytes_out) = encode(RecordFormat::Jsonl, LineEnding::Lf, &[b"Pa\"s", b"s1\nab\n"]);
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(
+        out,
+        concat!(
+            "{\"index\":0,\"word\":\"Pa\\\"ss1\",\"length\":6,\"mask\":\"?u?l?s?l?l?d\",",
+            "\"composition\":\"mixedalphaspecialnum\"}\n",
+            "{\"index\":1,\"word\":\"ab\",\"length\":2,\"mask\":\"?l?l\",\"composition\":\"loweralpha\"}\n",
+        )
+    );
+    assert_eq!((bytes_in, bytes_out), (10, out.len() as u64));
+    assert!(Records::new(Vec::new(), RecordFormat::Jsonl, LineEnding::None).is_err());
+}
+
+#[test]
+fn csv_rows_follow_a_header_and_quote_what_needs_it() {
+    let (out, _, _) = encode(RecordFormat::Csv, LineEnding::Crlf, &[b"a,b\r\n\xffz\r\n"]);
+    assert_eq!(
+        out,
+        [
+            b"index,word,length,mask,composition\r\n".as_slice(),
+            b"0,\"a,b\",3,?l?s?l,loweralphaspecial\r\n",
+            b"1,\xffz,2,\xef\xbf\xbd?l,loweralphaspecial\r\n",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn records_are_not_split() {
+    let dir = std::env::temp_dir().join(format!("crunch-rs-format-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("out.txt");
+    let out = out.to_str().unwrap();
+    let archive = dir.join("out.zip");
+    for split in [
+        vec!["-o", out, "--split-by-length"],
+        vec!["-o", out, "-c", "5"],
+        vec!["-o", out, "-b", "1kb"],
+        vec!["--archive", archive.to_str().unwrap(), "-c", "5"],
+    ] {
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_crunch-rs"))
+            .args(["1", "2", "ab", "-q", "--format", "csv"])
+            .args(&split)
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success(), "{:?}", split);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "{:?}", split);
+    }
+    std::fs::remove_dir(&dir).unwrap();
+}
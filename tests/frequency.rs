@@ -0,0 +1,62 @@
+use crunch_rs::charset::Charset;
+use crunch_rs::frequency::Frequencies;
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+use std::sync::Arc;
+
+mod common;
+
+use common::words;
+
+fn chars(charset: &Charset) -> String {
+    charset.symbols().iter().map(|symbol| String::from_utf8_lossy(symbol)).collect()
+}
+
+#[test]
+fn builtin_ranking_puts_common_characters_first() {
+    let ordered = Frequencies::builtin().order(&Charset::from_chars("zqa1!e"), 0);
+    assert_eq!(chars(&ordered), "ae1zq!");
+}
+
+#[test]
+fn learned_frequencies_differ_per_position() {
+    let mut frequencies = Frequencies::default();
+    for word in ["cat", "cab", "bat", "x"] {
+        frequencies.add(word);
+    }
+    let charset = Charset::from_chars("abctx");
+    assert_eq!(chars(&frequencies.order(&charset, 0)), "cbxat");
+    assert_eq!(chars(&frequencies.order(&charset, 1)), "abctx");
+    assert_eq!(chars(&frequencies.order(&charset, 2)), "tbacx");
+    // Positions never seen fall back to the overall counts.
+    assert_eq!(chars(&frequencies.order(&charset, 5)), "abctx");
+}
+
+#[test]
+fn frequency_order_permutes_the_keyspace() {
+    let plain = Config {
+        min_len: 1,
+        max_len: 3,
+        charset: "xa1".to_string(),
+        ..Default::default()
+    };
+    let ordered = Config {
+        frequencies: Some(Arc::new(Frequencies::builtin())),
+        ..plain.clone()
+    };
+    let reordered = words(&ordered);
+    assert_eq!(reordered[..5], ["a", "1", "x", "aa", "a1"]);
+    assert_eq!(calculate_size(&ordered).unwrap(), 39);
+
+    let mut sorted = reordered.clone();
+    sorted.sort();
+    let mut expected = words(&plain);
+    expected.sort();
+    assert_eq!(sorted, expected);
+
+    for (index, word) in reordered.iter().enumerate() {
+        assert_eq!(rank(&ordered, word.as_bytes()).unwrap(), Some(index as u64));
+        assert_eq!(unrank(&ordered, index as u64).unwrap().as_deref(), Some(word.as_bytes()));
+    }
+}
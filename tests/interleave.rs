@@ -0,0 +1,98 @@
+use crunch_rs::generator::{generate_words, generate_words_window, Window};
+use crunch_rs::progress::Progress;
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+
+fn lines(config: &Config, window: Window) -> Vec<String> {
+    let mut out = Vec::new();
+    generate_words_window(config, &mut out, &Progress::new(0), &mut { window }).unwrap();
+    String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+}
+
+fn interleaved(min_len: usize, max_len: usize, charset: &str, batch: u64) -> Config {
+    Config {
+        min_len,
+        max_len,
+        charset: charset.to_string(),
+        interleave: Some(batch),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn lengths_take_turns_a_batch_at_a_time() {
+    let words = lines(&interleaved(1, 3, "ab", 2), Window::default());
+    assert_eq!(
+        words,
+        ["a", "b", "aa", "ab", "aaa", "aab", "ba", "bb", "aba", "abb", "baa", "bab", "bba", "bbb"]
+    );
+}
+
+#[test]
+fn every_word_is_written_once_with_its_rank() {
+    let config = interleaved(0, 4, "abc", 7);
+    let words = lines(&config, Window::default());
+    assert_eq!(words.len() as u64, calculate_size(&config).unwrap());
+    for (index, word) in words.iter().enumerate() {
+        assert_eq!(rank(&config, word.as_bytes()).unwrap(), Some(index as u64), "{}", word);
+        assert_eq!(unrank(&config, index as u64).unwrap().unwrap(), word.as_bytes());
+    }
+    assert_eq!(unrank(&config, words.len() as u64).unwrap(), None);
+}
+
+#[test]
+fn windows_match_slices_with_and_without_filters() {
+    let configs = [
+        interleaved(2, 5, "abcd", 10),
+        Config {
+            no_duplicates: true,
+            exclude_substrings: vec!["bc".to_string()],
+            ..interleaved(1, 4, "abcd", 5)
+        },
+        Config {
+            min_entropy: Some(1.5),
+            ..interleaved(2, 4, "abc", 4)
+        },
+    ];
+    for config in configs {
+        let all = lines(&config, Window::default());
+        for (skip, limit) in [(0, Some(5)), (3, Some(40)), (57, None), (all.len() as u64, None)] {
+            let end = limit.map_or(all.len(), |limit| (skip + limit) as usize).min(all.len());
+            assert_eq!(
+                lines(&config, Window { skip, limit }),
+                all[skip as usize..end],
+                "{:?} {} {:?}",
+                config.exclude_substrings,
+                skip,
+                limit
+            );
+        }
+    }
+}
+
+#[test]
+fn filters_keep_the_interleaved_order() {
+    let filtered = Config {
+        contains: vec!["b".to_string()],
+        ..interleaved(1, 3, "ab", 2)
+    };
+    let expected: Vec<String> = lines(&interleaved(1, 3, "ab", 2), Window::default())
+        .into_iter()
+        .filter(|word| word.contains('b'))
+        .collect();
+    assert_eq!(lines(&filtered, Window::default()), expected);
+
+    let mut out = Vec::new();
+    generate_words(&filtered, &mut out, &Progress::new(0)).unwrap();
+    assert_eq!(out.iter().filter(|&&byte| byte == b'\n').count(), expected.len());
+}
+
+#[test]
+fn shuffling_cannot_be_interleaved() {
+    let config = Config {
+        shuffle_seed: Some(1),
+        ..interleaved(1, 3, "ab", 2)
+    };
+    assert!(config.validate().is_err());
+}
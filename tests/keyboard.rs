@@ -0,0 +1,44 @@
+use crunch_rs::keyboard::{Keyboard, KeyboardLayout, ShiftMode, Walks};
+use crunch_rs::progress::Progress;
+use crunch_rs::LineEnding;
+
+fn generate(walks: &Walks) -> Vec<String> {
+    let mut out = Vec::new();
+    walks.generate(&mut out, &Progress::new(0), LineEnding::Lf).unwrap();
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn qwerty_walks_follow_adjacent_keys() {
+    let walks = Walks {
+        keyboard: Keyboard::new(KeyboardLayout::Qwerty),
+        min_len: 2,
+        max_len: 4,
+        shift: ShiftMode::None,
+    };
+    let words = generate(&walks);
+    assert_eq!(words.len() as u64, walks.size());
+    for expected in ["qw", "asdf", "1qaz", "zaq1", "poi"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(!words.iter().any(|w| w == "qp"));
+}
+
+#[test]
+fn mixed_shift_covers_every_combination() {
+    let walks = Walks {
+        keyboard: Keyboard::new(KeyboardLayout::Qwertz),
+        min_len: 2,
+        max_len: 2,
+        shift: ShiftMode::Mixed,
+    };
+    let words = generate(&walks);
+    assert_eq!(words.len() as u64, walks.size());
+    for expected in ["tz", "tZ", "Tz", "TZ"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+}
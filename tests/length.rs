@@ -0,0 +1,71 @@
+use crunch_rs::mutate::Mutator;
+use crunch_rs::size::calculate_size;
+use crunch_rs::{Config, LengthBounds, LengthPolicy};
+
+mod common;
+
+use common::words;
+
+#[test]
+fn templates_outside_the_bounds_need_a_policy() {
+    let config = Config {
+        min_len: 4,
+        max_len: 4,
+        template: Some("*%".to_string()),
+        wordlist: vec![b"al".to_vec(), b"ann".to_vec(), b"bobby".to_vec()],
+        ..Config::default()
+    };
+    assert!(config.validate().is_err());
+
+    let skip = Config {
+        length_policy: Some(LengthPolicy::Skip),
+        ..config.clone()
+    };
+    skip.validate().unwrap();
+    let kept = words(&skip);
+    assert_eq!(&kept[..2], ["ann0", "ann1"]);
+    assert_eq!(kept.len(), 10);
+    assert_eq!(kept.len() as u64, calculate_size(&skip).unwrap());
+
+    let truncate = Config {
+        min_len: 3,
+        length_policy: Some(LengthPolicy::Truncate),
+        ..config.clone()
+    };
+    let cut = words(&truncate);
+    assert_eq!(cut.len(), 21);
+    assert_eq!(cut.last().unwrap(), "bobb");
+    assert_eq!(cut.len() as u64, calculate_size(&truncate).unwrap());
+
+    let nothing = Config {
+        min_len: 7,
+        max_len: 8,
+        ..skip
+    };
+    assert!(nothing.validate().is_err());
+}
+
+#[test]
+fn mutations_are_held_to_the_bounds() {
+    let skip = LengthBounds {
+        min: 3,
+        max: 4,
+        policy: LengthPolicy::Skip,
+    };
+    assert_eq!(skip.apply("ab"), None);
+    assert_eq!(skip.apply("pâss"), Some("pâss"));
+    assert_eq!(skip.apply("passw"), None);
+    let truncate = LengthBounds {
+        policy: LengthPolicy::Truncate,
+        ..skip
+    };
+    assert_eq!(truncate.apply("pâssword"), Some("pâss"));
+    assert_eq!(truncate.apply("ab"), None);
+
+    let mutator = Mutator {
+        suffixes: vec!["1".to_string(), "123".to_string()],
+        length: Some(skip),
+        ..Mutator::default()
+    };
+    assert_eq!(mutator.mutate("ab"), ["ab1"]);
+}
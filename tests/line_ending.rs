@@ -0,0 +1,38 @@
+use crunch_rs::size::estimate;
+use crunch_rs::{Config, LineEnding};
+
+mod common;
+
+use common::output;
+
+fn config(charset: &str, line_ending: LineEnding) -> Config {
+    Config {
+        min_len: 1,
+        max_len: 2,
+        charset: charset.to_string(),
+        line_ending,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn words_end_with_the_chosen_terminator() {
+    for (line_ending, expected) in [
+        (LineEnding::Crlf, &b"a\r\nb\r\naa\r\nab\r\nba\r\nbb\r\n"[..]),
+        (LineEnding::Nul, &b"a\0b\0aa\0ab\0ba\0bb\0"[..]),
+        (LineEnding::None, &b"abaaabbabb"[..]),
+    ] {
+        let config = config("ab", line_ending);
+        assert_eq!(output(&config), expected);
+        assert_eq!(estimate(&config).unwrap().bytes, expected.len() as u64);
+    }
+}
+
+#[test]
+fn byte_estimate_is_exact_for_mixed_width_symbols() {
+    let config = config("a€", LineEnding::Crlf);
+    let out = output(&config);
+    let total = estimate(&config).unwrap();
+    assert_eq!(total.words, 6);
+    assert_eq!(total.bytes, out.len() as u64);
+}
@@ -0,0 +1,44 @@
+use crunch_rs::merge::{MergeOrder, Merger};
+
+fn merge(order: MergeOrder, memory: usize, words: &[&str]) -> Vec<String> {
+    let temp_dir = std::env::temp_dir();
+    let mut merger = Merger::new(order, memory, &temp_dir);
+    for word in words {
+        merger.push(word).unwrap();
+    }
+    let mut merged = Vec::new();
+    merger
+        .finish(|word| {
+            merged.push(word.to_string());
+            Ok(())
+        })
+        .unwrap();
+    merged
+}
+
+#[test]
+fn merge_sorts_and_removes_duplicates() {
+    let words = ["pear", "apple", "fig", "apple", "kiwi", "fig", ""];
+    assert_eq!(merge(MergeOrder::Lexicographic, usize::MAX, &words), ["", "apple", "fig", "kiwi", "pear"]);
+    assert_eq!(merge(MergeOrder::Length, usize::MAX, &words), ["", "fig", "kiwi", "pear", "apple"]);
+}
+
+#[test]
+fn spilled_runs_merge_like_an_in_memory_sort() {
+    let words: Vec<String> = (0..2000).map(|i| format!("w{}", (i * 7919) % 1500)).collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    for order in [MergeOrder::Lexicographic, MergeOrder::Length] {
+        let in_memory = merge(order, usize::MAX, &words);
+        assert_eq!(in_memory.len(), 1500);
+        assert_eq!(merge(order, 1024, &words), in_memory);
+    }
+}
+
+#[test]
+fn spilling_uses_runs_on_disk() {
+    let mut merger = Merger::new(MergeOrder::Lexicographic, 64, std::env::temp_dir());
+    for word in ["one", "two", "three", "four", "five", "six"] {
+        merger.push(word).unwrap();
+    }
+    assert!(merger.runs() > 1);
+}
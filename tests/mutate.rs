@@ -0,0 +1,39 @@
+use crunch_rs::input::for_each_line;
+use crunch_rs::mutate::{case_variants, leet_variants, CaseMode, Mutator, Rule};
+
+#[test]
+fn rules_follow_hashcat_semantics() {
+    let rules = Rule::parse_file("# comment\n:\nc $1\nr\nsa@ u\n] ^x\nT0 D2\n").unwrap();
+    let applied: Vec<String> = rules.iter().map(|rule| rule.apply("pass")).collect();
+    assert_eq!(applied, ["pass", "Pass1", "ssap", "P@SS", "xpas", "Pas"]);
+    assert!(Rule::parse("X").is_err());
+}
+
+#[test]
+fn case_and_leet_variants_compose() {
+    assert_eq!(case_variants("ab1", CaseMode::All).len(), 4);
+    assert_eq!(leet_variants("to"), ["to", "t0", "7o", "70"]);
+
+    let mutator = Mutator {
+        rules: Vec::new(),
+        case: Some(CaseMode::Simple),
+        leet: true,
+        suffixes: Vec::new(),
+        length: None,
+    };
+    let words = mutator.mutate("go");
+    assert!(words.contains(&"G0".to_string()));
+    assert!(words.contains(&"90".to_string()));
+}
+
+#[test]
+fn lines_are_streamed_without_terminators() {
+    let mut input: &[u8] = b"one\r\ntwo\n\nthree";
+    let mut lines = Vec::new();
+    for_each_line(&mut input, |line| {
+        lines.push(line.to_string());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(lines, ["one", "two", "", "three"]);
+}
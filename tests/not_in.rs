@@ -0,0 +1,81 @@
+use crunch_rs::bloom::{BloomFilter, DEFAULT_FALSE_POSITIVE_RATE};
+use crunch_rs::generator::generate_words;
+use crunch_rs::progress::Progress;
+use crunch_rs::Config;
+use std::io::Write;
+use std::sync::Arc;
+
+#[test]
+fn bloom_filter_never_misses_and_rarely_false_alarms() {
+    let mut filter = BloomFilter::new(10_000, 0.01);
+    for i in 0..10_000 {
+        filter.insert(format!("word{}", i).as_bytes());
+    }
+    assert!((0..10_000).all(|i| filter.contains(format!("word{}", i).as_bytes())));
+    let false_positives = (0..10_000)
+        .filter(|i| filter.contains(format!("other{}", i).as_bytes()))
+        .count();
+    assert!(false_positives < 300, "{} false positives", false_positives);
+}
+
+#[test]
+fn not_in_skips_baseline_words_and_counts_them_as_rejected() {
+    let path = std::env::temp_dir().join(format!("crunch-rs-not-in-{}.txt", std::process::id()));
+    std::fs::write(&path, "aa\nab\r\nba").unwrap();
+    let baseline = BloomFilter::from_wordlists(&[path.to_string_lossy().into_owned()], 0.0001).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "ab".to_string(),
+        not_in: Some(Arc::new(baseline)),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    let progress = Progress::new(4);
+    generate_words(&config, &mut out, &progress).unwrap();
+    assert_eq!(out, b"bb\n");
+    assert_eq!((progress.words(), progress.enumerated()), (1, 4));
+}
+
+#[test]
+fn not_in_reads_standard_input() {
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_crunch-rs"))
+        .args(["2", "2", "ab", "-q", "--not-in", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"aa\nba\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"ab\nbb\n");
+}
+
+#[test]
+fn small_filters_keep_the_words_they_were_not_given() {
+    let keyspace: Vec<Vec<u8>> = (1..=3)
+        .flat_map(|len| {
+            (0..3u32.pow(len)).map(move |mut index| {
+                (0..len)
+                    .map(|_| {
+                        let byte = b"abc"[(index % 3) as usize];
+                        index /= 3;
+                        byte
+                    })
+                    .collect()
+            })
+        })
+        .collect();
+    for members in [&["aa"][..], &["aa", "ab"], &["aa", "ba", "cab"]] {
+        let mut filter = BloomFilter::new(members.len() as u64, DEFAULT_FALSE_POSITIVE_RATE);
+        for member in members {
+            filter.insert(member.as_bytes());
+        }
+        for word in &keyspace {
+            let member = members.iter().any(|member| member.as_bytes() == word.as_slice());
+            assert_eq!(filter.contains(word), member, "{:?}", String::from_utf8_lossy(word));
+        }
+    }
+}
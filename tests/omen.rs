@@ -0,0 +1,64 @@
+use crunch_rs::config::LineEnding;
+use crunch_rs::omen::{Model, Omen, Trainer};
+use crunch_rs::progress::Progress;
+use std::collections::HashSet;
+
+fn train(words: &[&str]) -> Model {
+    let mut trainer = Trainer::new(3);
+    for word in words {
+        trainer.add(word);
+    }
+    trainer.finish()
+}
+
+fn generate(model: &Model, min_len: usize, max_len: usize, max_level: Option<u32>) -> Vec<String> {
+    let mut out = Vec::new();
+    Omen::new(model)
+        .generate(min_len, max_len, max_level, &mut out, &Progress::new(0), LineEnding::Lf)
+        .unwrap();
+    String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn model_survives_a_save_and_load() {
+    let model = train(&["password", "pass word", "letmein", "abc"]);
+    let mut saved = Vec::new();
+    model.save(&mut saved).unwrap();
+    assert_eq!(Model::load(saved.as_slice()).unwrap(), model);
+}
+
+#[test]
+fn malformed_models_are_rejected() {
+    assert!(Model::load("".as_bytes()).is_err());
+    assert!(Model::load("omen 3\nxx 1 ab\n".as_bytes()).is_err());
+    assert!(Model::load("omen 3\ncp 99 abc\n".as_bytes()).is_err());
+}
+
+#[test]
+fn likeliest_training_word_comes_first() {
+    let model = train(&["password", "password", "password", "password", "password", "monkey", "dragon"]);
+    let words = generate(&model, 6, 8, Some(4));
+    assert_eq!(words[0], "password");
+    assert!(words.contains(&"monkey".to_string()));
+}
+
+#[test]
+fn candidates_are_unique_and_within_length() {
+    let model = train(&["abc", "abd", "bcd"]);
+    let words = generate(&model, 3, 4, None);
+    let unique: HashSet<&String> = words.iter().collect();
+    assert_eq!(unique.len(), words.len());
+    assert!(words.iter().all(|word| (3..=4).contains(&word.len())));
+    // With no level cap every word over the alphabet is reached.
+    assert_eq!(words.len(), 4usize.pow(3) + 4usize.pow(4));
+}
+
+#[test]
+fn max_level_caps_the_output() {
+    let model = train(&["abc", "abd", "bcd"]);
+    let capped = generate(&model, 3, 4, Some(2));
+    let all = generate(&model, 3, 4, None);
+    assert!(!capped.is_empty());
+    assert!(capped.len() < all.len());
+    assert_eq!(capped, all[..capped.len()]);
+}
@@ -0,0 +1,332 @@
+use crunch_rs::generator::generate_words;
+use crunch_rs::output::{
+    count_lines, length_file_name, parse_size, truncate_partial_line, LengthSplitter, SizeLimit, SplitLimits, Splitter,
+    DEFAULT_NAME_TEMPLATE,
+};
+use crunch_rs::progress::Progress;
+use crunch_rs::{Config, LineEnding};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+#[test]
+fn chunks_are_named_after_their_first_and_last_word() {
+    let dir = std::env::temp_dir().join(format!("crunch-rs-split-{}", std::process::id()));
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "abc".to_string(),
+        ..Default::default()
+    };
+    let limits = SplitLimits {
+        max_lines: Some(4),
+        max_bytes: None,
+    };
+
+    let mut splitter = Splitter::new(&dir, DEFAULT_NAME_TEMPLATE, limits, LineEnding::Lf).unwrap();
+    generate_words(&config, &mut splitter, &Progress::new(0)).unwrap();
+    let files = splitter.finish().unwrap();
+
+    let names: Vec<_> = files
+        .iter()
+        .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["aa-ba.txt", "bb-cb.txt", "cc-cc.txt"]);
+    assert_eq!(fs::read_to_string(&files[1]).unwrap(), "bb\nbc\nca\ncb\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sizes_accept_decimal_and_binary_units() {
+    assert_eq!(parse_size("500"), Ok(500));
+    assert_eq!(parse_size("64kb"), Ok(64_000));
+    assert_eq!(parse_size("2MiB"), Ok(2 << 20));
+    assert!(parse_size("10xb").is_err());
+}
+
+#[test]
+fn durations_combine_units() {
+    use crunch_rs::output::parse_duration;
+    use std::time::Duration;
+
+    assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+    assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+    for invalid in ["", "h", "1.5h", "10x", "2h30"] {
+        assert!(parse_duration(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn pipe_feeds_the_child_and_reports_its_early_exit() {
+    use std::io::Write;
+
+    let mut child = crunch_rs::output::spawn_pipe("exit 0").unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    assert!(child.wait().unwrap().success());
+    let err = stdin.write_all(b"more words\n").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn rates_are_words_unless_given_in_bytes() {
+    use crunch_rs::output::{parse_rate, Rate};
+
+    assert_eq!(parse_rate("50000/s"), Ok(Rate::WordsPerSec(50_000)));
+    assert_eq!(parse_rate("2k/s"), Ok(Rate::WordsPerSec(2_000)));
+    assert_eq!(parse_rate("10MB/s"), Ok(Rate::BytesPerSec(10_000_000)));
+    assert!(parse_rate("0/s").is_err());
+}
+
+#[test]
+fn throttle_paces_words_without_reordering_them() {
+    use crunch_rs::output::{Rate, Throttle};
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    let words: Vec<u8> = (0..40).flat_map(|i| format!("w{}\n", i).into_bytes()).collect();
+    let mut out = Vec::new();
+    let start = Instant::now();
+    let mut throttle = Throttle::new(&mut out, Rate::WordsPerSec(400), LineEnding::Lf).unwrap();
+    throttle.write_all(&words).unwrap();
+    // 20 words of burst, then 20 more at 400/s.
+    assert!(start.elapsed() >= Duration::from_millis(45));
+    assert_eq!(out, words);
+}
+
+#[test]
+fn output_buffer_writes_in_capacity_sized_chunks() {
+    use crunch_rs::output::OutputBuffer;
+    use std::io::Write;
+
+    /// Records the size of every write it receives.
+    struct Recorder(Vec<usize>);
+    impl Write for &mut Recorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.push(buf.len());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut recorder = Recorder(Vec::new());
+    let mut buffer = OutputBuffer::with_capacity(8, &mut recorder);
+    for _ in 0..5 {
+        buffer.write_all(b"abc\n").unwrap();
+    }
+    buffer.flush().unwrap();
+    drop(buffer);
+    assert_eq!(recorder.0, [8, 8, 4]);
+}
+
+#[test]
+fn archives_hold_one_member_per_chunk() {
+    use std::io::Read;
+
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "ab".to_string(),
+        ..Default::default()
+    };
+    let limits = SplitLimits {
+        max_lines: Some(2),
+        max_bytes: None,
+    };
+    let dir = std::env::temp_dir().join(format!("crunch-rs-archive-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    for name in ["words.zip", "words.7z"] {
+        let path = dir.join(name);
+        let mut splitter = Splitter::into_archive(&path, DEFAULT_NAME_TEMPLATE, limits, LineEnding::Lf).unwrap();
+        generate_words(&config, &mut splitter, &Progress::new(0)).unwrap();
+        let members = splitter.finish().unwrap();
+        assert_eq!(members, [Path::new("aa-ab.txt"), Path::new("ba-bb.txt")]);
+
+        let mut contents = Vec::new();
+        if name.ends_with(".zip") {
+            let mut archive = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+            for i in 0..archive.len() {
+                let mut member = String::new();
+                archive.by_index(i).unwrap().read_to_string(&mut member).unwrap();
+                contents.push(member);
+            }
+        } else {
+            let mut archive = sevenz_rust::SevenZReader::open(&path, "".into()).unwrap();
+            archive
+                .for_each_entries(|_, reader| {
+                    let mut member = String::new();
+                    reader.read_to_string(&mut member)?;
+                    contents.push(member);
+                    Ok(true)
+                })
+                .unwrap();
+        }
+        assert_eq!(contents, ["aa\nab\n", "ba\nbb\n"]);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Writes `contents`, cuts off its partial last line and checks what is left.
+fn check_truncation(contents: &[u8], line_ending: LineEnding, kept: &[u8], last: Option<&[u8]>, lines: u64) {
+    let path = std::env::temp_dir().join(format!("crunch-rs-partial-{}-{}.txt", std::process::id(), lines));
+    fs::write(&path, contents).unwrap();
+    assert_eq!(truncate_partial_line(&path, line_ending).unwrap().as_deref(), last);
+    assert_eq!(fs::read(&path).unwrap(), kept);
+    assert_eq!(count_lines(&path, line_ending).unwrap(), lines);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn partial_last_lines_are_cut_off() {
+    check_truncation(b"aa\nab\nac\n", LineEnding::Lf, b"aa\nab\nac\n", Some(b"ac"), 3);
+    check_truncation(b"aa\nab\na", LineEnding::Lf, b"aa\nab\n", Some(b"ab"), 2);
+    check_truncation(b"aa\r\nab\r", LineEnding::Crlf, b"aa\r\n", Some(b"aa"), 1);
+    check_truncation(b"aa\0ab\0ac", LineEnding::Nul, b"aa\0ab\0", Some(b"ab"), 2);
+    check_truncation(b"a", LineEnding::Lf, b"", None, 0);
+}
+
+#[test]
+fn framing_holds_a_word_back_until_its_terminator() {
+    use crunch_rs::output::Framing;
+
+    let mut framing = Framing::required(LineEnding::Crlf, "--unique").unwrap();
+    assert_eq!(&*framing.complete(b"one\r\ntw"), b"one\r\n");
+    assert!(framing.complete(b"o\r").is_empty());
+    let words = framing.complete(b"\nthree\r\nfo");
+    let lines: Vec<&[u8]> = framing.lines(&words).collect();
+    assert_eq!(lines, [&b"two\r\n"[..], b"three\r\n"]);
+    assert_eq!(framing.word(lines[1]), b"three");
+    assert_eq!(framing.take_pending(), b"fo");
+
+    assert!(Framing::of(LineEnding::None).is_none());
+    let err = Framing::required(LineEnding::None, "--unique").err().unwrap();
+    assert_eq!(err.to_string(), "--unique needs a line ending to find word boundaries");
+}
+
+#[test]
+fn size_limit_stops_on_a_word_boundary() {
+    use std::io::Write;
+
+    let mut out = Vec::new();
+    let mut limited = SizeLimit::new(&mut out, 10, LineEnding::Crlf).unwrap();
+    limited.write_all(b"one\r\ntw").unwrap();
+    limited.write_all(b"o\r\n").unwrap();
+    assert!(limited.write_all(b"three\r\n").is_err());
+    assert!(limited.is_full());
+    assert_eq!((limited.words(), limited.last_word()), (2, &b"two"[..]));
+    assert_eq!(out, b"one\r\ntwo\r\n");
+
+    let mut out = Vec::new();
+    let config = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "abc".to_string(),
+        ..Default::default()
+    };
+    let mut limited = SizeLimit::new(&mut out, 20, LineEnding::Lf).unwrap();
+    assert!(generate_words(&config, &mut limited, &Progress::new(0)).is_err());
+    assert_eq!((limited.words(), limited.last_word()), (6, &b"bc"[..]));
+    assert_eq!(out, b"aa\nab\nac\nba\nbb\nbc\n");
+}
+
+#[test]
+fn interrupted_output_stops_between_words() {
+    use crunch_rs::output::Interruptible;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static STOP: AtomicBool = AtomicBool::new(false);
+    let mut out = Vec::new();
+    let mut writer = Interruptible::new(&mut out, &STOP, LineEnding::Lf);
+    writer.write_all(b"one\ntw").unwrap();
+    STOP.store(true, Ordering::Relaxed);
+    // The word in progress is finished before stopping.
+    writer.write_all(b"o\n").unwrap();
+    assert!(writer.write_all(b"three\n").is_err());
+    assert!(writer.is_interrupted());
+    assert_eq!(out, b"one\ntwo\n");
+}
+
+#[test]
+fn tee_keeps_writing_to_the_sinks_that_still_work() {
+    use crunch_rs::output::Tee;
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    /// Shares what it receives with the test, failing once `room` runs out.
+    struct Sink {
+        received: Rc<RefCell<Vec<u8>>>,
+        room: usize,
+        kind: io::ErrorKind,
+    }
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() > self.room {
+                return Err(io::Error::from(self.kind));
+            }
+            self.room -= buf.len();
+            self.received.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let sink = |room, kind| {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        (Sink { received: Rc::clone(&received), room, kind }, received)
+    };
+
+    let (disk, on_disk) = sink(usize::MAX, io::ErrorKind::Other);
+    let (cracker, cracked) = sink(4, io::ErrorKind::BrokenPipe);
+    let mut tee = Tee::new();
+    tee.add("disk", disk);
+    tee.add("cracker", cracker);
+    for word in ["ab\n", "cd\n", "ef\n"] {
+        tee.write_all(word.as_bytes()).unwrap();
+    }
+    let failed = tee.finish();
+    assert_eq!(*on_disk.borrow(), b"ab\ncd\nef\n");
+    assert_eq!(*cracked.borrow(), b"ab\n");
+    assert_eq!(failed.len(), 1);
+    assert_eq!((failed[0].0.as_str(), failed[0].1.kind()), ("cracker", io::ErrorKind::BrokenPipe));
+
+    let (full, _) = sink(0, io::ErrorKind::StorageFull);
+    let mut tee = Tee::new();
+    tee.add("full", full);
+    assert_eq!(tee.write_all(b"ab\n").unwrap_err().kind(), io::ErrorKind::StorageFull);
+}
+
+#[test]
+fn words_are_split_by_their_length() {
+    let dir = std::env::temp_dir().join(format!("crunch-rs-lengths-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut opened = Vec::new();
+    let open = |path: &Path| {
+        opened.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        fs::File::create(path)
+    };
+    let mut splitter = LengthSplitter::new(&dir.join("words.txt"), LineEnding::Crlf, open).unwrap();
+    // Words arrive in pieces and out of length order; the last has no terminator.
+    for piece in [&b"ab\r"[..], b"\nx\r\n\xc3\xa9t\r\ncd", b"\r\ny"] {
+        splitter.write_all(piece).unwrap();
+    }
+    let files = splitter.finish().unwrap();
+
+    assert_eq!(opened, ["words-2.txt", "words-1.txt"]);
+    let summary: Vec<_> = files.iter().map(|file| (file.length, file.words, file.bytes)).collect();
+    assert_eq!(summary, [(1, 2, 4), (2, 3, 13)]);
+    assert_eq!(fs::read_to_string(&files[0].path).unwrap(), "x\r\ny");
+    assert_eq!(fs::read_to_string(&files[1].path).unwrap(), "ab\r\nét\r\ncd\r\n");
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(length_file_name(Path::new("w.txt.gz"), 4), Path::new("w-4.txt.gz"));
+    assert_eq!(length_file_name(Path::new("dir/.hidden"), 4), Path::new("dir/.hidden-4"));
+    assert_eq!(length_file_name(Path::new("plain"), 10), Path::new("plain-10"));
+}
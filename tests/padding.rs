@@ -0,0 +1,83 @@
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::{calculate_size, estimate};
+use crunch_rs::{Config, PadSide, Padding};
+
+mod common;
+
+use common::words;
+
+fn padded(width: usize, symbol: &[u8], side: PadSide) -> Config {
+    Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "ab".to_string(),
+        padding: Some(Padding {
+            width,
+            symbol: symbol.to_vec(),
+            side,
+        }),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn short_words_are_padded_on_either_side() {
+    assert_eq!(words(&padded(3, b"0", PadSide::Left)), ["00a", "00b", "0aa", "0ab", "0ba", "0bb"]);
+    assert_eq!(words(&padded(2, b".", PadSide::Right)), ["a.", "b.", "aa", "ab", "ba", "bb"]);
+    // Longer words are left alone.
+    assert_eq!(words(&padded(1, b"0", PadSide::Left)), ["a", "b", "aa", "ab", "ba", "bb"]);
+}
+
+#[test]
+fn estimates_and_ranks_count_the_padding() {
+    let config = padded(4, "é".as_bytes(), PadSide::Left);
+    assert_eq!(calculate_size(&config).unwrap(), 6);
+    // 2 words of 1 + 3 * 2 bytes and 4 words of 2 + 2 * 2 bytes, each with a newline.
+    assert_eq!(estimate(&config).unwrap().bytes, 2 * 8 + 4 * 7);
+    let word = "ééab".as_bytes();
+    assert_eq!(rank(&config, word).unwrap(), Some(3));
+    assert_eq!(unrank(&config, 3).unwrap().unwrap(), word);
+}
+
+#[test]
+fn padding_makes_each_word_once() {
+    let ids = Config {
+        min_len: 1,
+        max_len: 3,
+        charset: "01".to_string(),
+        ..padded(3, b"0", PadSide::Left)
+    };
+    assert_eq!(words(&ids), ["000", "001", "010", "011", "100", "101", "110", "111"]);
+    assert_eq!(calculate_size(&ids).unwrap(), 8);
+    assert_eq!(words(&padded(3, b"a", PadSide::Right)), ["aaa", "baa", "aba", "bba"]);
+}
+
+#[test]
+fn max_char_count_counts_the_padding() {
+    let config = Config {
+        max_len: 3,
+        max_char_count: Some(2),
+        ..padded(3, b"a", PadSide::Left)
+    };
+    assert_eq!(words(&config).len(), 6);
+    assert_eq!(calculate_size(&config).unwrap(), 6);
+}
+
+#[test]
+fn affixes_apply_to_the_padded_word() {
+    let starts = Config {
+        starts_with: Some("0b".to_string()),
+        ..padded(3, b"0", PadSide::Left)
+    };
+    assert_eq!(words(&starts), ["0ba", "0bb"]);
+    let ends = Config {
+        ends_with: Some("b".to_string()),
+        ..padded(3, b"0", PadSide::Left)
+    };
+    assert_eq!(words(&ends), ["00b", "0ab", "0bb"]);
+    let unpadded = Config {
+        starts_with: Some("b".to_string()),
+        ..padded(3, b"0", PadSide::Left)
+    };
+    assert!(unpadded.validate().is_err());
+}
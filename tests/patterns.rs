@@ -0,0 +1,35 @@
+use crunch_rs::patterns::{Date, DateRange, PinRange};
+use crunch_rs::progress::Progress;
+use crunch_rs::LineEnding;
+
+#[test]
+fn date_range_crosses_leap_day_and_year_end() {
+    let range = DateRange {
+        from: Date::parse("1999-12-31").unwrap(),
+        to: Date::parse("2000-03-01").unwrap(),
+        formats: vec!["DDMMYYYY".to_string(), "M/D/YY".to_string()],
+    };
+    let mut out = Vec::new();
+    range.generate(&mut out, &Progress::new(0), LineEnding::Lf).unwrap();
+    let words: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+
+    assert_eq!(range.days(), 62);
+    assert_eq!(words.len() as u64, range.size());
+    assert_eq!(words[0], "31121999");
+    assert!(words.contains(&"29022000"));
+    assert_eq!(words[62], "12/31/99");
+    assert!(Date::parse("1900-02-29").is_err());
+}
+
+#[test]
+fn pins_are_zero_padded() {
+    let pins = PinRange {
+        from: 7,
+        to: 10,
+        width: 4,
+    };
+    let mut out = Vec::new();
+    pins.generate(&mut out, &Progress::new(0), LineEnding::Lf).unwrap();
+    assert_eq!(out, b"0007\n0008\n0009\n0010\n");
+    assert_eq!(pins.size(), 4);
+}
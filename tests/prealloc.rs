@@ -0,0 +1,60 @@
+use crunch_rs::prealloc::{regions, total, write_preallocated};
+use crunch_rs::progress::Progress;
+use crunch_rs::size::estimate;
+use crunch_rs::{Config, LineEnding};
+use std::fs;
+
+mod common;
+
+use common::output;
+
+#[test]
+fn threads_fill_the_file_with_the_usual_output() {
+    let config = Config {
+        min_len: 1,
+        max_len: 7,
+        charset: "abcdé".to_string(),
+        suffixes: vec![b"1".to_vec(), b"!!".to_vec()],
+        line_ending: LineEnding::Crlf,
+        ..Config::default()
+    };
+    let regions = regions(&config, 4).unwrap();
+    assert!(regions.len() > 1);
+    assert_eq!(total(&regions), estimate(&config).unwrap());
+
+    let path = std::env::temp_dir().join(format!("crunch-rs-prealloc-{}.txt", std::process::id()));
+    let progress = Progress::new(0);
+    let written = write_preallocated(&regions, config.line_ending, &path, 4, 4096, &progress, None).unwrap();
+    let expected = output(&config);
+    let actual = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(written.complete);
+    assert_eq!((written.words, written.bytes), (progress.words(), expected.len() as u64));
+    assert!(actual == expected);
+}
+
+#[test]
+fn filtered_and_reordered_keyspaces_are_refused() {
+    let plain = Config {
+        min_len: 1,
+        max_len: 3,
+        charset: "ab".to_string(),
+        ..Config::default()
+    };
+    for config in [
+        Config {
+            contains: vec!["a".to_string()],
+            ..plain.clone()
+        },
+        Config {
+            shuffle_seed: Some(1),
+            ..plain.clone()
+        },
+        Config {
+            interleave: Some(2),
+            ..plain
+        },
+    ] {
+        assert!(regions(&config, 2).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+use crunch_rs::prince::Prince;
+use crunch_rs::progress::Progress;
+use crunch_rs::LineEnding;
+
+#[test]
+fn chains_are_emitted_smallest_keyspace_first() {
+    let words = ["a", "bb", "c", "bb", "ddd"].map(str::to_string);
+    let prince = Prince::new(words, 2, 3, 8);
+
+    let keyspaces: Vec<u64> = prince.chains().iter().map(|c| c.keyspace).collect();
+    assert_eq!(keyspaces, [1, 1, 2, 2, 4, 8]);
+
+    let mut out = Vec::new();
+    prince.generate(&mut out, &Progress::new(0), LineEnding::Lf).unwrap();
+    let words: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+    assert_eq!(words.len() as u64, prince.size());
+    assert_eq!(&words[..4], ["bb", "ddd", "abb", "cbb"]);
+    assert_eq!(words.last(), Some(&"ccc"));
+}
@@ -0,0 +1,53 @@
+use crunch_rs::profile::ProfileFile;
+
+#[test]
+fn profiles_are_selected_by_name_or_default() {
+    let file = ProfileFile::parse(
+        r#"
+        [profiles.default]
+        min-len = 4
+        charset = "abc"
+
+        [profiles.corp-audit]
+        template = "?1%%"
+        set = { "1" = "AB" }
+        exclude-substr = ["00"]
+        split-bytes = "100mb"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(file.profile(None).unwrap().min_len, Some(4));
+    let audit = file.profile(Some("corp-audit")).unwrap();
+    assert_eq!(audit.template.as_deref(), Some("?1%%"));
+    assert_eq!(audit.set[&'1'], "AB");
+    assert_eq!(audit.exclude_substr.as_deref(), Some(&["00".to_string()][..]));
+    assert!(file.profile(Some("missing")).is_err());
+}
+
+#[test]
+fn unknown_profile_keys_are_rejected() {
+    assert!(ProfileFile::parse("[profiles.x]\nmin_len = 3\n").is_err());
+}
+
+#[test]
+fn the_command_line_can_turn_off_a_profiles_switches() {
+    let path = std::env::temp_dir().join(format!("crunch-rs-profile-{}.toml", std::process::id()));
+    std::fs::write(&path, "[profiles.default]\nno-duplicates = true\nshuffle = true\nquiet = true\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_crunch-rs"))
+            .args(["2", "2", "ab", "--config", path.to_str().unwrap()])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        (String::from_utf8(output.stdout).unwrap(), output.stderr.is_empty())
+    };
+    let (words, quiet) = run(&[]);
+    assert_eq!((words.lines().count(), quiet), (2, true));
+    let (words, quiet) = run(&["--allow-duplicates", "--no-shuffle", "--no-quiet"]);
+    assert_eq!((words.as_str(), quiet), ("aa\nab\nba\nbb\n", false));
+    let (words, _) = run(&["--no-shuffle", "--shuffle", "--allow-duplicates"]);
+    assert_ne!(words, "aa\nab\nba\nbb\n");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,66 @@
+use crunch_rs::progress::{FileCounts, Progress};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn counts_from_every_thread_add_up() {
+    let progress = Progress::new(4000);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..500 {
+                    progress.increment(3);
+                }
+                progress.add(400, 1200);
+                progress.reject(100);
+            });
+        }
+    });
+    assert_eq!(progress.words(), 3600);
+    assert_eq!(progress.bytes(), 10800);
+    assert_eq!(progress.enumerated(), 4000);
+}
+
+#[test]
+fn progresses_on_one_thread_stay_apart() {
+    let first = Progress::unbounded();
+    let second = Progress::unbounded();
+    first.increment(2);
+    second.add(5, 10);
+    first.increment(2);
+    assert_eq!((first.words(), first.bytes()), (2, 4));
+    assert_eq!((second.words(), second.bytes()), (5, 10));
+    drop(first);
+    assert_eq!(Progress::new(1).words(), 0);
+}
+
+#[test]
+fn reports_cover_filters_files_and_the_resume_index() {
+    let progress = Progress::new(10);
+    progress.add(6, 18);
+    progress.reject(4);
+    let files = vec![
+        FileCounts {
+            path: "out-2.txt".to_string(),
+            words: Some(6),
+            bytes: 18,
+        },
+        FileCounts {
+            path: "a \"b\".gz".to_string(),
+            words: None,
+            bytes: 7,
+        },
+    ];
+    let mut report = progress.report(files, Some(106));
+    assert_eq!((report.words, report.skipped, report.bytes), (6, 4, 18));
+
+    report.elapsed = Duration::from_secs(2);
+    assert_eq!(
+        report.to_text(),
+        "6 words written, 4 skipped by filters, 18 B in 00:00:02 (3 words/s, 9 B/s)\n  out-2.txt: 6 words, 18 B\n  a \"b\".gz: 7 B\nResume index: 106"
+    );
+    assert_eq!(
+        report.to_json(),
+        r#"{"event":"summary","words":6,"skipped":4,"enumerated":10,"bytes":18,"duration_secs":2.000,"words_per_sec":3.0,"bytes_per_sec":9.0,"resume_index":106,"output_files":["out-2.txt","a \"b\".gz"],"files":[{"path":"out-2.txt","words":6,"bytes":18},{"path":"a \"b\".gz","words":null,"bytes":7}]}"#
+    );
+}
@@ -0,0 +1,59 @@
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::Config;
+use std::collections::BTreeMap;
+
+mod common;
+
+use common::byte_words;
+
+#[test]
+fn rank_and_unrank_follow_generation_order() {
+    let configs = [
+        Config {
+            min_len: 1,
+            max_len: 3,
+            charset: "abç".to_string(),
+            ..Default::default()
+        },
+        Config {
+            charset: "xy".to_string(),
+            template: Some("@-%?1".to_string()),
+            placeholders: BTreeMap::from([('1', "ab".to_string())]),
+            ..Default::default()
+        },
+    ];
+    for config in &configs {
+        let words = byte_words(config);
+        for (index, word) in words.iter().enumerate() {
+            assert_eq!(rank(config, word).unwrap(), Some(index as u64));
+            assert_eq!(unrank(config, index as u64).unwrap().as_ref(), Some(word));
+        }
+        assert_eq!(unrank(config, words.len() as u64).unwrap(), None);
+    }
+}
+
+#[test]
+fn words_outside_the_keyspace_have_no_rank() {
+    let config = Config {
+        min_len: 2,
+        max_len: 3,
+        charset: "abc".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(rank(&config, b"a").unwrap(), None);
+    assert_eq!(rank(&config, b"abd").unwrap(), None);
+    assert_eq!(rank(&config, b"abca").unwrap(), None);
+}
+
+#[test]
+fn filtered_keyspaces_are_rejected() {
+    let config = Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "abc".to_string(),
+        no_duplicates: true,
+        ..Default::default()
+    };
+    assert!(rank(&config, b"ab").is_err());
+    assert!(unrank(&config, 0).is_err());
+}
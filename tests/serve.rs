@@ -0,0 +1,102 @@
+use crunch_rs::serve::{serve, MAX_CONNECTIONS};
+use crunch_rs::Config;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+fn start(config: Config) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener, config));
+    addr
+}
+
+/// Sends `GET target` and returns the status line and body.
+fn get(addr: SocketAddr, target: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+fn abc(min_len: usize, max_len: usize) -> Config {
+    Config {
+        min_len,
+        max_len,
+        charset: "abc".to_string(),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn words_endpoint_streams_the_requested_slice() {
+    let addr = start(abc(1, 2));
+    let (status, body) = get(addr, "/words?skip=2&limit=3");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, "c\naa\nab\n");
+
+    let (_, body) = get(addr, "/words?skip=10");
+    assert_eq!(body, "cb\ncc\n");
+}
+
+#[test]
+fn info_reports_the_keyspace_size() {
+    let addr = start(abc(2, 2));
+    let (status, body) = get(addr, "/info");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, "{\"words\":9,\"bytes\":27}\n");
+}
+
+#[test]
+fn bad_requests_are_rejected() {
+    let addr = start(abc(1, 1));
+    assert_eq!(get(addr, "/words?skip=x").0, "HTTP/1.1 400 Bad Request");
+    assert_eq!(get(addr, "/words?offset=1").0, "HTTP/1.1 400 Bad Request");
+    assert_eq!(get(addr, "/nope").0, "HTTP/1.1 404 Not Found");
+}
+
+#[test]
+fn oversized_requests_are_cut_off() {
+    let addr = start(abc(1, 1));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let header = format!("GET /info HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(10_000));
+    // The server may hang up before it has read everything.
+    let _ = stream.write_all(header.as_bytes());
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 431 "), "{}", response);
+}
+
+#[test]
+fn connections_beyond_the_limit_are_turned_away() {
+    let addr = start(abc(1, 1));
+    let idle: Vec<TcpStream> = (0..MAX_CONNECTIONS).map(|_| TcpStream::connect(addr).unwrap()).collect();
+    // Connections are counted as they are accepted, in order.
+    let mut response = String::new();
+    TcpStream::connect(addr).unwrap().read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+    drop(idle);
+}
+
+#[test]
+fn byte_keyspaces_are_served_as_binary() {
+    let head = |config: Config| {
+        let addr = start(config);
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /words HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let end = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+        (String::from_utf8(response[..end].to_vec()).unwrap(), response[end + 4..].to_vec())
+    };
+    let (text, _) = head(abc(1, 1));
+    assert!(text.contains("Content-Type: text/plain; charset=utf-8"));
+    let (binary, body) = head(Config {
+        charset_bytes: Some(b"\xff\0".to_vec()),
+        ..abc(1, 1)
+    });
+    assert!(binary.contains("Content-Type: application/octet-stream"));
+    assert_eq!(body, b"\xff\n\0\n");
+}
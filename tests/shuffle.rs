@@ -0,0 +1,57 @@
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::shuffle::Permutation;
+use crunch_rs::Config;
+
+mod common;
+
+use common::words;
+
+#[test]
+fn permutations_are_bijections() {
+    for len in [1, 2, 3, 10, 17, 1000] {
+        let permutation = Permutation::new(len, 42);
+        let mut seen = vec![false; len as usize];
+        for index in 0..len {
+            let position = permutation.apply(index);
+            assert!(!seen[position as usize]);
+            seen[position as usize] = true;
+            assert_eq!(permutation.invert(position), index);
+        }
+    }
+}
+
+#[test]
+fn shuffled_output_covers_the_keyspace_once() {
+    let plain = Config {
+        min_len: 1,
+        max_len: 3,
+        charset: "abc".to_string(),
+        no_duplicates: true,
+        ..Default::default()
+    };
+    let shuffled = Config {
+        shuffle_seed: Some(5),
+        ..plain.clone()
+    };
+    let (mut expected, mut got) = (words(&plain), words(&shuffled));
+    assert_ne!(expected, got);
+    assert_eq!(got, words(&shuffled));
+    expected.sort();
+    got.sort();
+    assert_eq!(expected, got);
+    assert_ne!(words(&shuffled), words(&Config { shuffle_seed: Some(6), ..shuffled }));
+}
+
+#[test]
+fn ranks_follow_the_shuffled_order() {
+    let config = Config {
+        charset: "xy".to_string(),
+        template: Some("@%@".to_string()),
+        shuffle_seed: Some(1),
+        ..Default::default()
+    };
+    for (index, word) in words(&config).iter().enumerate() {
+        assert_eq!(rank(&config, word.as_bytes()).unwrap(), Some(index as u64));
+        assert_eq!(unrank(&config, index as u64).unwrap().as_deref(), Some(word.as_bytes()));
+    }
+}
@@ -0,0 +1,112 @@
+use crunch_rs::generator::Window;
+use crunch_rs::stream::{for_each_chunk, Words};
+use crunch_rs::Config;
+
+mod common;
+
+use common::byte_words;
+
+fn config(min_len: usize, max_len: usize, charset: &str) -> Config {
+    Config {
+        min_len,
+        max_len,
+        charset: charset.to_string(),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn iterates_the_same_words_as_the_writer() {
+    let config = config(1, 4, "abc1");
+    let words: Vec<Vec<u8>> = Words::new(config.clone()).map(Result::unwrap).collect();
+    assert_eq!(words, byte_words(&config));
+}
+
+#[test]
+fn large_keyspaces_span_many_batches() {
+    let config = config(5, 5, "abcdefghij");
+    assert_eq!(Words::new(config).count(), 100_000);
+}
+
+#[test]
+fn windows_and_filters_apply() {
+    let config = Config {
+        contains: vec!["a".to_string()],
+        ..config(2, 2, "abc")
+    };
+    let words: Vec<Vec<u8>> = Words::window(config, Window { skip: 1, limit: Some(2) })
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(words, [b"ab".to_vec(), b"ac".to_vec()]);
+}
+
+#[test]
+fn empty_word_is_kept() {
+    let words: Vec<Vec<u8>> = Words::new(config(0, 1, "a")).map(Result::unwrap).collect();
+    assert_eq!(words, [b"".to_vec(), b"a".to_vec()]);
+}
+
+#[test]
+fn errors_are_passed_on() {
+    let config = Config {
+        template: Some("a[b".to_string()),
+        ..config(1, 1, "a")
+    };
+    let mut words = Words::new(config);
+    assert!(words.next().unwrap().is_err());
+    assert!(words.next().is_none());
+}
+
+#[test]
+fn dropping_stops_the_generator() {
+    let mut words = Words::new(config(1, 12, "abcdefghijklmnopqrstuvwxyz"));
+    assert_eq!(words.next().unwrap().unwrap(), b"a");
+    drop(words);
+}
+
+#[test]
+fn chunks_cover_the_window_and_can_stop() {
+    let config = config(1, 3, "abc");
+    let mut chunks = Vec::new();
+    let words = for_each_chunk(&config, Window { skip: 2, limit: Some(10) }, 4, |chunk| {
+        chunks.push(chunk);
+        true
+    })
+    .unwrap();
+    assert_eq!(words, 10);
+    assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [4, 4, 2]);
+    assert_eq!(chunks.concat(), byte_words(&config)[2..12]);
+
+    let mut seen = 0;
+    let words = for_each_chunk(&config, Window::default(), 5, |chunk| {
+        seen += chunk.len();
+        seen < 10
+    })
+    .unwrap();
+    assert_eq!((words, seen), (10, 10));
+}
+
+#[test]
+fn words_may_hold_any_byte() {
+    let nul: Vec<Vec<u8>> = Words::new(config(0, 2, "\0")).map(Result::unwrap).collect();
+    assert_eq!(nul, [b"".to_vec(), b"\0".to_vec(), b"\0\0".to_vec()]);
+
+    let config = Config {
+        template: Some("*%".to_string()),
+        wordlist: vec![b"\xff\0".to_vec(), b"".to_vec()],
+        ..config(1, 3, "a")
+    };
+    let expected: Vec<Vec<u8>> = [b"\xff\0".as_slice(), b""]
+        .iter()
+        .flat_map(|word| (b'0'..=b'9').map(move |digit| [*word, &[digit]].concat()))
+        .collect();
+    assert_eq!(Words::new(config.clone()).map(Result::unwrap).collect::<Vec<_>>(), expected);
+
+    let mut chunks = Vec::new();
+    for_each_chunk(&config, Window::default(), 3, |chunk| {
+        chunks.extend(chunk);
+        true
+    })
+    .unwrap();
+    assert_eq!(chunks, expected);
+}
@@ -0,0 +1,95 @@
+use crunch_rs::classes::parse_requirements;
+use crunch_rs::mutate::Mutator;
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::calculate_size;
+use crunch_rs::suffix::{parse_numbers, suffixes};
+use crunch_rs::Config;
+
+mod common;
+
+use common::words;
+
+#[test]
+fn numbers_expand_ranges_and_lists() {
+    assert_eq!(parse_numbers("1999-2001").unwrap(), ["1999", "2000", "2001"]);
+    assert_eq!(parse_numbers("8-10, 00-01,7").unwrap(), ["8", "9", "10", "00", "01", "7"]);
+    for bad in ["", "5-3", "1-", "x", "-1", "0-99999999"] {
+        assert!(parse_numbers(bad).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn separators_add_to_the_plain_suffixes() {
+    let values = parse_numbers("1,1,2").unwrap();
+    assert_eq!(suffixes(&values, "_!"), ["1", "2", "_1", "_2", "!1", "!2"]);
+}
+
+#[test]
+fn every_word_gets_each_suffix() {
+    let config = Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "ab".to_string(),
+        suffixes: suffixes(&parse_numbers("2024-2025").unwrap(), "_")
+            .into_iter()
+            .map(String::into_bytes)
+            .collect(),
+        ..Config::default()
+    };
+    let suffixed = words(&config);
+    assert_eq!(&suffixed[..5], ["a2024", "a2025", "a_2024", "a_2025", "b2024"]);
+    assert_eq!(suffixed.len() as u64, calculate_size(&config).unwrap());
+    assert_eq!(suffixed.len(), 6 * 4);
+    assert_eq!(rank(&config, b"ab_2025").unwrap(), Some(15));
+    assert_eq!(unrank(&config, 15).unwrap().unwrap(), b"ab_2025");
+    assert_eq!(rank(&config, b"ab").unwrap(), None);
+}
+
+#[test]
+fn filters_count_the_whole_suffix() {
+    let base = Config {
+        min_len: 2,
+        max_len: 2,
+        charset: "ab".to_string(),
+        suffixes: vec![b"2000".to_vec(), b"2001".to_vec()],
+        ..Config::default()
+    };
+    for config in [
+        Config {
+            contains: vec!["00".to_string()],
+            ..base.clone()
+        },
+        Config {
+            require: parse_requirements("digit:4,lower:2").unwrap(),
+            ..base.clone()
+        },
+        Config {
+            contains: vec!["b20".to_string()],
+            shuffle_seed: Some(3),
+            ..base.clone()
+        },
+    ] {
+        config.validate().unwrap();
+        let count = words(&config).len();
+        assert_eq!(count, if config.shuffle_seed.is_some() { 4 } else { 8 });
+        assert_eq!(count as u64, calculate_size(&config).unwrap());
+    }
+    let short = Config {
+        min_len: 1,
+        max_len: 1,
+        contains: vec!["20".to_string()],
+        ..base
+    };
+    short.validate().unwrap();
+    assert_eq!(calculate_size(&short).unwrap(), 4);
+}
+
+#[test]
+fn mutate_appends_after_the_other_transforms() {
+    let mutator = Mutator {
+        leet: true,
+        suffixes: vec!["1".to_string(), "!".to_string()],
+        ..Mutator::default()
+    };
+    assert_eq!(mutator.mutate("to"), ["to1", "to!", "t01", "t0!", "7o1", "7o!", "701", "70!"]);
+}
@@ -0,0 +1,67 @@
+use crunch_rs::target::{wordlist, Target, TargetRules};
+
+fn rules() -> TargetRules {
+    TargetRules {
+        years: 2020..=2021,
+        numbers: 9,
+        specials: "!".to_string(),
+        leet: false,
+        min_len: 1,
+        max_len: 32,
+    }
+}
+
+#[test]
+fn answers_file_is_parsed_and_validated() {
+    let target = Target::parse("first-name = \"John\"\nbirthday = \"1985-07-04\"\nkeywords = [\"chelsea\"]\n").unwrap();
+    assert_eq!(target.first_name.as_deref(), Some("John"));
+    assert_eq!(target.keywords, ["chelsea"]);
+    assert!(Target::parse("birthday = \"1985-13-04\"\n").is_err());
+    assert!(Target::parse("favourite = \"x\"\n").is_err());
+}
+
+#[test]
+fn interactive_answers_skip_blanks_and_repeat_bad_dates() {
+    let answers = "Ann\n\n\nnot a date\n1990-02-03\n\n\n\n\n\n\nDog\n\nred, blue\n";
+    let mut prompts = Vec::new();
+    let target = Target::ask(answers.as_bytes(), &mut prompts).unwrap();
+    assert_eq!(target.first_name.as_deref(), Some("Ann"));
+    assert_eq!(target.surname, None);
+    assert_eq!(target.birthday.as_deref(), Some("1990-02-03"));
+    assert_eq!(target.pet.as_deref(), Some("Dog"));
+    assert_eq!(target.keywords, ["red", "blue"]);
+    assert!(String::from_utf8(prompts).unwrap().contains("invalid date"));
+}
+
+#[test]
+fn combines_names_cases_dates_and_suffixes() {
+    let target = Target {
+        first_name: Some("John".to_string()),
+        surname: Some("Smith".to_string()),
+        birthday: Some("1985-07-04".to_string()),
+        ..Target::default()
+    };
+    let words = wordlist(&target, &rules()).unwrap();
+    for expected in [
+        "john", "JOHN", "John", "johnsmith", "SmithJohn", "John1985", "john0407", "Smith2021", "john7", "John!",
+        "smith85!", "19850704",
+    ] {
+        assert!(words.contains(&expected.to_string()), "missing {}", expected);
+    }
+    let mut unique = words.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), words.len());
+}
+
+#[test]
+fn leet_and_length_limits() {
+    let target = Target {
+        pet: Some("Rex Dog".to_string()),
+        ..Target::default()
+    };
+    let words = wordlist(&target, &TargetRules { leet: true, min_len: 6, max_len: 6, ..rules() }).unwrap();
+    assert!(words.contains(&"r3xd0g".to_string()));
+    assert!(words.contains(&"rexdog".to_string()));
+    assert!(words.iter().all(|word| word.chars().count() == 6));
+}
@@ -0,0 +1,141 @@
+use crunch_rs::rank::{rank, unrank};
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+
+mod common;
+
+use common::words;
+
+#[test]
+fn custom_placeholders_draw_from_their_own_pools() {
+    let config = Config {
+        charset: "xyz".to_string(),
+        template: Some("p?1?2??".to_string()),
+        placeholders: [('1', "ab".to_string()), ('2', "!$".to_string())].into(),
+        ..Default::default()
+    };
+    assert_eq!(words(&config), ["pa!?", "pa$?", "pb!?", "pb$?"]);
+    assert_eq!(calculate_size(&config).unwrap(), 4);
+}
+
+#[test]
+fn undefined_placeholder_is_rejected() {
+    let config = Config {
+        template: Some("?3".to_string()),
+        ..Default::default()
+    };
+    assert!(calculate_size(&config).is_err());
+}
+
+#[test]
+fn optional_groups_expand_shortest_first() {
+    let config = Config {
+        charset: "ab".to_string(),
+        template: Some("p[%][%]".to_string()),
+        ..Default::default()
+    };
+    let expanded = words(&config);
+    assert_eq!(expanded.len(), 111);
+    assert_eq!(expanded[..3], ["p", "p0", "p1"]);
+    assert_eq!(expanded[10..12], ["p9", "p00"]);
+    assert_eq!(calculate_size(&config).unwrap(), 111);
+
+    let config = Config {
+        charset: "ab".to_string(),
+        template: Some("[x@]y[?[]".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(words(&config), ["y", "xay", "xby", "y[", "xay[", "xby["]);
+    assert_eq!(calculate_size(&config).unwrap(), 6);
+}
+
+#[test]
+fn malformed_optional_groups_are_rejected() {
+    for template in ["a[b", "a]b", "[[a]]", "a[]", "[a][a][a][a][a][a][a][a][a][a][a][a][a]"] {
+        let config = Config {
+            template: Some(template.to_string()),
+            ..Default::default()
+        };
+        assert!(calculate_size(&config).is_err(), "{}", template);
+    }
+}
+
+#[test]
+fn star_draws_whole_words_from_the_wordlist() {
+    let config = Config {
+        min_len: 3,
+        max_len: 5,
+        template: Some("*%?*".to_string()),
+        wordlist: vec![b"al".to_vec(), b"bob".to_vec()],
+        ..Default::default()
+    };
+    config.validate().unwrap();
+    let words = words(&config);
+    assert_eq!(words.len(), 20);
+    assert_eq!(words[..2], ["al0*", "al1*"]);
+    assert_eq!(words[10], "bob0*");
+    assert_eq!(calculate_size(&config).unwrap(), 20);
+    assert_eq!(rank(&config, b"bob7*").unwrap(), Some(17));
+    assert_eq!(unrank(&config, 17).unwrap().unwrap(), b"bob7*");
+
+    // Lengths count the characters of the words, not the positions.
+    assert!(Config { max_len: 4, ..config.clone() }.validate().is_err());
+    assert!(Config { wordlist: Vec::new(), ..config }.validate().is_err());
+}
+
+#[test]
+fn filters_see_the_wordlist_words_whole() {
+    let config = Config {
+        min_len: 2,
+        max_len: 4,
+        template: Some("%*".to_string()),
+        wordlist: vec![b"bob".to_vec(), b"al".to_vec(), b"aa".to_vec()],
+        ..Default::default()
+    };
+    let check = |config: Config, expected: &[&str]| {
+        config.validate().unwrap();
+        let words = words(&config);
+        assert_eq!(words.len() as u64, calculate_size(&config).unwrap());
+        assert_eq!(&words[..expected.len()], expected);
+        words.len()
+    };
+    let contains = Config {
+        contains: vec!["ob".to_string()],
+        ..config.clone()
+    };
+    assert_eq!(check(contains, &["0bob", "1bob"]), 10);
+    let lower = Config {
+        require: crunch_rs::classes::parse_requirements("lower:3").unwrap(),
+        ..config.clone()
+    };
+    assert_eq!(check(lower, &["0bob"]), 10);
+    let no_duplicates = Config {
+        no_duplicates: true,
+        ..config.clone()
+    };
+    assert_eq!(check(no_duplicates, &["0bob", "0al", "1bob"]), 20);
+    assert!(crunch_rs::charset::is_repeat(b"aa", b"a") && !crunch_rs::charset::is_repeat(b"b", b"ab"));
+
+    // The star in the middle and last position, pinned from either end.
+    let pinned = Config {
+        min_len: 3,
+        max_len: 5,
+        template: Some("%*%".to_string()),
+        starts_with: Some("1b".to_string()),
+        ends_with: Some("b2".to_string()),
+        ..config.clone()
+    };
+    assert_eq!(check(pinned, &["1bob2"]), 1);
+    let last = Config {
+        template: Some("*%".to_string()),
+        starts_with: Some("bo".to_string()),
+        ..config.clone()
+    };
+    assert_eq!(check(last, &["bob0", "bob1"]), 10);
+    let ends = Config {
+        template: Some("*%".to_string()),
+        ends_with: Some("l1".to_string()),
+        ..config
+    };
+    assert_eq!(check(ends, &["al1"]), 1);
+}
@@ -0,0 +1,56 @@
+use crunch_rs::generator::generate_words;
+use crunch_rs::progress::Progress;
+use crunch_rs::transform::{Emit, Rewritten, TransformCommand, Transformed};
+use crunch_rs::{Config, LineEnding};
+use std::io::Write;
+
+#[test]
+fn a_transform_rewrites_and_skips_words() {
+    let config = Config {
+        min_len: 1,
+        max_len: 2,
+        charset: "ab".to_string(),
+        ..Config::default()
+    };
+    let mut out = Vec::new();
+    let capitalize = |word: &mut Vec<u8>| {
+        if word.len() < 2 {
+            return Emit::Skip;
+        }
+        word[0].make_ascii_uppercase();
+        word.push(b'!');
+        Emit::Keep
+    };
+    let mut transformed = Transformed::new(&mut out, capitalize, LineEnding::Lf).unwrap();
+    generate_words(&config, &mut transformed, &Progress::new(0)).unwrap();
+    let counts = transformed.counts();
+    drop(transformed);
+    assert_eq!(out, b"Aa!\nAb!\nBa!\nBb!\n");
+    assert_eq!(
+        counts,
+        Rewritten {
+            words_in: 6,
+            bytes_in: 16,
+            words_out: 4,
+            bytes_out: 16,
+        }
+    );
+    assert!(Transformed::new(Vec::new(), |_: &mut Vec<u8>| Emit::Keep, LineEnding::None).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn a_command_rewrites_words_in_batches() {
+    let mut out = Vec::new();
+    let mut command = TransformCommand::new(&mut out, "sed 's/a/@/; /x/d'", 2, LineEnding::Crlf).unwrap();
+    command.write_all(b"pass\r\nx\r\nwo").unwrap();
+    command.write_all(b"rd\r\nab\r\n").unwrap();
+    command.flush().unwrap();
+    assert_eq!((command.counts().words_in, command.counts().words_out), (4, 3));
+    drop(command);
+    assert_eq!(out, b"p@ss\r\nword\r\n@b\r\n");
+
+    let mut failing = TransformCommand::new(Vec::new(), "cat; exit 3", 10, LineEnding::Lf).unwrap();
+    failing.write_all(b"word\n").unwrap();
+    assert!(failing.flush().is_err());
+}
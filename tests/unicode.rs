@@ -0,0 +1,62 @@
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+
+mod common;
+
+use common::words;
+
+fn config(min_len: usize, max_len: usize, charset: &str, template: Option<&str>) -> Config {
+    Config {
+        min_len,
+        max_len,
+        charset: charset.to_string(),
+        template: template.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn plain_mode_with_umlauts() {
+    let config = config(1, 2, "äö", None);
+    assert_eq!(words(&config), ["ä", "ö", "ää", "äö", "öä", "öö"]);
+    assert_eq!(calculate_size(&config).unwrap(), 6);
+}
+
+#[test]
+fn plain_mode_with_mixed_width_symbols() {
+    let config = config(2, 2, "a€😀", None);
+    let words = words(&config);
+    assert_eq!(
+        words,
+        ["aa", "a€", "a😀", "€a", "€€", "€😀", "😀a", "😀€", "😀😀"]
+    );
+    assert_eq!(calculate_size(&config).unwrap(), words.len() as u64);
+}
+
+#[test]
+fn plain_mode_no_duplicates_with_cyrillic() {
+    let mut config = config(2, 3, "пр", None);
+    config.no_duplicates = true;
+    let words = words(&config);
+    assert_eq!(words, ["пр", "рп", "прп", "рпр"]);
+    assert_eq!(calculate_size(&config).unwrap(), words.len() as u64);
+}
+
+#[test]
+fn template_mode_with_multibyte_literals_and_charset() {
+    let config = config(1, 1, "жё", Some("ß@%"));
+    let words = words(&config);
+    assert_eq!(words.len(), 20);
+    assert_eq!(words[0], "ßж0");
+    assert_eq!(words[19], "ßё9");
+    assert_eq!(calculate_size(&config).unwrap(), 20);
+}
+
+#[test]
+fn template_mode_no_duplicates_with_emoji() {
+    let mut config = config(1, 1, "😀🎉", Some("😀@@"));
+    config.no_duplicates = true;
+    let words = words(&config);
+    assert_eq!(words, ["😀🎉😀"]);
+    assert_eq!(calculate_size(&config).unwrap(), 1);
+}
@@ -0,0 +1,117 @@
+use crunch_rs::charset::{repeated_symbols, Charset};
+use crunch_rs::error::{Error, EXIT_CONFIG, EXIT_INPUT, EXIT_OUTPUT};
+use crunch_rs::input::open_input;
+use crunch_rs::output::check_writable;
+use crunch_rs::size::calculate_size;
+use crunch_rs::Config;
+use std::io;
+use std::path::Path;
+
+fn config(min_len: usize, max_len: usize, charset: &str) -> Config {
+    Config {
+        min_len,
+        max_len,
+        charset: charset.to_string(),
+        ..Config::default()
+    }
+}
+
+fn rejection(config: &Config) -> String {
+    match config.validate() {
+        Err(err @ Error::Config(_)) => err.to_string(),
+        other => panic!("expected a config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn valid_keyspaces_pass() {
+    config(1, 3, "abc").validate().unwrap();
+    config(0, 0, "").validate().unwrap();
+    Config {
+        template: Some("pass[%]".to_string()),
+        ..config(4, 5, "abc")
+    }
+    .validate()
+    .unwrap();
+}
+
+#[test]
+fn lengths_and_empty_charsets_are_rejected() {
+    assert!(rejection(&config(3, 1, "abc")).contains("greater than <max_len>"));
+    assert!(rejection(&config(1, 2, "")).contains("no characters"));
+    let excluded = Config {
+        exclude_chars: "abc".to_string(),
+        ..config(1, 2, "abc")
+    };
+    assert!(rejection(&excluded).contains("--exclude-chars"));
+}
+
+#[test]
+fn templates_must_match_the_lengths() {
+    let template = |template: &str, min_len, max_len| Config {
+        template: Some(template.to_string()),
+        ..config(min_len, max_len, "abc")
+    };
+    assert!(rejection(&template("pass%%", 0, 0)).ends_with("use 6 6"));
+    assert!(rejection(&template("a[b][c]", 1, 2)).contains("words of 1 to 3 characters"));
+    let empty_position = Config {
+        exclude_chars: "x".to_string(),
+        ..template("@x", 2, 2)
+    };
+    assert!(rejection(&empty_position).contains("position 2"));
+    let mut unused = config(1, 1, "a");
+    unused.placeholders.insert('1', "xy".to_string());
+    assert!(rejection(&unused).contains("--set"));
+}
+
+#[test]
+fn unreachable_filters_are_rejected() {
+    let contains = Config {
+        contains: vec!["abcd".to_string()],
+        ..config(1, 3, "abcd")
+    };
+    assert!(rejection(&contains).contains("longer than the longest word"));
+    let prefix = Config {
+        starts_with: Some("z".to_string()),
+        ..config(1, 3, "abc")
+    };
+    assert!(rejection(&prefix).contains("--startswith"));
+    let entropy = Config {
+        min_entropy: Some(3.0),
+        max_entropy: Some(1.0),
+        ..config(1, 3, "abc")
+    };
+    assert!(rejection(&entropy).contains("--min-entropy"));
+}
+
+#[test]
+fn repeated_characters_count_once() {
+    assert_eq!(repeated_symbols("abcabca").unwrap(), [b"a", b"b", b"c"]);
+    assert!(repeated_symbols("abc").unwrap().is_empty());
+    assert_eq!(Charset::from_chars("aab").len(), 2);
+    assert_eq!(calculate_size(&config(2, 2, "aab")).unwrap(), 4);
+}
+
+#[test]
+fn errors_survive_a_trip_through_io_error() {
+    let err: io::Error = Error::config("bad settings").into();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = Error::from(err);
+    assert_eq!(err.exit_code(), EXIT_CONFIG);
+    assert_eq!(err.to_string(), "bad settings");
+
+    let missing = open_input("/nonexistent/wordlist.txt", 1024).err().unwrap();
+    let err = Error::from(missing);
+    assert_eq!(err.exit_code(), EXIT_INPUT);
+    assert!(err.to_string().starts_with("cannot read /nonexistent/wordlist.txt: "));
+}
+
+#[test]
+fn unwritable_outputs_are_caught_early() {
+    let dir = std::env::temp_dir();
+    let exit_code = |result: io::Result<()>| Error::from(result.unwrap_err()).exit_code();
+    assert_eq!(exit_code(check_writable(&dir, false)), EXIT_OUTPUT);
+    assert_eq!(exit_code(check_writable(Path::new("/nonexistent/out.txt"), false)), EXIT_OUTPUT);
+    check_writable(&dir.join("crunch-rs-validate-test.txt"), false).unwrap();
+    check_writable(&dir.join("crunch-rs-validate-chunks"), true).unwrap();
+}
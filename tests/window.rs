@@ -0,0 +1,77 @@
+use crunch_rs::generator::{generate_words, generate_words_window, Window};
+use crunch_rs::progress::Progress;
+use crunch_rs::Config;
+
+fn lines(out: Vec<u8>) -> Vec<String> {
+    String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn windows_match_slices_of_the_full_output() {
+    let configs = [
+        Config {
+            min_len: 1,
+            max_len: 3,
+            charset: "abc".to_string(),
+            ..Default::default()
+        },
+        Config {
+            min_len: 2,
+            max_len: 3,
+            charset: "abcd".to_string(),
+            no_duplicates: true,
+            exclude_substrings: vec!["bc".to_string()],
+            ..Default::default()
+        },
+        Config {
+            charset: "xy".to_string(),
+            template: Some("@%@".to_string()),
+            max_char_count: Some(1),
+            ..Default::default()
+        },
+        Config {
+            min_len: 2,
+            max_len: 4,
+            charset: "abc".to_string(),
+            min_entropy: Some(2.5),
+            ..Default::default()
+        },
+        Config {
+            min_len: 2,
+            max_len: 4,
+            charset: "abc".to_string(),
+            contains: vec!["ba".to_string()],
+            ..Default::default()
+        },
+        Config {
+            min_len: 1,
+            max_len: 3,
+            charset: "abc".to_string(),
+            shuffle_seed: Some(3),
+            ..Default::default()
+        },
+        Config {
+            min_len: 1,
+            max_len: 3,
+            charset: "abc".to_string(),
+            exclude_substrings: vec!["ab".to_string()],
+            shuffle_seed: Some(3),
+            ..Default::default()
+        },
+    ];
+    for config in &configs {
+        let mut out = Vec::new();
+        generate_words(config, &mut out, &Progress::new(0)).unwrap();
+        let all = lines(out);
+        for skip in [0, 1, 2, 5, 13, all.len() - 1, all.len(), all.len() + 3] {
+            for limit in [None, Some(0), Some(1), Some(4), Some(30)] {
+                let mut window = Window { skip: skip as u64, limit };
+                let mut out = Vec::new();
+                generate_words_window(config, &mut out, &Progress::new(0), &mut window).unwrap();
+                let start = skip.min(all.len());
+                let end = limit.map_or(all.len(), |limit| (start + limit as usize).min(all.len()));
+                assert_eq!(lines(out), all[start..end], "skip {} limit {:?}", skip, limit);
+            }
+        }
+    }
+}